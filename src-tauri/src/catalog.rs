@@ -0,0 +1,663 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use crate::jar_install::InstallResult;
+use crate::jar_scan::JarScanner;
+use crate::lockfile::{ExtensionKind, ExtensionSource, LockEntry, Lockfile};
+use crate::mods::ModManager;
+use crate::plugins::PluginManager;
+use crate::ServerType;
+
+pub const DEFAULT_MODRINTH_URL: &str = "https://api.modrinth.com/v2";
+pub const DEFAULT_HANGAR_URL: &str = "https://hangar.papermc.io/api/v1";
+
+const SEARCH_LIMIT: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CatalogSource {
+    Modrinth,
+    Hangar,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogProject {
+    pub source: CatalogSource,
+    /// The Modrinth project id or the Hangar slug.
+    pub id: String,
+    pub slug: String,
+    pub name: String,
+    pub description: String,
+    pub author: String,
+    pub downloads: u64,
+}
+
+/// A required dependency of a catalog version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogDependency {
+    pub project: String,
+    /// Modrinth dependencies can pin an exact version.
+    pub version_id: Option<String>,
+}
+
+/// A downloadable version of a catalog project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogVersion {
+    pub source: CatalogSource,
+    pub project: String,
+    /// The Modrinth version id, or the version name on Hangar.
+    pub version_id: String,
+    pub version_number: String,
+    pub file_name: String,
+    pub url: String,
+    /// Hex SHA-512 on Modrinth, SHA-256 on Hangar.
+    pub hash: Option<String>,
+    pub dependencies: Vec<CatalogDependency>,
+    /// Required dependencies hosted elsewhere, which have to be installed by hand.
+    pub external_dependencies: Vec<String>,
+}
+
+impl CatalogVersion {
+    pub fn extension_source(&self) -> ExtensionSource {
+        match self.source {
+            CatalogSource::Modrinth => ExtensionSource::Modrinth {
+                project_id: self.project.clone(),
+                version_id: self.version_id.clone(),
+                url: self.url.clone(),
+                file_name: self.file_name.clone(),
+            },
+            CatalogSource::Hangar => ExtensionSource::Hangar {
+                project: self.project.clone(),
+                version: self.version_id.clone(),
+                url: self.url.clone(),
+                file_name: self.file_name.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailableUpdate {
+    pub kind: ExtensionKind,
+    pub name: String,
+    pub current_version: String,
+    pub latest: CatalogVersion,
+}
+
+/// The catalog project an installed jar came from, with the version id it was.
+fn catalog_origin(source: &ExtensionSource) -> Option<(CatalogSource, &str, &str)> {
+    match source {
+        ExtensionSource::Modrinth { project_id, version_id, .. } => Some((CatalogSource::Modrinth, project_id, version_id)),
+        ExtensionSource::Hangar { project, version, .. } => Some((CatalogSource::Hangar, project, version)),
+        ExtensionSource::Url { .. } | ExtensionSource::File { .. } | ExtensionSource::Modpack { .. } => None,
+    }
+}
+
+/// Modrinth loader names whose jars run on `server_type`.
+fn modrinth_loaders(server_type: ServerType) -> Result<&'static [&'static str], String> {
+    Ok(match server_type {
+        ServerType::Paper => &["paper", "spigot", "bukkit"],
+        ServerType::Spigot => &["spigot", "bukkit"],
+        ServerType::Fabric => &["fabric"],
+        ServerType::Quilt => &["quilt", "fabric"],
+        ServerType::Forge => &["forge"],
+        ServerType::NeoForge => &["neoforge"],
+        ServerType::Vanilla => return Err("Vanilla servers don't load plugins or mods".to_string()),
+    })
+}
+
+/// Hangar only hosts plugins for Paper and the proxies.
+fn hangar_platform(server_type: ServerType) -> Result<&'static str, String> {
+    match server_type {
+        ServerType::Paper => Ok("PAPER"),
+        other => Err(format!("Hangar has no plugins for {:?} servers", other)),
+    }
+}
+
+#[derive(Deserialize)]
+struct ModrinthSearch {
+    hits: Vec<ModrinthHit>,
+}
+
+#[derive(Deserialize)]
+struct ModrinthHit {
+    project_id: String,
+    slug: String,
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    author: String,
+    #[serde(default)]
+    downloads: u64,
+}
+
+#[derive(Deserialize)]
+struct ModrinthVersion {
+    id: String,
+    project_id: String,
+    version_number: String,
+    files: Vec<ModrinthFile>,
+    #[serde(default)]
+    dependencies: Vec<ModrinthDependency>,
+}
+
+#[derive(Deserialize)]
+struct ModrinthFile {
+    url: String,
+    filename: String,
+    #[serde(default)]
+    primary: bool,
+    #[serde(default)]
+    hashes: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct ModrinthDependency {
+    project_id: Option<String>,
+    version_id: Option<String>,
+    dependency_type: String,
+}
+
+impl ModrinthVersion {
+    fn into_catalog(self) -> Result<CatalogVersion, String> {
+        let index = self.files.iter().position(|f| f.primary).unwrap_or(0);
+        let file = self
+            .files
+            .into_iter()
+            .nth(index)
+            .ok_or_else(|| format!("Version {} has no files", self.version_number))?;
+        let dependencies = self
+            .dependencies
+            .into_iter()
+            .filter(|d| d.dependency_type == "required")
+            .filter_map(|d| {
+                Some(CatalogDependency {
+                    project: d.project_id.clone().or_else(|| d.version_id.clone())?,
+                    version_id: d.version_id,
+                })
+            })
+            .collect();
+        Ok(CatalogVersion {
+            source: CatalogSource::Modrinth,
+            project: self.project_id,
+            version_id: self.id,
+            version_number: self.version_number,
+            file_name: file.filename,
+            url: file.url,
+            hash: file.hashes.get("sha512").cloned(),
+            dependencies,
+            external_dependencies: Vec::new(),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct HangarPage<T> {
+    result: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct HangarNamespace {
+    owner: String,
+    slug: String,
+}
+
+#[derive(Deserialize, Default)]
+struct HangarStats {
+    downloads: u64,
+}
+
+#[derive(Deserialize)]
+struct HangarProject {
+    name: String,
+    namespace: HangarNamespace,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    stats: HangarStats,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HangarFileInfo {
+    name: String,
+    sha256_hash: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HangarDownload {
+    file_info: Option<HangarFileInfo>,
+    external_url: Option<String>,
+    download_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HangarDependency {
+    name: String,
+    required: bool,
+    external_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HangarVersion {
+    name: String,
+    downloads: HashMap<String, HangarDownload>,
+    #[serde(default)]
+    plugin_dependencies: HashMap<String, Vec<HangarDependency>>,
+}
+
+/// Searches and downloads plugins and mods from Modrinth and Hangar.
+pub struct Catalog {
+    client: reqwest::Client,
+    modrinth_url: String,
+    hangar_url: String,
+    scanner: JarScanner,
+}
+
+impl Default for Catalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Catalog {
+            // Both APIs ask clients to identify themselves.
+            client: reqwest::Client::builder()
+                .user_agent(concat!("mcsm/", env!("CARGO_PKG_VERSION")))
+                .build()
+                .unwrap_or_default(),
+            modrinth_url: DEFAULT_MODRINTH_URL.to_string(),
+            hangar_url: DEFAULT_HANGAR_URL.to_string(),
+            scanner: JarScanner::new(),
+        }
+    }
+
+    pub fn with_modrinth_url(mut self, url: &str) -> Self {
+        self.modrinth_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_hangar_url(mut self, url: &str) -> Self {
+        self.hangar_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// The scanner installs go through.
+    pub fn with_scanner(mut self, scanner: JarScanner) -> Self {
+        self.scanner = scanner;
+        self
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str, query: &[(&str, String)]) -> Result<T, String> {
+        let response = self
+            .client
+            .get(url)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach {}: {}", url, e))?;
+        if !response.status().is_success() {
+            return Err(format!("{} returned HTTP {}", url, response.status()));
+        }
+        response.json().await.map_err(|e| format!("Unexpected response from {}: {}", url, e))
+    }
+
+    /// Projects matching `query` that have versions for this server's loader and
+    /// Minecraft version.
+    pub async fn search(&self, source: CatalogSource, query: &str, server_type: ServerType, minecraft_version: &str) -> Result<Vec<CatalogProject>, String> {
+        match source {
+            CatalogSource::Modrinth => {
+                let loaders: Vec<String> = modrinth_loaders(server_type)?.iter().map(|l| format!("categories:{}", l)).collect();
+                let facets = serde_json::json!([loaders, [format!("versions:{}", minecraft_version)]]);
+                let search: ModrinthSearch = self
+                    .get_json(
+                        &format!("{}/search", self.modrinth_url),
+                        &[("query", query.to_string()), ("facets", facets.to_string()), ("limit", SEARCH_LIMIT.to_string())],
+                    )
+                    .await?;
+                Ok(search
+                    .hits
+                    .into_iter()
+                    .map(|hit| CatalogProject {
+                        source,
+                        id: hit.project_id,
+                        slug: hit.slug,
+                        name: hit.title,
+                        description: hit.description,
+                        author: hit.author,
+                        downloads: hit.downloads,
+                    })
+                    .collect())
+            }
+            CatalogSource::Hangar => {
+                let page: HangarPage<HangarProject> = self
+                    .get_json(
+                        &format!("{}/projects", self.hangar_url),
+                        &[
+                            ("query", query.to_string()),
+                            ("platform", hangar_platform(server_type)?.to_string()),
+                            ("version", minecraft_version.to_string()),
+                            ("limit", SEARCH_LIMIT.to_string()),
+                        ],
+                    )
+                    .await?;
+                Ok(page
+                    .result
+                    .into_iter()
+                    .map(|project| CatalogProject {
+                        source,
+                        id: project.namespace.slug.clone(),
+                        slug: project.namespace.slug,
+                        name: project.name,
+                        description: project.description,
+                        author: project.namespace.owner,
+                        downloads: project.stats.downloads,
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// The newest version of `project` that runs on this server, if any.
+    pub async fn latest_version(&self, source: CatalogSource, project: &str, server_type: ServerType, minecraft_version: &str) -> Result<Option<CatalogVersion>, String> {
+        match source {
+            CatalogSource::Modrinth => {
+                let loaders = serde_json::to_string(modrinth_loaders(server_type)?).map_err(|e| e.to_string())?;
+                let versions: Vec<ModrinthVersion> = self
+                    .get_json(
+                        &format!("{}/project/{}/version", self.modrinth_url, project),
+                        &[("loaders", loaders), ("game_versions", serde_json::json!([minecraft_version]).to_string())],
+                    )
+                    .await?;
+                versions.into_iter().next().map(ModrinthVersion::into_catalog).transpose()
+            }
+            CatalogSource::Hangar => {
+                let platform = hangar_platform(server_type)?;
+                let page: HangarPage<HangarVersion> = self
+                    .get_json(
+                        &format!("{}/projects/{}/versions", self.hangar_url, project),
+                        &[
+                            ("platform", platform.to_string()),
+                            ("platformVersion", minecraft_version.to_string()),
+                            ("limit", "1".to_string()),
+                        ],
+                    )
+                    .await?;
+                let Some(mut version) = page.result.into_iter().next() else {
+                    return Ok(None);
+                };
+                let download = version
+                    .downloads
+                    .remove(platform)
+                    .ok_or_else(|| format!("{} {} has no {} download", project, version.name, platform))?;
+                let (Some(url), Some(file)) = (download.download_url, download.file_info) else {
+                    return Err(format!(
+                        "{} {} is only available from {}",
+                        project,
+                        version.name,
+                        download.external_url.unwrap_or_else(|| "an external site".to_string())
+                    ));
+                };
+
+                let mut dependencies = Vec::new();
+                let mut external_dependencies = Vec::new();
+                for dep in version.plugin_dependencies.remove(platform).unwrap_or_default() {
+                    match (dep.required, dep.external_url) {
+                        (false, _) => {}
+                        (true, Some(url)) => external_dependencies.push(format!("{} ({})", dep.name, url)),
+                        (true, None) => dependencies.push(CatalogDependency { project: dep.name, version_id: None }),
+                    }
+                }
+                Ok(Some(CatalogVersion {
+                    source,
+                    project: project.to_string(),
+                    version_id: version.name.clone(),
+                    version_number: version.name,
+                    file_name: file.name,
+                    url,
+                    hash: file.sha256_hash,
+                    dependencies,
+                    external_dependencies,
+                }))
+            }
+        }
+    }
+
+    async fn modrinth_version(&self, version_id: &str) -> Result<CatalogVersion, String> {
+        let version: ModrinthVersion = self
+            .get_json(&format!("{}/version/{}", self.modrinth_url, version_id), &[])
+            .await?;
+        version.into_catalog()
+    }
+
+    /// `project`'s newest compatible version followed by the required dependencies
+    /// that aren't installed yet, ordered so dependencies come first.
+    async fn resolve(&self, server_path: &PathBuf, source: CatalogSource, project: &str, server_type: ServerType, minecraft_version: &str) -> Result<Vec<CatalogVersion>, String> {
+        let lockfile = Lockfile::load(server_path).await?;
+        let installed_from_catalog: HashSet<(CatalogSource, String)> = lockfile
+            .entries
+            .iter()
+            .filter_map(|e| catalog_origin(&e.source).map(|(s, p, _)| (s, p.to_string())))
+            .collect();
+        let mut installed_plugins = PluginManager::new(server_path);
+        if server_type == ServerType::Paper || server_type == ServerType::Spigot {
+            installed_plugins.load_plugins().await?;
+        }
+
+        let mut versions: BTreeMap<String, CatalogVersion> = BTreeMap::new();
+        let mut queue = vec![(project.to_string(), None::<String>)];
+        while let Some((next, version_id)) = queue.pop() {
+            if versions.contains_key(&next) {
+                continue;
+            }
+            let version = match version_id {
+                Some(id) => self.modrinth_version(&id).await?,
+                None => self
+                    .latest_version(source, &next, server_type, minecraft_version)
+                    .await?
+                    .ok_or_else(|| format!("{} has no version for {:?} {}", next, server_type, minecraft_version))?,
+            };
+            for dep in &version.dependencies {
+                // Hangar names dependencies after the plugin, which may have been
+                // installed by hand.
+                let installed = installed_from_catalog.contains(&(source, dep.project.clone()))
+                    || (source == CatalogSource::Hangar && installed_plugins.get_plugin(&dep.project).is_some());
+                if !installed {
+                    queue.push((dep.project.clone(), dep.version_id.clone()));
+                }
+            }
+            versions.insert(next, version);
+        }
+
+        // Depth-first so every version comes after its dependencies.
+        fn visit(name: &str, versions: &BTreeMap<String, CatalogVersion>, done: &mut HashSet<String>, order: &mut Vec<CatalogVersion>) {
+            if !done.insert(name.to_string()) {
+                return;
+            }
+            let Some(version) = versions.get(name) else {
+                return;
+            };
+            for dep in &version.dependencies {
+                visit(&dep.project, versions, done, order);
+            }
+            order.push(version.clone());
+        }
+        let mut order = Vec::new();
+        let mut done = HashSet::new();
+        visit(project, &versions, &mut done, &mut order);
+        Ok(order)
+    }
+
+    /// Installs `project` and any required dependencies that are missing. Returns one
+    /// result per jar, dependencies first.
+    pub async fn install(&self, server_path: &PathBuf, source: CatalogSource, project: &str, server_type: ServerType, minecraft_version: &str) -> Result<Vec<InstallResult>, String> {
+        let mut results = Vec::new();
+        for version in self.resolve(server_path, source, project, server_type, minecraft_version).await? {
+            let mut result = install_version(server_path, &version, server_type, minecraft_version, &self.scanner).await?;
+            for external in &version.external_dependencies {
+                result.outcome.warnings.push(format!("{} needs {}, which has to be installed by hand", result.name, external));
+            }
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// The newest compatible version of a locked jar, when it differs from the
+    /// installed one. Jars not installed from a catalog have no updates.
+    pub async fn update_for(&self, entry: &LockEntry, server_type: ServerType, minecraft_version: &str) -> Result<Option<CatalogVersion>, String> {
+        let Some((source, project, installed)) = catalog_origin(&entry.source) else {
+            return Ok(None);
+        };
+        let latest = self.latest_version(source, project, server_type, minecraft_version).await?;
+        Ok(latest.filter(|v| v.version_id != installed))
+    }
+
+    /// Updates available for the plugins and mods installed from a catalog.
+    pub async fn check_updates(&self, server_path: &PathBuf, server_type: ServerType, minecraft_version: &str) -> Result<Vec<AvailableUpdate>, String> {
+        let lockfile = Lockfile::load(server_path).await?;
+        let mut updates = Vec::new();
+        for entry in &lockfile.entries {
+            if let Some(latest) = self.update_for(entry, server_type, minecraft_version).await? {
+                updates.push(AvailableUpdate {
+                    kind: entry.kind,
+                    name: entry.name.clone(),
+                    current_version: entry.version.clone(),
+                    latest,
+                });
+            }
+        }
+        Ok(updates)
+    }
+}
+
+/// Installs a catalog version as a plugin or mod, depending on the server type.
+pub async fn install_version(server_path: &PathBuf, version: &CatalogVersion, server_type: ServerType, minecraft_version: &str, scanner: &JarScanner) -> Result<InstallResult, String> {
+    let source = version.extension_source();
+    match server_type {
+        ServerType::Paper | ServerType::Spigot => {
+            PluginManager::new(server_path)
+                .with_scanner(scanner.clone())
+                .install_plugin(&source, version.hash.as_deref(), server_type, minecraft_version)
+                .await
+        }
+        _ => {
+            ModManager::new(server_path, server_type)
+                .with_scanner(scanner.clone())
+                .install_mod(&source, version.hash.as_deref())
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use mockito::Matcher;
+    use crate::lockfile::{sha256_hex, sha512_hex};
+
+    const SHOPKEEPER: &[u8] = include_bytes!("../tests/fixtures/plugins/ShopKeeper.jar");
+
+    fn plugin_jar(name: &str, version: &str) -> Vec<u8> {
+        let mut jar = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        jar.start_file("plugin.yml", zip::write::FileOptions::default()).unwrap();
+        write!(jar, "name: {}\nversion: '{}'\nmain: a.{}\napi-version: '1.20'\n", name, version, name).unwrap();
+        jar.finish().unwrap().into_inner()
+    }
+
+    fn modrinth_version(url: &str, id: &str, project: &str, file: &str, bytes: &[u8], depends_on: Option<&str>) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "project_id": project,
+            "version_number": id,
+            "files": [{ "url": format!("{}/cdn/{}", url, file), "filename": file, "primary": true, "hashes": { "sha512": sha512_hex(bytes) } }],
+            "dependencies": depends_on
+                .map(|p| vec![serde_json::json!({ "project_id": p, "version_id": null, "dependency_type": "required" })])
+                .unwrap_or_default(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_catalog_install_and_update() {
+        let server_path = std::env::temp_dir().join("mcsm_test_catalog");
+        let _ = std::fs::remove_dir_all(&server_path);
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+        let catalog = Catalog::new().with_modrinth_url(&url).with_hangar_url(&format!("{}/hangar/", url));
+        let luckperms_old = plugin_jar("LuckPerms", "5.4");
+        let luckperms_new = plugin_jar("LuckPerms", "5.5");
+
+        server.mock("GET", "/search")
+            .match_query(Matcher::UrlEncoded("facets".into(), r#"[["categories:paper","categories:spigot","categories:bukkit"],["versions:1.20.4"]]"#.into()))
+            .with_body(r#"{"hits":[{"project_id":"sk","slug":"shopkeeper","title":"ShopKeeper","author":"Alex","downloads":12}]}"#)
+            .create_async().await;
+        server.mock("GET", "/project/sk/version")
+            .match_query(Matcher::UrlEncoded("game_versions".into(), r#"["1.20.4"]"#.into()))
+            .with_body(serde_json::json!([modrinth_version(&url, "sk1", "sk", "ShopKeeper.jar", SHOPKEEPER, Some("lp"))]).to_string())
+            .create_async().await;
+        let old_versions = server.mock("GET", "/project/lp/version")
+            .match_query(Matcher::Any)
+            .with_body(serde_json::json!([modrinth_version(&url, "lp1", "lp", "LuckPerms-5.4.jar", &luckperms_old, None)]).to_string())
+            .create_async().await;
+        server.mock("GET", "/cdn/ShopKeeper.jar").with_body(SHOPKEEPER).create_async().await;
+        server.mock("GET", "/cdn/LuckPerms-5.4.jar").with_body(&luckperms_old).create_async().await;
+        server.mock("GET", "/cdn/LuckPerms-5.5.jar").with_body(&luckperms_new).create_async().await;
+
+        let found = catalog.search(CatalogSource::Modrinth, "shop", ServerType::Paper, "1.20.4").await.unwrap();
+        assert_eq!(found[0].slug, "shopkeeper");
+        assert!(catalog.search(CatalogSource::Modrinth, "shop", ServerType::Vanilla, "1.20.4").await.is_err());
+
+        let results = catalog.install(&server_path, CatalogSource::Modrinth, "sk", ServerType::Paper, "1.20.4").await.unwrap();
+        let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["LuckPerms", "ShopKeeper"]);
+        assert!(results[1].outcome.warnings.is_empty());
+        let lockfile = Lockfile::load(&server_path).await.unwrap();
+        assert!(matches!(
+            &lockfile.get(ExtensionKind::Plugin, "ShopKeeper").unwrap().source,
+            ExtensionSource::Modrinth { version_id, .. } if version_id == "sk1"
+        ));
+
+        let mut manager = PluginManager::new(&server_path);
+        assert!(manager.check_updates(&catalog, ServerType::Paper, "1.20.4").await.unwrap().is_empty());
+        old_versions.remove_async().await;
+        server.mock("GET", "/project/lp/version")
+            .match_query(Matcher::Any)
+            .with_body(serde_json::json!([modrinth_version(&url, "lp2", "lp", "LuckPerms-5.5.jar", &luckperms_new, None)]).to_string())
+            .create_async().await;
+        let updates = manager.check_updates(&catalog, ServerType::Paper, "1.20.4").await.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!((updates[0].name.as_str(), updates[0].latest.version_id.as_str()), ("LuckPerms", "lp2"));
+        let updated = manager.update_plugin("LuckPerms", &catalog, ServerType::Paper, "1.20.4").await.unwrap();
+        assert_eq!(updated.upgraded_from.as_deref(), Some("5.4"));
+        assert!(server_path.join("plugins/LuckPerms-5.5.jar").exists());
+        assert!(!server_path.join("plugins/LuckPerms-5.4.jar").exists());
+        assert_eq!(manager.update_plugin("LuckPerms", &catalog, ServerType::Paper, "1.20.4").await.unwrap_err(), "LuckPerms is up to date");
+
+        let chat = plugin_jar("Chat", "2.0");
+        server.mock("GET", "/hangar/projects/Chat/versions")
+            .match_query(Matcher::UrlEncoded("platform".into(), "PAPER".into()))
+            .with_body(serde_json::json!({ "result": [{
+                "name": "2.0",
+                "downloads": { "PAPER": {
+                    "fileInfo": { "name": "Chat-2.0.jar", "sha256Hash": sha256_hex(&chat) },
+                    "downloadUrl": format!("{}/cdn/Chat-2.0.jar", url),
+                }},
+                "pluginDependencies": { "PAPER": [
+                    { "name": "LuckPerms", "required": true },
+                    { "name": "ProtocolLib", "required": true, "externalUrl": "https://example.com/protocollib" },
+                ]},
+            }]}).to_string())
+            .create_async().await;
+        server.mock("GET", "/cdn/Chat-2.0.jar").with_body(&chat).create_async().await;
+        let results = catalog.install(&server_path, CatalogSource::Hangar, "Chat", ServerType::Paper, "1.20.4").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].outcome.warnings, ["Chat needs ProtocolLib (https://example.com/protocollib), which has to be installed by hand"]);
+        assert!(catalog.install(&server_path, CatalogSource::Hangar, "Chat", ServerType::Fabric, "1.20.4").await.is_err());
+    }
+}
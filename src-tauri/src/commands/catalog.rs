@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+use tauri::Runtime;
+use crate::catalog::{AvailableUpdate, Catalog, CatalogProject, CatalogSource};
+use crate::jar_install::InstallResult;
+use crate::lockfile::ExtensionKind;
+use crate::mods::ModManager;
+use crate::plugins::PluginManager;
+use super::scan::jar_scanner;
+use super::server::{is_server_running, registered_server};
+
+fn catalog(modrinth_url: Option<String>, hangar_url: Option<String>) -> Catalog {
+    let mut catalog = Catalog::new();
+    if let Some(url) = modrinth_url {
+        catalog = catalog.with_modrinth_url(&url);
+    }
+    if let Some(url) = hangar_url {
+        catalog = catalog.with_hangar_url(&url);
+    }
+    catalog
+}
+
+/// Projects that have a version for the server's loader and Minecraft version.
+#[tauri::command]
+pub async fn search_catalog<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    source: CatalogSource,
+    query: String,
+    modrinth_url: Option<String>,
+    hangar_url: Option<String>,
+) -> Result<Vec<CatalogProject>, String> {
+    let server = registered_server(&_app, &server_path).await?;
+    catalog(modrinth_url, hangar_url)
+        .search(source, &query, server.server_type, &server.version)
+        .await
+}
+
+/// Installs a project with its missing required dependencies.
+#[tauri::command]
+pub async fn install_from_catalog<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    source: CatalogSource,
+    project: String,
+    modrinth_url: Option<String>,
+    hangar_url: Option<String>,
+) -> Result<Vec<InstallResult>, String> {
+    let server = registered_server(&_app, &server_path).await?;
+    let mut results = catalog(modrinth_url, hangar_url)
+        .with_scanner(jar_scanner(&_app).await?)
+        .install(&server_path, source, &project, server.server_type, &server.version)
+        .await?;
+    let running = is_server_running(&server_path);
+    for result in &mut results {
+        result.outcome.restart_required &= running;
+    }
+    Ok(results)
+}
+
+#[tauri::command]
+pub async fn check_updates<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    modrinth_url: Option<String>,
+    hangar_url: Option<String>,
+) -> Result<Vec<AvailableUpdate>, String> {
+    let server = registered_server(&_app, &server_path).await?;
+    catalog(modrinth_url, hangar_url)
+        .check_updates(&server_path, server.server_type, &server.version)
+        .await
+}
+
+#[tauri::command]
+pub async fn update_extension<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    kind: ExtensionKind,
+    name: String,
+    modrinth_url: Option<String>,
+    hangar_url: Option<String>,
+) -> Result<InstallResult, String> {
+    let server = registered_server(&_app, &server_path).await?;
+    let catalog = catalog(modrinth_url, hangar_url);
+    let mut result = match kind {
+        ExtensionKind::Plugin => {
+            PluginManager::new(&server_path)
+                .with_scanner(jar_scanner(&_app).await?)
+                .update_plugin(&name, &catalog, server.server_type, &server.version)
+                .await?
+        }
+        ExtensionKind::Mod => {
+            ModManager::new(&server_path, server.server_type)
+                .with_scanner(jar_scanner(&_app).await?)
+                .update_mod(&name, &catalog, &server.version)
+                .await?
+        }
+    };
+    result.outcome.restart_required &= is_server_running(&server_path);
+    Ok(result)
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::Runtime;
+use crate::config::ServerProperties;
+use crate::config_history::ConfigHistory;
+use crate::property_schema::{schema_for_version, server_properties_schema, IssueSeverity, PropertySchema, ValidationIssue};
+use crate::version::MinecraftVersion;
+use crate::yaml_config::{ConfigFileKind, PerformanceSetting, ServerConfigFiles};
+use crate::ServerType;
+use super::history::DEFAULT_AUTHOR;
+
+fn parse_version(version: Option<String>) -> Result<Option<MinecraftVersion>, String> {
+    version.as_deref().map(MinecraftVersion::parse).transpose()
+}
+
+#[tauri::command]
+pub async fn get_properties_schema<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    version: Option<String>,
+) -> Result<Vec<PropertySchema>, String> {
+    Ok(match parse_version(version)? {
+        Some(version) => schema_for_version(&version),
+        None => server_properties_schema(),
+    })
+}
+
+#[tauri::command]
+pub async fn get_server_properties<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+) -> Result<Vec<(String, String)>, String> {
+    let mut properties = ServerProperties::new();
+    properties.load_from_file(&server_path.join("server.properties")).await?;
+    Ok(properties
+        .to_document()
+        .entries()
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn validate_server_properties<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    version: Option<String>,
+    changes: HashMap<String, String>,
+) -> Result<Vec<ValidationIssue>, String> {
+    let mut properties = ServerProperties::new();
+    properties.load_from_file(&server_path.join("server.properties")).await?;
+    // Values the properties can't hold are issues like any other, not a failed check.
+    let mut issues = Vec::new();
+    for (key, value) in &changes {
+        if let Err(message) = properties.set_property(key, value) {
+            issues.push(ValidationIssue { key: key.clone(), message, severity: IssueSeverity::Error });
+        }
+    }
+    issues.extend(properties.check(parse_version(version)?.as_ref()));
+    Ok(issues)
+}
+
+#[tauri::command]
+pub async fn update_server_properties<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    changes: HashMap<String, String>,
+    author: Option<String>,
+) -> Result<(), String> {
+    let path = server_path.join("server.properties");
+    let mut properties = ServerProperties::new();
+    properties.load_from_file(&path).await?;
+    for (key, value) in &changes {
+        properties.set_property(key, value)?;
+    }
+
+    let author = author.unwrap_or_else(|| DEFAULT_AUTHOR.to_string());
+    let history = ConfigHistory::new(&server_path);
+    history.ensure_baseline(&author).await?;
+    properties.save_to_file(&path).await?;
+    history.snapshot(&author, "Updated server.properties").await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_performance_settings<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    server_type: ServerType,
+    version: Option<String>,
+) -> Result<Vec<PerformanceSetting>, String> {
+    ServerConfigFiles::new(&server_path, server_type, parse_version(version)?)
+        .performance_settings()
+        .await
+}
+
+#[tauri::command]
+pub async fn update_performance_settings<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    server_type: ServerType,
+    version: Option<String>,
+    changes: Vec<(ConfigFileKind, String, serde_yaml::Value)>,
+    author: Option<String>,
+) -> Result<Vec<ValidationIssue>, String> {
+    let author = author.unwrap_or_else(|| DEFAULT_AUTHOR.to_string());
+    let history = ConfigHistory::new(&server_path);
+    history.ensure_baseline(&author).await?;
+    let issues = ServerConfigFiles::new(&server_path, server_type, parse_version(version)?)
+        .apply_changes(&changes)
+        .await?;
+    history.snapshot(&author, "Updated performance settings").await?;
+    Ok(issues)
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::Runtime;
+use tokio::fs;
+use crate::groups::{ConflictStrategy, DriftEntry, GroupManager, LiveConnection, ServerGroup, SyncReport};
+use crate::properties::PropertiesDocument;
+use crate::rcon::RconClient;
+use super::app_data_dir;
+use super::server::{has_console, is_server_running, send_console_command};
+
+pub(crate) async fn load_groups<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<GroupManager, String> {
+    let mut manager = GroupManager::new(&app_data_dir(app)?);
+    manager.load().await?;
+    Ok(manager)
+}
+
+/// How a running member receives changes live.
+pub(crate) enum LiveRoute {
+    /// Written to the stdin of a server MCSM started.
+    Console,
+    Rcon(RconClient),
+}
+
+/// Live routes to the running servers among `members`, which would overwrite
+/// edited files on shutdown. Servers MCSM started use their console; others need RCON.
+pub(crate) async fn live_routes(members: Vec<PathBuf>) -> Result<Vec<(PathBuf, LiveRoute)>, String> {
+    let mut routes = Vec::new();
+    for member in members.into_iter().filter(is_server_running) {
+        if has_console(&member) {
+            routes.push((member, LiveRoute::Console));
+            continue;
+        }
+        match connect_rcon(&member).await? {
+            Some(client) => routes.push((member, LiveRoute::Rcon(client))),
+            None => return Err(format!(
+                "{} is running without console access or RCON; stop it or enable RCON to sync",
+                member.display()
+            )),
+        }
+    }
+    Ok(routes)
+}
+
+pub(crate) fn live_connections(routes: &mut [(PathBuf, LiveRoute)]) -> HashMap<PathBuf, LiveConnection<'_>> {
+    routes
+        .iter_mut()
+        .map(|(member, route)| {
+            let connection = match route {
+                LiveRoute::Console => {
+                    let path = member.clone();
+                    LiveConnection::Console(Box::new(move |command: &str| {
+                        if send_console_command(&path, command)? {
+                            Ok(())
+                        } else {
+                            Err("The server is no longer running".to_string())
+                        }
+                    }))
+                }
+                LiveRoute::Rcon(client) => LiveConnection::Rcon(client),
+            };
+            (member.clone(), connection)
+        })
+        .collect()
+}
+
+/// Opens an RCON session to a running member, if it has RCON enabled.
+pub(crate) async fn connect_rcon(server_path: &PathBuf) -> Result<Option<RconClient>, String> {
+    let content = fs::read_to_string(server_path.join("server.properties"))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let properties = PropertiesDocument::parse(&content)?;
+
+    if properties.get("enable-rcon") != Some("true") {
+        return Ok(None);
+    }
+    let port = properties.get("rcon.port").unwrap_or("25575");
+    let password = properties.get("rcon.password").unwrap_or("");
+    let client = RconClient::connect(&format!("127.0.0.1:{}", port), password).await?;
+    Ok(Some(client))
+}
+
+#[tauri::command]
+pub async fn get_server_groups<R: Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<Vec<ServerGroup>, String> {
+    let manager = load_groups(&app).await?;
+    Ok(manager.get_all_groups().into_iter().cloned().collect())
+}
+
+#[tauri::command]
+pub async fn create_server_group<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    name: String,
+    members: Vec<PathBuf>,
+) -> Result<(), String> {
+    let mut manager = load_groups(&app).await?;
+    manager.create_group(&name)?;
+    for member in members {
+        manager.add_member(&name, member)?;
+    }
+    manager.save().await
+}
+
+#[tauri::command]
+pub async fn delete_server_group<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    name: String,
+) -> Result<(), String> {
+    let mut manager = load_groups(&app).await?;
+    manager.remove_group(&name);
+    manager.save().await
+}
+
+#[tauri::command]
+pub async fn add_server_group_member<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    group: String,
+    server_path: PathBuf,
+) -> Result<(), String> {
+    let mut manager = load_groups(&app).await?;
+    manager.add_member(&group, server_path)?;
+    manager.save().await
+}
+
+#[tauri::command]
+pub async fn remove_server_group_member<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    group: String,
+    server_path: PathBuf,
+) -> Result<(), String> {
+    let mut manager = load_groups(&app).await?;
+    manager.remove_member(&group, &server_path)?;
+    manager.save().await
+}
+
+#[tauri::command]
+pub async fn get_server_group_drift<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    group: String,
+) -> Result<Vec<DriftEntry>, String> {
+    let manager = load_groups(&app).await?;
+    manager.drift_report(&group).await
+}
+
+#[tauri::command]
+pub async fn sync_server_group<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    group: String,
+    strategy: ConflictStrategy,
+) -> Result<SyncReport, String> {
+    let manager = load_groups(&app).await?;
+    let members = manager
+        .get_group(&group)
+        .ok_or_else(|| format!("Group {} not found", group))?
+        .members
+        .clone();
+
+    let mut routes = live_routes(members).await?;
+    let mut live = live_connections(&mut routes);
+    manager.reconcile(&group, &strategy, &mut live).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::process::{Command, Stdio};
+    use crate::groups::{apply_change, ListChange, PlayerListKind};
+    use super::super::server::RUNNING_SERVERS;
+
+    #[tokio::test]
+    async fn test_sync_running_server_without_rcon() {
+        let server_path = std::env::temp_dir().join("mcsm_test_console_sync");
+        let _ = std::fs::remove_dir_all(&server_path);
+        std::fs::create_dir_all(&server_path).unwrap();
+        std::fs::write(server_path.join("server.properties"), "enable-rcon=false\n").unwrap();
+
+        // `cat` stands in for a server MCSM started, echoing its console input.
+        let mut child = Command::new("cat").stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+        let mut stdout = child.stdout.take().unwrap();
+        RUNNING_SERVERS.lock().unwrap().insert(server_path.clone(), child);
+
+        let mut routes = live_routes(vec![server_path.clone()]).await.unwrap();
+        assert!(matches!(routes[0].1, LiveRoute::Console));
+        let change = ListChange {
+            kind: PlayerListKind::Whitelist,
+            uuid: "uuid-a".to_string(),
+            name: "Alice".to_string(),
+            added: true,
+            reason: None,
+            op_level: None,
+        };
+        let report = apply_change(&server_path, &change, &mut live_connections(&mut routes)).await;
+        assert!(report.errors.is_empty() && report.applied[0].live);
+        assert!(!server_path.join("whitelist.json").exists());
+
+        let mut child = RUNNING_SERVERS.lock().unwrap().remove(&server_path).unwrap();
+        drop(child.stdin.take());
+        let mut echoed = String::new();
+        stdout.read_to_string(&mut echoed).unwrap();
+        child.wait().unwrap();
+        assert_eq!(echoed, "whitelist add Alice\n");
+    }
+}
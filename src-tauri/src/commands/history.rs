@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+use tauri::Runtime;
+use crate::config_history::{ConfigFileDiff, ConfigHistory, ConfigSnapshot};
+use super::server::is_server_running;
+
+/// Author recorded for config changes when the caller doesn't name one.
+pub(crate) const DEFAULT_AUTHOR: &str = "MCSM";
+
+#[tauri::command]
+pub async fn get_config_history<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+) -> Result<Vec<ConfigSnapshot>, String> {
+    ConfigHistory::new(&server_path).list().await
+}
+
+#[tauri::command]
+pub async fn create_config_snapshot<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    author: Option<String>,
+    message: String,
+) -> Result<ConfigSnapshot, String> {
+    ConfigHistory::new(&server_path)
+        .snapshot(author.as_deref().unwrap_or(DEFAULT_AUTHOR), &message)
+        .await
+}
+
+#[tauri::command]
+pub async fn diff_config_snapshots<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    from: u64,
+    to: u64,
+) -> Result<Vec<ConfigFileDiff>, String> {
+    ConfigHistory::new(&server_path).diff(from, to).await
+}
+
+#[tauri::command]
+pub async fn rollback_config_snapshot<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    snapshot_id: u64,
+    author: Option<String>,
+) -> Result<ConfigSnapshot, String> {
+    // Plugins rewrite their configs on shutdown, which would undo the rollback.
+    if is_server_running(&server_path) {
+        return Err("Stop the server before rolling back its configuration".to_string());
+    }
+
+    ConfigHistory::new(&server_path)
+        .rollback(snapshot_id, author.as_deref().unwrap_or(DEFAULT_AUTHOR))
+        .await
+}
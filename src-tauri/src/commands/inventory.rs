@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+use tauri::Runtime;
+use crate::inventory::{players_for_edit, InventoryItem, InventorySection, PlayerDataEditor, PlayerInventory};
+use super::server::is_server_running;
+
+#[tauri::command]
+pub async fn get_player_inventory<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    world: Option<String>,
+    uuid: String,
+) -> Result<PlayerInventory, String> {
+    let editor = PlayerDataEditor::new(&server_path, world.as_deref().unwrap_or("world"));
+    editor.get_inventory(&uuid).await
+}
+
+#[tauri::command]
+pub async fn remove_player_item<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    world: Option<String>,
+    uuid: String,
+    section: InventorySection,
+    slot: i32,
+) -> Result<InventoryItem, String> {
+    let players = players_for_edit(&server_path, is_server_running(&server_path)).await?;
+
+    let editor = PlayerDataEditor::new(&server_path, world.as_deref().unwrap_or("world"));
+    editor.remove_item(&uuid, section, slot, &players).await
+}
+
+#[tauri::command]
+pub async fn add_player_item<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    world: Option<String>,
+    uuid: String,
+    item: InventoryItem,
+) -> Result<(), String> {
+    let players = players_for_edit(&server_path, is_server_running(&server_path)).await?;
+
+    let editor = PlayerDataEditor::new(&server_path, world.as_deref().unwrap_or("world"));
+    editor.add_item(&uuid, item, &players).await
+}
+
@@ -1,120 +1,107 @@
-use std::process::Command;
-use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use tauri::Runtime;
+use crate::java::{required_java_major, resolve_java, JavaDetector, JavaInfo};
+use crate::jvm::JvmOptions;
+use crate::managed_java::{ManagedJava, ManagedRuntime};
+use super::app_data_dir;
+use super::server::load_registry;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct JavaInfo {
-    pub version: String,
-    pub path: String,
-    pub is_valid: bool,
+fn managed_java<R: Runtime>(app: &tauri::AppHandle<R>, metadata_url: Option<String>) -> Result<ManagedJava, String> {
+    let managed = ManagedJava::new(&app_data_dir(app)?);
+    Ok(match metadata_url {
+        Some(url) => managed.with_metadata_url(&url),
+        None => managed,
+    })
+}
+
+/// A detector that also finds the runtimes MCSM downloaded.
+pub(crate) fn java_detector<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<JavaDetector, String> {
+    Ok(managed_java(app, None)?.detector())
 }
 
 #[tauri::command]
 pub async fn check_java_installation<R: Runtime>(
     _app: tauri::AppHandle<R>,
 ) -> Result<Vec<JavaInfo>, String> {
-    let mut java_installations = Vec::new();
-
-    // Check JAVA_HOME environment variable
-    if let Ok(java_home) = std::env::var("JAVA_HOME") {
-        if let Some(info) = check_java_path(&java_home) {
-            java_installations.push(info);
-        }
-    }
-
-    // Check common Java installation paths
-    let common_paths = if cfg!(target_os = "windows") {
-        vec![
-            "C:\\Program Files\\Java",
-            "C:\\Program Files (x86)\\Java",
-        ]
-    } else {
-        vec![
-            "/usr/lib/jvm",
-            "/usr/java",
-            "/opt/java",
-        ]
-    };
-
-    for path in common_paths {
-        if let Ok(entries) = std::fs::read_dir(path) {
-            for entry in entries.flatten() {
-                if let Some(info) = check_java_path(entry.path().to_str().unwrap_or("")) {
-                    java_installations.push(info);
-                }
-            }
-        }
-    }
+    Ok(java_detector(&_app)?.detect().await)
+}
 
-    // Check PATH for java executable
-    if let Ok(path) = std::env::var("PATH") {
-        for dir in path.split(if cfg!(target_os = "windows") { ";" } else { ":" }) {
-            let java_path = format!("{}/java", dir);
-            if let Some(info) = check_java_path(&java_path) {
-                java_installations.push(info);
-            }
-        }
-    }
+#[tauri::command]
+pub async fn get_java_version<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    java_path: String,
+) -> Result<String, String> {
+    JavaDetector::inspect(&PathBuf::from(&java_path))
+        .await
+        .map(|info| info.version)
+        .ok_or_else(|| format!("{} is not a working Java runtime", java_path))
+}
 
-    Ok(java_installations)
+/// The runtime a server will start with: its pinned one, or the best match for its version.
+#[tauri::command]
+pub async fn get_server_java<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+) -> Result<JavaInfo, String> {
+    let registry = load_registry(&_app).await?;
+    let server = registry
+        .get_server(&server_path)
+        .ok_or_else(|| format!("No server is registered at {}", server_path.display()))?;
+    let options = JvmOptions::load(&server_path).await?;
+    resolve_java(&java_detector(&_app)?, &server.version, options.java_path.as_deref()).await
 }
 
-fn check_java_path(path: &str) -> Option<JavaInfo> {
-    let java_executable = if cfg!(target_os = "windows") {
-        format!("{}/bin/java.exe", path)
-    } else {
-        format!("{}/bin/java", path)
-    };
+/// Like `get_server_java`, but downloads a JDK when no compatible runtime is installed.
+#[tauri::command]
+pub async fn ensure_server_java<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    metadata_url: Option<String>,
+) -> Result<JavaInfo, String> {
+    let registry = load_registry(&_app).await?;
+    let server = registry
+        .get_server(&server_path)
+        .ok_or_else(|| format!("No server is registered at {}", server_path.display()))?;
+    let options = JvmOptions::load(&server_path).await?;
+    let managed = managed_java(&_app, metadata_url)?;
+    let detector = managed.detector();
 
-    if !std::path::Path::new(&java_executable).exists() {
-        return None;
+    if options.java_path.is_some() {
+        return resolve_java(&detector, &server.version, options.java_path.as_deref()).await;
     }
-
-    let output = Command::new(&java_executable)
-        .arg("-version")
-        .output();
-
-    match output {
-        Ok(output) => {
-            let version_output = String::from_utf8_lossy(&output.stderr);
-            let version = version_output
-                .lines()
-                .next()
-                .unwrap_or("")
-                .split('"')
-                .nth(1)
-                .unwrap_or("Unknown")
-                .to_string();
-
-            Some(JavaInfo {
-                version,
-                path: java_executable,
-                is_valid: true,
-            })
-        }
-        Err(_) => None,
+    if let Ok(java) = resolve_java(&detector, &server.version, None).await {
+        return Ok(java);
     }
+    managed.install(required_java_major(&server.version)).await?;
+    resolve_java(&detector, &server.version, None).await
 }
 
 #[tauri::command]
-pub async fn get_java_version<R: Runtime>(
+pub async fn list_managed_java<R: Runtime>(
     _app: tauri::AppHandle<R>,
-    java_path: String,
-) -> Result<String, String> {
-    let output = Command::new(java_path)
-        .arg("-version")
-        .output()
-        .map_err(|e| e.to_string())?;
+) -> Result<Vec<ManagedRuntime>, String> {
+    managed_java(&_app, None)?.list().await
+}
 
-    let version_output = String::from_utf8_lossy(&output.stderr);
-    let version = version_output
-        .lines()
-        .next()
-        .ok_or_else(|| "No version output".to_string())?
-        .split('"')
-        .nth(1)
-        .ok_or_else(|| "Invalid version format".to_string())?
-        .to_string();
+#[tauri::command]
+pub async fn install_managed_java<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    major_version: u32,
+    metadata_url: Option<String>,
+) -> Result<ManagedRuntime, String> {
+    managed_java(&_app, metadata_url)?.install(major_version).await
+}
 
-    Ok(version)
-} 
\ No newline at end of file
+#[tauri::command]
+pub async fn remove_managed_java<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    id: String,
+) -> Result<(), String> {
+    let server_paths: Vec<PathBuf> = load_registry(&_app)
+        .await?
+        .get_all_servers()
+        .into_iter()
+        .map(|config| config.path.clone())
+        .collect();
+    managed_java(&_app, None)?.remove(&id, &server_paths).await
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use tauri::Runtime;
+use crate::java::resolve_java;
+use crate::jvm::{parse_java_major, JvmOptions};
+use crate::property_schema::{IssueSeverity, ValidationIssue};
+use crate::MemoryConfig;
+use super::java::java_detector;
+use super::server::load_registry;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JvmPreview {
+    pub command: Vec<String>,
+    pub issues: Vec<ValidationIssue>,
+}
+
+async fn server_memory<R: Runtime>(app: &tauri::AppHandle<R>, server_path: &PathBuf) -> Result<MemoryConfig, String> {
+    load_registry(app)
+        .await?
+        .get_server(server_path)
+        .map(|server| server.memory.clone())
+        .ok_or_else(|| format!("No server is registered at {}", server_path.display()))
+}
+
+#[tauri::command]
+pub async fn get_jvm_options<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+) -> Result<JvmOptions, String> {
+    JvmOptions::load(&server_path).await
+}
+
+#[tauri::command]
+pub async fn preview_jvm_options<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    options: JvmOptions,
+    java_version: Option<String>,
+) -> Result<JvmPreview, String> {
+    let memory = server_memory(&_app, &server_path).await?;
+    let java_major = java_version.as_deref().and_then(parse_java_major);
+    Ok(JvmPreview {
+        command: options.command_args(&memory, java_major, "server.jar"),
+        issues: options.check(&memory, java_major),
+    })
+}
+
+#[tauri::command]
+pub async fn update_jvm_options<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    options: JvmOptions,
+    java_version: Option<String>,
+    java_path: Option<String>,
+) -> Result<Vec<ValidationIssue>, String> {
+    let memory = server_memory(&_app, &server_path).await?;
+    let mut java_major = java_version.as_deref().and_then(parse_java_major);
+    let mut java_path = java_path;
+    if java_path.is_none() {
+        // Write the runtime the server would start with into the script when one is available.
+        let registry = load_registry(&_app).await?;
+        if let Some(server) = registry.get_server(&server_path) {
+            if let Ok(java) = resolve_java(&java_detector(&_app)?, &server.version, options.java_path.as_deref()).await {
+                java_major = java_major.or(Some(java.major_version));
+                java_path = Some(java.path.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    let issues = options.check(&memory, java_major);
+    let errors: Vec<&str> = issues
+        .iter()
+        .filter(|i| i.severity == IssueSeverity::Error)
+        .map(|i| i.message.as_str())
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+
+    options.save(&server_path).await?;
+    options
+        .write_start_script(&server_path, &memory, java_major, java_path.as_deref().unwrap_or("java"))
+        .await?;
+    Ok(issues)
+}
@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+use tauri::Runtime;
+use crate::jar_install::InstallResult;
+use crate::lockfile::{Lockfile, LockfileDrift};
+use crate::mods::{ModLoader, ModManager};
+use crate::plugins::PluginManager;
+use crate::ServerType;
+use super::scan::jar_scanner;
+use super::server::{is_server_running, registered_server};
+
+/// Jars added, replaced or deleted since MCSM last touched the server.
+#[tauri::command]
+pub async fn check_lockfile_drift<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+) -> Result<LockfileDrift, String> {
+    Lockfile::load(&server_path).await?.check_drift(&server_path).await
+}
+
+async fn export(server_path: &PathBuf, server_type: ServerType) -> Result<Lockfile, String> {
+    if ModLoader::for_server(&server_type).is_some() {
+        let mut manager = ModManager::new(server_path, server_type);
+        manager.load_mods().await?;
+        manager.export_mods().await
+    } else {
+        let mut manager = PluginManager::new(server_path);
+        manager.load_plugins().await?;
+        manager.export_plugins().await
+    }
+}
+
+/// The server's plugin or mod set, for importing into another server.
+#[tauri::command]
+pub async fn export_lockfile<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+) -> Result<Lockfile, String> {
+    let server = registered_server(&_app, &server_path).await?;
+    export(&server_path, server.server_type).await
+}
+
+/// Installs the plugins or mods in `lockfile` that the server doesn't have at the
+/// locked hash. With `prune`, anything not in it is removed.
+#[tauri::command]
+pub async fn import_lockfile<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    lockfile: Lockfile,
+    prune: bool,
+) -> Result<Vec<InstallResult>, String> {
+    let server = registered_server(&_app, &server_path).await?;
+    let scanner = jar_scanner(&_app).await?;
+    let mut results = if ModLoader::for_server(&server.server_type).is_some() {
+        ModManager::new(&server_path, server.server_type)
+            .with_scanner(scanner)
+            .import_mods(&lockfile, prune)
+            .await?
+    } else {
+        PluginManager::new(&server_path)
+            .with_scanner(scanner)
+            .import_plugins(&lockfile, server.server_type, &server.version, prune)
+            .await?
+    };
+    let running = is_server_running(&server_path);
+    for result in &mut results {
+        result.outcome.restart_required &= running;
+    }
+    Ok(results)
+}
+
+/// Gives `server_path` exactly the plugin or mod set of `source_path`.
+#[tauri::command]
+pub async fn recreate_extensions<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    source_path: PathBuf,
+    server_path: PathBuf,
+    prune: bool,
+) -> Result<Vec<InstallResult>, String> {
+    let source = registered_server(&app, &source_path).await?;
+    let lockfile = export(&source_path, source.server_type).await?;
+    import_lockfile(app, server_path, lockfile, prune).await
+}
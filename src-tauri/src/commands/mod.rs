@@ -1,2 +1,26 @@
 pub mod server;
-pub mod java; 
\ No newline at end of file
+pub mod java; 
+pub mod inventory;
+pub mod groups;
+pub mod players;
+pub mod config;
+pub mod history;
+pub mod templates;
+pub mod jvm;
+pub mod system;
+pub mod mods;
+pub mod plugins;
+pub mod catalog;
+pub mod modpack;
+pub mod lockfile;
+pub mod scan;
+
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// MCSM's own data directory, where app-wide state such as server groups lives.
+pub(crate) fn app_data_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))
+}
@@ -0,0 +1,23 @@
+use std::path::PathBuf;
+use tauri::Runtime;
+use crate::modpack::{import_modpack, ModpackImport, ModpackOptions};
+use super::scan::jar_scanner;
+use super::server::load_registry;
+
+/// Creates and registers a server from a `.mrpack` or a CurseForge server pack.
+#[tauri::command]
+pub async fn import_modpack_server<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    pack: PathBuf,
+    server_path: PathBuf,
+    options: ModpackOptions,
+) -> Result<ModpackImport, String> {
+    let mut registry = load_registry(&app).await?;
+    if registry.get_server(&server_path).is_some() {
+        return Err(format!("A server is already registered at {}", server_path.display()));
+    }
+    let imported = import_modpack(&pack, &server_path, &options, &jar_scanner(&app).await?).await?;
+    registry.register(imported.config.clone())?;
+    registry.save().await?;
+    Ok(imported)
+}
@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use tauri::Runtime;
+use crate::jar_install::InstallResult;
+use crate::lockfile::ExtensionSource;
+use crate::mods::{ModInfo, ModManager};
+use crate::plugins::PluginLoadError;
+use crate::property_schema::ValidationIssue;
+use super::scan::jar_scanner;
+use super::server::{is_server_running, registered_server};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModReport {
+    pub mods: Vec<ModInfo>,
+    /// Client-only mods, mods for another loader and broken dependencies.
+    pub issues: Vec<ValidationIssue>,
+    pub load_errors: Vec<PluginLoadError>,
+}
+
+#[tauri::command]
+pub async fn get_mods<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+) -> Result<ModReport, String> {
+    let server = registered_server(&_app, &server_path).await?;
+    let mut manager = ModManager::new(&server_path, server.server_type);
+    manager.load_mods().await?;
+    Ok(ModReport {
+        mods: manager.get_all_mods().to_vec(),
+        issues: manager.check(),
+        load_errors: manager.get_load_errors().to_vec(),
+    })
+}
+
+/// Installs or upgrades a mod from a URL or local jar, checking it against `hash`
+/// (hex SHA-256 or SHA-512) when given.
+#[tauri::command]
+pub async fn install_mod<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    source: ExtensionSource,
+    hash: Option<String>,
+) -> Result<InstallResult, String> {
+    let server = registered_server(&_app, &server_path).await?;
+    let mut manager = ModManager::new(&server_path, server.server_type).with_scanner(jar_scanner(&_app).await?);
+    let mut result = manager.install_mod(&source, hash.as_deref()).await?;
+    result.outcome.restart_required &= is_server_running(&server_path);
+    Ok(result)
+}
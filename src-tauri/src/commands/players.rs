@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+use tauri::Runtime;
+use crate::groups::{apply_change, ListChange, PlayerListKind, SyncReport};
+use super::groups::{live_connections, live_routes, load_groups};
+
+/// Applies `change` to `server_path`, then spreads it to the other members of
+/// every group the server belongs to.
+async fn change_player_list<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    server_path: &PathBuf,
+    change: ListChange,
+) -> Result<SyncReport, String> {
+    let manager = load_groups(app).await?;
+    let groups = manager.groups_for(server_path);
+
+    let mut members = vec![server_path.clone()];
+    for member in groups.iter().flat_map(|g| &g.members) {
+        if !members.contains(member) {
+            members.push(member.clone());
+        }
+    }
+    let mut routes = live_routes(members).await?;
+    let mut live = live_connections(&mut routes);
+
+    let mut report = apply_change(server_path, &change, &mut live).await;
+    if !report.errors.is_empty() {
+        return Err(report.errors.join("; "));
+    }
+    for group in groups {
+        let group_report = manager.propagate(&group.name, server_path, &change, &mut live).await?;
+        report.applied.extend(group_report.applied);
+        report.errors.extend(group_report.errors);
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn set_player_whitelisted<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    uuid: String,
+    name: String,
+    whitelisted: bool,
+) -> Result<SyncReport, String> {
+    change_player_list(&app, &server_path, ListChange {
+        kind: PlayerListKind::Whitelist,
+        uuid,
+        name,
+        added: whitelisted,
+        reason: None,
+        op_level: None,
+    }).await
+}
+
+#[tauri::command]
+pub async fn set_player_op<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    uuid: String,
+    name: String,
+    op: bool,
+    level: Option<u32>,
+) -> Result<SyncReport, String> {
+    change_player_list(&app, &server_path, ListChange {
+        kind: PlayerListKind::Ops,
+        uuid,
+        name,
+        added: op,
+        reason: None,
+        op_level: level,
+    }).await
+}
+
+#[tauri::command]
+pub async fn set_player_banned<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    uuid: String,
+    name: String,
+    banned: bool,
+    reason: Option<String>,
+) -> Result<SyncReport, String> {
+    change_player_list(&app, &server_path, ListChange {
+        kind: PlayerListKind::Bans,
+        uuid,
+        name,
+        added: banned,
+        reason,
+        op_level: None,
+    }).await
+}
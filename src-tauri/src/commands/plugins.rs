@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use tauri::Runtime;
+use crate::config_history::ConfigHistory;
+use crate::jar_install::InstallResult;
+use crate::lockfile::ExtensionSource;
+use crate::plugin_graph::DependencyReport;
+use crate::plugins::{Plugin, PluginActionOutcome, PluginConfigFile, PluginLoadError, PluginManager};
+use super::groups::connect_rcon;
+use super::history::DEFAULT_AUTHOR;
+use super::scan::jar_scanner;
+use super::server::{is_server_running, registered_server, send_console_command};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginReport {
+    pub plugins: Vec<Plugin>,
+    pub dependencies: DependencyReport,
+    pub load_errors: Vec<PluginLoadError>,
+}
+
+async fn load_manager(server_path: &PathBuf) -> Result<PluginManager, String> {
+    let mut manager = PluginManager::new(server_path);
+    manager.load_plugins().await?;
+    Ok(manager)
+}
+
+/// A stopped server picks the change up on its next start anyway.
+fn finish(server_path: &PathBuf, mut outcome: PluginActionOutcome) -> PluginActionOutcome {
+    outcome.restart_required &= is_server_running(server_path);
+    outcome
+}
+
+#[tauri::command]
+pub async fn get_plugins<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+) -> Result<PluginReport, String> {
+    let manager = load_manager(&server_path).await?;
+    let mut plugins: Vec<Plugin> = manager.get_all_plugins().into_iter().cloned().collect();
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(PluginReport {
+        plugins,
+        dependencies: manager.dependency_report(),
+        load_errors: manager.get_load_errors().to_vec(),
+    })
+}
+
+/// Installs or upgrades a plugin from a URL or local jar, checking it against `hash`
+/// (hex SHA-256 or SHA-512) when given.
+#[tauri::command]
+pub async fn install_plugin<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    source: ExtensionSource,
+    hash: Option<String>,
+) -> Result<InstallResult, String> {
+    let server = registered_server(&_app, &server_path).await?;
+    let mut manager = PluginManager::new(&server_path).with_scanner(jar_scanner(&_app).await?);
+    let mut result = manager
+        .install_plugin(&source, hash.as_deref(), server.server_type, &server.version)
+        .await?;
+    result.outcome = finish(&server_path, result.outcome);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn enable_plugin<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    name: String,
+) -> Result<PluginActionOutcome, String> {
+    let outcome = load_manager(&server_path).await?.enable_plugin(&name).await?;
+    Ok(finish(&server_path, outcome))
+}
+
+/// Refuses when other plugins require `name`, unless `force` is set.
+#[tauri::command]
+pub async fn disable_plugin<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    name: String,
+    force: bool,
+) -> Result<PluginActionOutcome, String> {
+    let outcome = load_manager(&server_path).await?.disable_plugin(&name, force).await?;
+    Ok(finish(&server_path, outcome))
+}
+
+/// Refuses when other plugins require `name`, unless `force` is set.
+#[tauri::command]
+pub async fn uninstall_plugin<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    name: String,
+    force: bool,
+) -> Result<PluginActionOutcome, String> {
+    let outcome = load_manager(&server_path).await?.uninstall_plugin(&name, force).await?;
+    Ok(finish(&server_path, outcome))
+}
+
+#[tauri::command]
+pub async fn get_plugin_config_files<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    name: String,
+) -> Result<Vec<PluginConfigFile>, String> {
+    load_manager(&server_path).await?.list_config_files(&name).await
+}
+
+#[tauri::command]
+pub async fn read_plugin_config<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    name: String,
+    file: String,
+) -> Result<String, String> {
+    load_manager(&server_path).await?.read_config_file(&name, &file).await
+}
+
+/// Saves a plugin config file with a config-history snapshot. With `reload`, a running
+/// server is told to reload the plugin through its console, or over RCON when MCSM
+/// didn't start it, instead of needing a restart.
+#[tauri::command]
+pub async fn update_plugin_config<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    name: String,
+    file: String,
+    content: String,
+    author: Option<String>,
+    reload: bool,
+) -> Result<PluginActionOutcome, String> {
+    let manager = load_manager(&server_path).await?;
+    let author = author.unwrap_or_else(|| DEFAULT_AUTHOR.to_string());
+    let history = ConfigHistory::new(&server_path);
+    history.ensure_baseline(&author).await?;
+    manager.write_config_file(&name, &file, &content).await?;
+    history
+        .snapshot(&author, &format!("Updated plugins/{}/{}", name, file))
+        .await?;
+
+    let mut outcome = PluginActionOutcome { restart_required: true, ..Default::default() };
+    if reload && is_server_running(&server_path) {
+        match manager.reload_command(&name)? {
+            None => outcome.warnings.push(format!("{} has no reload command", name)),
+            Some(command) if send_console_command(&server_path, &command)? => outcome.restart_required = false,
+            Some(command) => match connect_rcon(&server_path).await? {
+                Some(mut client) => {
+                    client.command(&command).await?;
+                    outcome.restart_required = false;
+                }
+                None => outcome.warnings.push("RCON is disabled, so the plugin can't be reloaded".to_string()),
+            },
+        }
+    }
+    Ok(finish(&server_path, outcome))
+}
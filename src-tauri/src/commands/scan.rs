@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use crate::jar_scan::{JarScanReport, JarScanner, BLOCKLIST_NAME, SECURITY_LOG_NAME};
+use super::app_data_dir;
+
+/// The scanner with the local blocklist, logging to MCSM's security log.
+pub(crate) async fn jar_scanner<R: Runtime>(app: &AppHandle<R>) -> Result<JarScanner, String> {
+    let dir = app_data_dir(app)?;
+    Ok(JarScanner::load(&dir.join(BLOCKLIST_NAME)).await?.with_log(&dir.join(SECURITY_LOG_NAME)))
+}
+
+/// Scans the server's plugin and mod jars.
+#[tauri::command]
+pub async fn scan_extensions<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+) -> Result<Vec<JarScanReport>, String> {
+    jar_scanner(&_app).await?.scan_server(&server_path).await
+}
+
+/// Adds a SHA-256 to the local blocklist so jars with it are refused.
+#[tauri::command]
+pub async fn block_jar_hash<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    sha256: String,
+    note: Option<String>,
+) -> Result<(), String> {
+    let sha256 = sha256.trim().to_ascii_lowercase();
+    if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Not a SHA-256: {}", sha256));
+    }
+    let dir = app_data_dir(&_app)?;
+    fs::create_dir_all(&dir).await.map_err(|e| e.to_string())?;
+    let line = match note {
+        Some(note) => format!("{} {}\n", sha256, note.trim()),
+        None => format!("{}\n", sha256),
+    };
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(BLOCKLIST_NAME))
+        .await
+        .map_err(|e| e.to_string())?;
+    file.write_all(line.as_bytes()).await.map_err(|e| e.to_string())?;
+    file.flush().await.map_err(|e| e.to_string())
+}
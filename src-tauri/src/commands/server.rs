@@ -1,10 +1,19 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use std::process::{Command, Child};
+use std::process::{Command, Child, ChildStdout, Stdio};
 use std::fs;
 use tauri::Runtime;
 use std::sync::Mutex;
 use std::collections::HashMap;
+use crate::java::resolve_java;
+use crate::jvm::JvmOptions;
+use crate::memory::{check_overcommit, count_extensions, recommend_memory, HostMemory, MemoryRecommendation};
+use crate::registry::ServerRegistry;
+use crate::server_clone::{CloneOptions, CloneResult, ServerCloner};
+use crate::sessions::SessionStore;
+use crate::{MemoryConfig, ServerType};
+use super::app_data_dir;
+use super::java::java_detector;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ServerConfig {
@@ -25,7 +34,118 @@ pub struct ServerStatus {
 
 // Store running server processes
 lazy_static::lazy_static! {
-    static ref RUNNING_SERVERS: Mutex<HashMap<PathBuf, Child>> = Mutex::new(HashMap::new());
+    pub(crate) static ref RUNNING_SERVERS: Mutex<HashMap<PathBuf, Child>> = Mutex::new(HashMap::new());
+}
+
+pub(crate) fn is_server_running(server_path: &PathBuf) -> bool {
+    RUNNING_SERVERS
+        .lock()
+        .map(|servers| servers.contains_key(server_path))
+        .unwrap_or(false)
+}
+
+/// Whether `server_path` was started by MCSM with its console on a pipe we can write to.
+pub(crate) fn has_console(server_path: &PathBuf) -> bool {
+    RUNNING_SERVERS
+        .lock()
+        .map(|servers| servers.get(server_path).is_some_and(|child| child.stdin.is_some()))
+        .unwrap_or(false)
+}
+
+/// Writes `command` to the console of a server started by MCSM. Returns `false` when
+/// the server isn't running from here, so callers can fall back to RCON.
+pub(crate) fn send_console_command(server_path: &PathBuf, command: &str) -> Result<bool, String> {
+    use std::io::Write;
+
+    let mut running_servers = RUNNING_SERVERS.lock()
+        .map_err(|e| format!("Failed to lock running servers: {}", e))?;
+    let Some(stdin) = running_servers.get_mut(server_path).and_then(|child| child.stdin.as_mut()) else {
+        return Ok(false);
+    };
+    writeln!(stdin, "{}", command)
+        .and_then(|_| stdin.flush())
+        .map_err(|e| format!("Failed to send console command: {}", e))?;
+    Ok(true)
+}
+
+pub(crate) async fn load_registry<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<ServerRegistry, String> {
+    let mut registry = ServerRegistry::new(&app_data_dir(app)?);
+    registry.load().await?;
+    Ok(registry)
+}
+
+pub(crate) async fn registered_server<R: Runtime>(app: &tauri::AppHandle<R>, server_path: &PathBuf) -> Result<crate::ServerConfig, String> {
+    load_registry(app)
+        .await?
+        .get_server(server_path)
+        .cloned()
+        .ok_or_else(|| format!("No server is registered at {}", server_path.display()))
+}
+
+/// Warns when starting `server_path` would overcommit host RAM with the heaps of
+/// the servers already running.
+async fn memory_overcommit_warning<R: Runtime>(app: &tauri::AppHandle<R>, server_path: &PathBuf) -> Result<Option<String>, String> {
+    let registry = load_registry(app).await?;
+    let Some(server) = registry.get_server(server_path) else {
+        return Ok(None);
+    };
+
+    let running_paths: Vec<PathBuf> = RUNNING_SERVERS
+        .lock()
+        .map_err(|e| format!("Failed to lock running servers: {}", e))?
+        .keys()
+        .filter(|path| *path != server_path)
+        .cloned()
+        .collect();
+    let running: Vec<(String, u32)> = running_paths
+        .iter()
+        .filter_map(|path| registry.get_server(path))
+        .map(|s| (s.name.clone(), s.memory.max_mb))
+        .collect();
+
+    Ok(check_overcommit(&HostMemory::detect(), &running, server.memory.max_mb))
+}
+
+/// Records joins and leaves from the server's console output in its session log.
+/// The output is drained even when the log can't be written, so the server never
+/// blocks on a full pipe.
+fn track_sessions(server_path: PathBuf, stdout: ChildStdout) {
+    tokio::spawn(async move {
+        use tokio::io::AsyncBufReadExt;
+
+        let stdout = match tokio::process::ChildStdout::from_std(stdout) {
+            Ok(stdout) => stdout,
+            Err(e) => {
+                eprintln!("Failed to read console of {}: {}", server_path.display(), e);
+                return;
+            }
+        };
+        let mut sessions = SessionStore::new(&server_path);
+        if let Err(e) = sessions.load().await {
+            eprintln!("Failed to load sessions of {}: {}", server_path.display(), e);
+        }
+
+        let mut lines = tokio::io::BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let Err(e) = sessions.handle_console_line(&line, chrono::Utc::now()).await {
+                eprintln!("Failed to record session of {}: {}", server_path.display(), e);
+            }
+        }
+        if let Err(e) = sessions.close_all(chrono::Utc::now(), "Server stopped").await {
+            eprintln!("Failed to close sessions of {}: {}", server_path.display(), e);
+        }
+    });
+}
+
+/// Parses a heap size such as `2G`, `1536M` or `1024` (megabytes) into megabytes.
+fn memory_mb(memory: &str) -> Option<u32> {
+    let memory = memory.trim();
+    let (number, multiplier) = match memory.chars().last()? {
+        'G' | 'g' => (&memory[..memory.len() - 1], 1024),
+        'M' | 'm' => (&memory[..memory.len() - 1], 1),
+        _ => (memory, 1),
+    };
+    number.parse::<u32>().ok().map(|n| n * multiplier)
 }
 
 #[tauri::command]
@@ -33,6 +153,19 @@ pub async fn create_server<R: Runtime>(
     _app: tauri::AppHandle<R>,
     config: ServerConfig,
 ) -> Result<String, String> {
+    let memory_mb = memory_mb(&config.memory)
+        .ok_or_else(|| format!("Invalid memory size: {}", config.memory))?;
+    let mut registry = load_registry(&_app).await?;
+    registry.register(crate::ServerConfig {
+        name: config.name.clone(),
+        version: config.version.clone(),
+        server_type: ServerType::Vanilla,
+        loader_version: None,
+        path: config.path.clone(),
+        memory: MemoryConfig { min_mb: memory_mb, max_mb: memory_mb },
+        port: config.port,
+    })?;
+
     // Create server directory
     fs::create_dir_all(&config.path)
         .map_err(|e| format!("Failed to create server directory: {}", e))?;
@@ -45,36 +178,14 @@ pub async fn create_server<R: Runtime>(
     fs::write(config.path.join("server.properties"), server_properties)
         .map_err(|e| format!("Failed to create server.properties: {}", e))?;
 
-    // Create start script
-    let start_script = if cfg!(target_os = "windows") {
-        format!(
-            "@echo off\njava -Xmx{} -jar server.jar nogui",
-            config.memory
-        )
-    } else {
-        format!(
-            "#!/bin/bash\njava -Xmx{} -jar server.jar nogui",
-            config.memory
-        )
-    };
-
-    let script_path = config.path.join(if cfg!(target_os = "windows") {
-        "start.bat"
-    } else {
-        "start.sh"
-    });
-
-    fs::write(&script_path, start_script)
-        .map_err(|e| format!("Failed to create start script: {}", e))?;
+    // Saved so the script and later edits start from the same options.
+    let options = JvmOptions::default();
+    options.save(&config.path).await?;
+    options
+        .write_start_script(&config.path, &MemoryConfig { min_mb: memory_mb, max_mb: memory_mb }, None, "java")
+        .await?;
 
-    if !cfg!(target_os = "windows") {
-        // Make the script executable on Unix-like systems
-        Command::new("chmod")
-            .arg("+x")
-            .arg(&script_path)
-            .output()
-            .map_err(|e| format!("Failed to make script executable: {}", e))?;
-    }
+    registry.save().await?;
 
     Ok(format!("Server {} created successfully!", config.name))
 }
@@ -84,6 +195,15 @@ pub async fn start_server<R: Runtime>(
     _app: tauri::AppHandle<R>,
     server_path: PathBuf,
 ) -> Result<String, String> {
+    let memory_warning = memory_overcommit_warning(&_app, &server_path).await?;
+    let java = match load_registry(&_app).await?.get_server(&server_path) {
+        Some(server) => {
+            let options = JvmOptions::load(&server_path).await?;
+            Some(resolve_java(&java_detector(&_app)?, &server.version, options.java_path.as_deref()).await?)
+        }
+        None => None,
+    };
+
     let mut running_servers = RUNNING_SERVERS.lock()
         .map_err(|e| format!("Failed to lock running servers: {}", e))?;
 
@@ -97,13 +217,30 @@ pub async fn start_server<R: Runtime>(
         "start.sh"
     });
 
-    let child = Command::new(start_script)
-        .current_dir(&server_path)
+    let mut command = Command::new(start_script);
+    command.current_dir(&server_path);
+    if let Some(java) = &java {
+        // Start scripts call plain `java`, so put the selected runtime first on PATH.
+        let mut paths = vec![java.home.join("bin")];
+        paths.extend(std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default()));
+        let path = std::env::join_paths(paths).map_err(|e| e.to_string())?;
+        command.env("JAVA_HOME", &java.home).env("PATH", path);
+    }
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
         .spawn()
         .map_err(|e| format!("Failed to start server: {}", e))?;
+    if let Some(stdout) = child.stdout.take() {
+        track_sessions(server_path.clone(), stdout);
+    }
 
     running_servers.insert(server_path, child);
-    Ok("Server started successfully!".to_string())
+    match memory_warning {
+        Some(warning) => Ok(format!("Server started successfully! Warning: {}", warning)),
+        None => Ok("Server started successfully!".to_string()),
+    }
 }
 
 #[tauri::command]
@@ -146,8 +283,18 @@ pub async fn get_server_status<R: Runtime>(
 pub async fn get_server_list<R: Runtime>(
     _app: tauri::AppHandle<R>,
 ) -> Result<Vec<ServerConfig>, String> {
-    // TODO: Implement server list retrieval from configuration
-    Ok(Vec::new())
+    let registry = load_registry(&_app).await?;
+    Ok(registry
+        .get_all_servers()
+        .into_iter()
+        .map(|server| ServerConfig {
+            name: server.name.clone(),
+            version: server.version.clone(),
+            port: server.port,
+            memory: format!("{}M", server.memory.max_mb),
+            path: server.path.clone(),
+        })
+        .collect())
 }
 
 #[tauri::command]
@@ -162,5 +309,59 @@ pub async fn delete_server<R: Runtime>(
     fs::remove_dir_all(&server_path)
         .map_err(|e| format!("Failed to delete server directory: {}", e))?;
 
+    let mut registry = load_registry(&_app).await?;
+    if registry.unregister(&server_path).is_some() {
+        registry.save().await?;
+    }
+
     Ok("Server deleted successfully!".to_string())
-} 
\ No newline at end of file
+}
+
+#[tauri::command]
+pub async fn clone_server<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    source_path: PathBuf,
+    options: CloneOptions,
+) -> Result<CloneResult, String> {
+    // World files are written continuously while the server runs.
+    if options.include_worlds && is_server_running(&source_path) {
+        return Err("Stop the server before cloning its worlds".to_string());
+    }
+
+    let mut registry = load_registry(&_app).await?;
+    let source = registry
+        .get_server(&source_path)
+        .ok_or_else(|| format!("No server is registered at {}", source_path.display()))?;
+    let cloner = ServerCloner::new(source);
+    let result = cloner.clone_to(&options, &registry.reserved_ports().await).await?;
+
+    registry.register(result.config.clone())?;
+    registry.save().await?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn check_start_memory<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+) -> Result<Option<String>, String> {
+    memory_overcommit_warning(&_app, &server_path).await
+}
+
+#[tauri::command]
+pub async fn recommend_server_memory<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+    server_path: PathBuf,
+    server_type: ServerType,
+) -> Result<MemoryRecommendation, String> {
+    let registry = load_registry(&_app).await?;
+    let other_servers_mb: u64 = registry
+        .get_all_servers()
+        .into_iter()
+        .filter(|s| s.path != server_path)
+        .map(|s| s.memory.max_mb as u64)
+        .sum();
+    let extensions = count_extensions(&server_path, &server_type).await?;
+
+    Ok(recommend_memory(&HostMemory::detect(), other_servers_mb, &server_type, extensions))
+}
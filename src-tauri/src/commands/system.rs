@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use tauri::Runtime;
+use crate::utils::{DiskInfo, SystemInfo, SystemUtils};
+use super::server::load_registry;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServerDisk {
+    pub server_name: String,
+    pub server_path: PathBuf,
+    pub disk: Option<DiskInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SystemOverview {
+    pub system: SystemInfo,
+    /// The disk each registered server lives on, for free-space checks.
+    pub server_disks: Vec<ServerDisk>,
+}
+
+#[tauri::command]
+pub async fn get_system_info<R: Runtime>(
+    _app: tauri::AppHandle<R>,
+) -> Result<SystemOverview, String> {
+    let system = SystemUtils::get_system_info().await?;
+    let registry = load_registry(&_app).await?;
+    let server_disks = registry
+        .get_all_servers()
+        .into_iter()
+        .map(|server| ServerDisk {
+            server_name: server.name.clone(),
+            server_path: server.path.clone(),
+            disk: system.disk_for(&server.path).cloned(),
+        })
+        .collect();
+
+    Ok(SystemOverview { system, server_disks })
+}
@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+use tauri::Runtime;
+use crate::templates::{ServerTemplate, TemplateCreation, TemplateManager, TemplateVariables};
+use super::app_data_dir;
+use super::scan::jar_scanner;
+use super::server::load_registry;
+
+fn template_manager<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<TemplateManager, String> {
+    Ok(TemplateManager::new(&app_data_dir(app)?))
+}
+
+#[tauri::command]
+pub async fn get_server_templates<R: Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<Vec<ServerTemplate>, String> {
+    template_manager(&app)?.get_all_templates().await
+}
+
+#[tauri::command]
+pub async fn save_server_template<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    template: ServerTemplate,
+) -> Result<(), String> {
+    template_manager(&app)?.save_template(&template).await
+}
+
+#[tauri::command]
+pub async fn delete_server_template<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    template_id: String,
+) -> Result<(), String> {
+    template_manager(&app)?.delete_template(&template_id).await
+}
+
+#[tauri::command]
+pub async fn export_server_template<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    template_id: String,
+    destination: PathBuf,
+) -> Result<(), String> {
+    template_manager(&app)?.export_template(&template_id, &destination).await
+}
+
+#[tauri::command]
+pub async fn import_server_template<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    source: PathBuf,
+) -> Result<ServerTemplate, String> {
+    template_manager(&app)?.import_template(&source).await
+}
+
+#[tauri::command]
+pub async fn create_server_from_template<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    template_id: String,
+    server_path: PathBuf,
+    variables: TemplateVariables,
+) -> Result<TemplateCreation, String> {
+    let created = template_manager(&app)?
+        .get_template(&template_id)
+        .await?
+        .instantiate(&server_path, &variables, &jar_scanner(&app).await?)
+        .await?;
+
+    let mut registry = load_registry(&app).await?;
+    registry.register(created.config.clone())?;
+    registry.save().await?;
+    Ok(created)
+}
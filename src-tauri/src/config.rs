@@ -1,7 +1,9 @@
 use std::path::PathBuf;
 use serde::{Serialize, Deserialize};
 use tokio::fs;
-use std::collections::HashMap;
+use crate::properties::PropertiesDocument;
+use crate::property_schema::{server_properties_schema, IssueSeverity, ValidationIssue};
+use crate::version::MinecraftVersion;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ServerProperties {
@@ -43,9 +45,7 @@ pub struct ServerProperties {
     pub max_tick_time: i64,
     pub enforce_whitelist: bool,
     pub use_native_transport: bool,
-    pub enable_jmx_monitoring: bool,
     pub enable_spawn_chunks: bool,
-    pub enable_command_block: bool,
     pub function_permission_level: i32,
     pub max_world_size: i32,
     pub network_compression_threshold: i32,
@@ -54,13 +54,10 @@ pub struct ServerProperties {
     pub require_resource_pack: bool,
     pub server_ip: String,
     pub server_port: i32,
-    pub spawn_npcs: bool,
-    pub spawn_animals: bool,
-    pub spawn_monsters: bool,
     pub snooper_enabled: bool,
-    pub use_native_transport: bool,
     pub white_list: bool,
-    pub enforce_whitelist: bool,
+    #[serde(skip)]
+    document: PropertiesDocument,
 }
 
 impl ServerProperties {
@@ -115,6 +112,7 @@ impl ServerProperties {
             server_port: 25565,
             snooper_enabled: true,
             white_list: false,
+            document: PropertiesDocument::new(),
         }
     }
 
@@ -126,38 +124,137 @@ impl ServerProperties {
         let content = fs::read_to_string(path)
             .await
             .map_err(|e| e.to_string())?;
+        let document = PropertiesDocument::parse(&content)?;
 
-        for line in content.lines() {
-            if line.starts_with('#') || line.trim().is_empty() {
-                continue;
-            }
-
-            if let Some((key, value)) = line.split_once('=') {
-                self.set_property(key.trim(), value.trim())?;
-            }
+        for (key, value) in document.entries() {
+            self.set_property(key, value)?;
         }
+        self.document = document;
 
         Ok(())
     }
 
     pub async fn save_to_file(&self, path: &PathBuf) -> Result<(), String> {
-        let mut content = String::new();
-        content.push_str("#Minecraft server properties\n");
-        content.push_str("#Generated by MCSM\n\n");
+        self.validate()?;
 
-        let properties = self.to_properties_map();
-        for (key, value) in properties {
-            content.push_str(&format!("{}={}\n", key, value));
-        }
-
-        fs::write(path, content)
+        let document = self.to_document();
+        fs::write(path, document.to_string())
             .await
             .map_err(|e| e.to_string())?;
 
         Ok(())
     }
 
-    fn set_property(&mut self, key: &str, value: &str) -> Result<(), String> {
+    /// The loaded document with the typed fields written back into it. Keys the file
+    /// doesn't have are only added when they differ from the defaults, so saving an
+    /// unmodified file reproduces it byte for byte.
+    pub fn to_document(&self) -> PropertiesDocument {
+        let properties = self.to_properties();
+        let fresh = !properties.iter().any(|(key, _)| self.document.contains_key(key));
+        let mut document = if fresh {
+            let mut document = PropertiesDocument::new();
+            document.push_comment("Minecraft server properties");
+            document.push_comment("Generated by MCSM");
+            for (key, value) in self.document.entries() {
+                document.set(key, value);
+            }
+            document
+        } else {
+            self.document.clone()
+        };
+
+        let defaults = ServerProperties::new().to_properties();
+        for ((key, value), (_, default)) in properties.into_iter().zip(defaults) {
+            if fresh || document.contains_key(key) || value != default {
+                document.set(key, &value);
+            }
+        }
+        document
+    }
+
+    /// Checks every property against the schema. Passing the server's version also
+    /// warns about keys that version doesn't read.
+    pub fn check(&self, version: Option<&MinecraftVersion>) -> Vec<ValidationIssue> {
+        let schema = server_properties_schema();
+        let document = self.to_document();
+        let mut issues = Vec::new();
+
+        for (key, value) in document.entries() {
+            let Some(property) = schema.iter().find(|p| p.key == key) else {
+                continue;
+            };
+            if let Some(message) = property.check_value(value) {
+                issues.push(ValidationIssue {
+                    key: key.to_string(),
+                    message,
+                    severity: IssueSeverity::Error,
+                });
+            }
+            if let Some(version) = version {
+                if !property.supports(version) {
+                    issues.push(ValidationIssue {
+                        key: key.to_string(),
+                        message: format!("{} is not used by Minecraft {}", key, version),
+                        severity: IssueSeverity::Warning,
+                    });
+                }
+            }
+        }
+
+        // Query listens on UDP, so it may share the game port; RCON is TCP and may not.
+        if self.enable_rcon && self.rcon_port == self.server_port {
+            issues.push(ValidationIssue {
+                key: "rcon.port".to_string(),
+                message: format!("rcon.port {} is the same as server-port", self.rcon_port),
+                severity: IssueSeverity::Error,
+            });
+        }
+        if self.enable_query && self.enable_rcon && self.query_port == self.rcon_port {
+            issues.push(ValidationIssue {
+                key: "query.port".to_string(),
+                message: format!("query.port {} is the same as rcon.port", self.query_port),
+                severity: IssueSeverity::Warning,
+            });
+        }
+        if self.simulation_distance > self.view_distance {
+            issues.push(ValidationIssue {
+                key: "simulation-distance".to_string(),
+                message: "simulation-distance is larger than view-distance and will be capped".to_string(),
+                severity: IssueSeverity::Warning,
+            });
+        }
+
+        issues
+    }
+
+    /// Fails with every error-level issue found by [`ServerProperties::check`].
+    pub fn validate(&self) -> Result<(), String> {
+        let errors: Vec<String> = self
+            .check(None)
+            .into_iter()
+            .filter(|issue| issue.severity == IssueSeverity::Error)
+            .map(|issue| issue.message)
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
+    /// Value of any property, including ones MCSM has no typed field for.
+    pub fn get_property(&self, key: &str) -> Option<String> {
+        let key = canonical_key(key);
+        self.to_properties()
+            .into_iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v)
+            .or_else(|| self.document.get(key).map(|v| v.to_string()))
+    }
+
+    pub fn set_property(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let key = canonical_key(key);
         match key {
             "server-name" => self.server_name = value.to_string(),
             "gamemode" => self.gamemode = value.to_string(),
@@ -165,106 +262,128 @@ impl ServerProperties {
             "max-players" => self.max_players = value.parse().map_err(|_| "Invalid max-players value")?,
             "view-distance" => self.view_distance = value.parse().map_err(|_| "Invalid view-distance value")?,
             "spawn-protection" => self.spawn_protection = value.parse().map_err(|_| "Invalid spawn-protection value")?,
-            "online-mode" => self.online_mode = value.parse().map_err(|_| "Invalid online-mode value")?,
-            "pvp" => self.pvp = value.parse().map_err(|_| "Invalid pvp value")?,
-            "allow-flight" => self.allow_flight = value.parse().map_err(|_| "Invalid allow-flight value")?,
+            "online-mode" => self.online_mode = parse_bool(value).ok_or("Invalid online-mode value")?,
+            "pvp" => self.pvp = parse_bool(value).ok_or("Invalid pvp value")?,
+            "allow-flight" => self.allow_flight = parse_bool(value).ok_or("Invalid allow-flight value")?,
             "motd" => self.motd = value.to_string(),
-            "enable-command-block" => self.enable_command_block = value.parse().map_err(|_| "Invalid enable-command-block value")?,
-            "spawn-animals" => self.spawn_animals = value.parse().map_err(|_| "Invalid spawn-animals value")?,
-            "spawn-monsters" => self.spawn_monsters = value.parse().map_err(|_| "Invalid spawn-monsters value")?,
-            "spawn-npcs" => self.spawn_npcs = value.parse().map_err(|_| "Invalid spawn-npcs value")?,
-            "generate-structures" => self.generate_structures = value.parse().map_err(|_| "Invalid generate-structures value")?,
+            "enable-command-block" => self.enable_command_block = parse_bool(value).ok_or("Invalid enable-command-block value")?,
+            "spawn-animals" => self.spawn_animals = parse_bool(value).ok_or("Invalid spawn-animals value")?,
+            "spawn-monsters" => self.spawn_monsters = parse_bool(value).ok_or("Invalid spawn-monsters value")?,
+            "spawn-npcs" => self.spawn_npcs = parse_bool(value).ok_or("Invalid spawn-npcs value")?,
+            "generate-structures" => self.generate_structures = parse_bool(value).ok_or("Invalid generate-structures value")?,
             "level-name" => self.level_name = value.to_string(),
             "level-seed" => self.level_seed = value.to_string(),
             "level-type" => self.level_type = value.to_string(),
-            "hardcore" => self.hardcore = value.parse().map_err(|_| "Invalid hardcore value")?,
-            "enable-rcon" => self.enable_rcon = value.parse().map_err(|_| "Invalid enable-rcon value")?,
-            "rcon-password" => self.rcon_password = value.to_string(),
-            "rcon-port" => self.rcon_port = value.parse().map_err(|_| "Invalid rcon-port value")?,
-            "enable-query" => self.enable_query = value.parse().map_err(|_| "Invalid enable-query value")?,
-            "query-port" => self.query_port = value.parse().map_err(|_| "Invalid query-port value")?,
-            "enable-jmx-monitoring" => self.enable_jmx_monitoring = value.parse().map_err(|_| "Invalid enable-jmx-monitoring value")?,
+            "hardcore" => self.hardcore = parse_bool(value).ok_or("Invalid hardcore value")?,
+            "enable-rcon" => self.enable_rcon = parse_bool(value).ok_or("Invalid enable-rcon value")?,
+            "rcon.password" => self.rcon_password = value.to_string(),
+            "rcon.port" => self.rcon_port = value.parse().map_err(|_| "Invalid rcon.port value")?,
+            "enable-query" => self.enable_query = parse_bool(value).ok_or("Invalid enable-query value")?,
+            "query.port" => self.query_port = value.parse().map_err(|_| "Invalid query.port value")?,
+            "enable-jmx-monitoring" => self.enable_jmx_monitoring = parse_bool(value).ok_or("Invalid enable-jmx-monitoring value")?,
             "jmx-port" => self.jmx_port = value.parse().map_err(|_| "Invalid jmx-port value")?,
-            "sync-chunk-writes" => self.sync_chunk_writes = value.parse().map_err(|_| "Invalid sync-chunk-writes value")?,
-            "enable-status" => self.enable_status = value.parse().map_err(|_| "Invalid enable-status value")?,
+            "sync-chunk-writes" => self.sync_chunk_writes = parse_bool(value).ok_or("Invalid sync-chunk-writes value")?,
+            "enable-status" => self.enable_status = parse_bool(value).ok_or("Invalid enable-status value")?,
             "entity-broadcast-range-percentage" => self.entity_broadcast_range_percentage = value.parse().map_err(|_| "Invalid entity-broadcast-range-percentage value")?,
             "text-filtering-config" => self.text_filtering_config = value.to_string(),
             "simulation-distance" => self.simulation_distance = value.parse().map_err(|_| "Invalid simulation-distance value")?,
             "player-idle-timeout" => self.player_idle_timeout = value.parse().map_err(|_| "Invalid player-idle-timeout value")?,
-            "force-gamemode" => self.force_gamemode = value.parse().map_err(|_| "Invalid force-gamemode value")?,
+            "force-gamemode" => self.force_gamemode = parse_bool(value).ok_or("Invalid force-gamemode value")?,
             "rate-limit" => self.rate_limit = value.parse().map_err(|_| "Invalid rate-limit value")?,
             "hard-max-players" => self.hard_max_players = value.parse().map_err(|_| "Invalid hard-max-players value")?,
             "max-tick-time" => self.max_tick_time = value.parse().map_err(|_| "Invalid max-tick-time value")?,
-            "enforce-whitelist" => self.enforce_whitelist = value.parse().map_err(|_| "Invalid enforce-whitelist value")?,
-            "use-native-transport" => self.use_native_transport = value.parse().map_err(|_| "Invalid use-native-transport value")?,
-            "enable-spawn-chunks" => self.enable_spawn_chunks = value.parse().map_err(|_| "Invalid enable-spawn-chunks value")?,
+            "enforce-whitelist" => self.enforce_whitelist = parse_bool(value).ok_or("Invalid enforce-whitelist value")?,
+            "use-native-transport" => self.use_native_transport = parse_bool(value).ok_or("Invalid use-native-transport value")?,
+            "enable-spawn-chunks" => self.enable_spawn_chunks = parse_bool(value).ok_or("Invalid enable-spawn-chunks value")?,
             "function-permission-level" => self.function_permission_level = value.parse().map_err(|_| "Invalid function-permission-level value")?,
             "max-world-size" => self.max_world_size = value.parse().map_err(|_| "Invalid max-world-size value")?,
             "network-compression-threshold" => self.network_compression_threshold = value.parse().map_err(|_| "Invalid network-compression-threshold value")?,
             "resource-pack" => self.resource_pack = value.to_string(),
             "resource-pack-sha1" => self.resource_pack_sha1 = value.to_string(),
-            "require-resource-pack" => self.require_resource_pack = value.parse().map_err(|_| "Invalid require-resource-pack value")?,
+            "require-resource-pack" => self.require_resource_pack = parse_bool(value).ok_or("Invalid require-resource-pack value")?,
             "server-ip" => self.server_ip = value.to_string(),
             "server-port" => self.server_port = value.parse().map_err(|_| "Invalid server-port value")?,
-            "snooper-enabled" => self.snooper_enabled = value.parse().map_err(|_| "Invalid snooper-enabled value")?,
-            "white-list" => self.white_list = value.parse().map_err(|_| "Invalid white-list value")?,
-            _ => return Err(format!("Unknown property: {}", key)),
+            "snooper-enabled" => self.snooper_enabled = parse_bool(value).ok_or("Invalid snooper-enabled value")?,
+            "white-list" => self.white_list = parse_bool(value).ok_or("Invalid white-list value")?,
+            // Keys without a typed field are kept as-is in the document.
+            _ => self.document.set(key, value),
         }
         Ok(())
     }
 
-    fn to_properties_map(&self) -> HashMap<String, String> {
-        let mut map = HashMap::new();
-        map.insert("server-name".to_string(), self.server_name.clone());
-        map.insert("gamemode".to_string(), self.gamemode.clone());
-        map.insert("difficulty".to_string(), self.difficulty.clone());
-        map.insert("max-players".to_string(), self.max_players.to_string());
-        map.insert("view-distance".to_string(), self.view_distance.to_string());
-        map.insert("spawn-protection".to_string(), self.spawn_protection.to_string());
-        map.insert("online-mode".to_string(), self.online_mode.to_string());
-        map.insert("pvp".to_string(), self.pvp.to_string());
-        map.insert("allow-flight".to_string(), self.allow_flight.to_string());
-        map.insert("motd".to_string(), self.motd.clone());
-        map.insert("enable-command-block".to_string(), self.enable_command_block.to_string());
-        map.insert("spawn-animals".to_string(), self.spawn_animals.to_string());
-        map.insert("spawn-monsters".to_string(), self.spawn_monsters.to_string());
-        map.insert("spawn-npcs".to_string(), self.spawn_npcs.to_string());
-        map.insert("generate-structures".to_string(), self.generate_structures.to_string());
-        map.insert("level-name".to_string(), self.level_name.clone());
-        map.insert("level-seed".to_string(), self.level_seed.clone());
-        map.insert("level-type".to_string(), self.level_type.clone());
-        map.insert("hardcore".to_string(), self.hardcore.to_string());
-        map.insert("enable-rcon".to_string(), self.enable_rcon.to_string());
-        map.insert("rcon-password".to_string(), self.rcon_password.clone());
-        map.insert("rcon-port".to_string(), self.rcon_port.to_string());
-        map.insert("enable-query".to_string(), self.enable_query.to_string());
-        map.insert("query-port".to_string(), self.query_port.to_string());
-        map.insert("enable-jmx-monitoring".to_string(), self.enable_jmx_monitoring.to_string());
-        map.insert("jmx-port".to_string(), self.jmx_port.to_string());
-        map.insert("sync-chunk-writes".to_string(), self.sync_chunk_writes.to_string());
-        map.insert("enable-status".to_string(), self.enable_status.to_string());
-        map.insert("entity-broadcast-range-percentage".to_string(), self.entity_broadcast_range_percentage.to_string());
-        map.insert("text-filtering-config".to_string(), self.text_filtering_config.clone());
-        map.insert("simulation-distance".to_string(), self.simulation_distance.to_string());
-        map.insert("player-idle-timeout".to_string(), self.player_idle_timeout.to_string());
-        map.insert("force-gamemode".to_string(), self.force_gamemode.to_string());
-        map.insert("rate-limit".to_string(), self.rate_limit.to_string());
-        map.insert("hard-max-players".to_string(), self.hard_max_players.to_string());
-        map.insert("max-tick-time".to_string(), self.max_tick_time.to_string());
-        map.insert("enforce-whitelist".to_string(), self.enforce_whitelist.to_string());
-        map.insert("use-native-transport".to_string(), self.use_native_transport.to_string());
-        map.insert("enable-spawn-chunks".to_string(), self.enable_spawn_chunks.to_string());
-        map.insert("function-permission-level".to_string(), self.function_permission_level.to_string());
-        map.insert("max-world-size".to_string(), self.max_world_size.to_string());
-        map.insert("network-compression-threshold".to_string(), self.network_compression_threshold.to_string());
-        map.insert("resource-pack".to_string(), self.resource_pack.clone());
-        map.insert("resource-pack-sha1".to_string(), self.resource_pack_sha1.clone());
-        map.insert("require-resource-pack".to_string(), self.require_resource_pack.to_string());
-        map.insert("server-ip".to_string(), self.server_ip.clone());
-        map.insert("server-port".to_string(), self.server_port.to_string());
-        map.insert("snooper-enabled".to_string(), self.snooper_enabled.to_string());
-        map.insert("white-list".to_string(), self.white_list.to_string());
-        map
+    fn to_properties(&self) -> Vec<(&'static str, String)> {
+        vec![
+        ("server-name", self.server_name.clone()),
+        ("gamemode", self.gamemode.clone()),
+        ("difficulty", self.difficulty.clone()),
+        ("max-players", self.max_players.to_string()),
+        ("view-distance", self.view_distance.to_string()),
+        ("spawn-protection", self.spawn_protection.to_string()),
+        ("online-mode", self.online_mode.to_string()),
+        ("pvp", self.pvp.to_string()),
+        ("allow-flight", self.allow_flight.to_string()),
+        ("motd", self.motd.clone()),
+        ("enable-command-block", self.enable_command_block.to_string()),
+        ("spawn-animals", self.spawn_animals.to_string()),
+        ("spawn-monsters", self.spawn_monsters.to_string()),
+        ("spawn-npcs", self.spawn_npcs.to_string()),
+        ("generate-structures", self.generate_structures.to_string()),
+        ("level-name", self.level_name.clone()),
+        ("level-seed", self.level_seed.clone()),
+        ("level-type", self.level_type.clone()),
+        ("hardcore", self.hardcore.to_string()),
+        ("enable-rcon", self.enable_rcon.to_string()),
+        ("rcon.password", self.rcon_password.clone()),
+        ("rcon.port", self.rcon_port.to_string()),
+        ("enable-query", self.enable_query.to_string()),
+        ("query.port", self.query_port.to_string()),
+        ("enable-jmx-monitoring", self.enable_jmx_monitoring.to_string()),
+        ("jmx-port", self.jmx_port.to_string()),
+        ("sync-chunk-writes", self.sync_chunk_writes.to_string()),
+        ("enable-status", self.enable_status.to_string()),
+        ("entity-broadcast-range-percentage", self.entity_broadcast_range_percentage.to_string()),
+        ("text-filtering-config", self.text_filtering_config.clone()),
+        ("simulation-distance", self.simulation_distance.to_string()),
+        ("player-idle-timeout", self.player_idle_timeout.to_string()),
+        ("force-gamemode", self.force_gamemode.to_string()),
+        ("rate-limit", self.rate_limit.to_string()),
+        ("hard-max-players", self.hard_max_players.to_string()),
+        ("max-tick-time", self.max_tick_time.to_string()),
+        ("enforce-whitelist", self.enforce_whitelist.to_string()),
+        ("use-native-transport", self.use_native_transport.to_string()),
+        ("enable-spawn-chunks", self.enable_spawn_chunks.to_string()),
+        ("function-permission-level", self.function_permission_level.to_string()),
+        ("max-world-size", self.max_world_size.to_string()),
+        ("network-compression-threshold", self.network_compression_threshold.to_string()),
+        ("resource-pack", self.resource_pack.clone()),
+        ("resource-pack-sha1", self.resource_pack_sha1.clone()),
+        ("require-resource-pack", self.require_resource_pack.to_string()),
+        ("server-ip", self.server_ip.clone()),
+        ("server-port", self.server_port.to_string()),
+        ("snooper-enabled", self.snooper_enabled.to_string()),
+        ("white-list", self.white_list.to_string()),
+        ]
+    }
+}
+
+/// Older MCSM versions wrote `rcon-port`-style keys; vanilla uses dotted names.
+fn canonical_key(key: &str) -> &str {
+    match key {
+        "rcon-port" => "rcon.port",
+        "rcon-password" => "rcon.password",
+        "query-port" => "query.port",
+        other => other,
+    }
+}
+
+/// Minecraft reads booleans with `Boolean.parseBoolean`, which ignores case.
+fn parse_bool(value: &str) -> Option<bool> {
+    if value.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
     }
 }
 
@@ -284,4 +403,60 @@ mod tests {
         props.set_property("gamemode", "creative").unwrap();
         assert_eq!(props.gamemode, "creative");
     }
+
+    #[test]
+    fn test_validation() {
+        let mut props = ServerProperties::new();
+        assert!(props.validate().is_ok());
+
+        props.difficulty = "nightmare".to_string();
+        props.view_distance = 64;
+        props.enable_rcon = true;
+        props.rcon_port = 25565;
+        let errors = props.validate().unwrap_err();
+        assert!(errors.contains("difficulty"));
+        assert!(errors.contains("view-distance"));
+        assert!(errors.contains("rcon.port"));
+
+        let props = ServerProperties::new();
+        let warnings = props.check(Some(&MinecraftVersion::new(1, 21, 4)));
+        assert!(warnings.iter().any(|issue| issue.key == "snooper-enabled"));
+    }
+
+    #[tokio::test]
+    async fn test_vanilla_properties_round_trip() {
+        let dir = std::env::temp_dir().join("mcsm_test_config");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for (name, golden) in [
+            ("server-1.21.properties", include_str!("../tests/fixtures/server-1.21.properties")),
+            ("server-1.12.2.properties", include_str!("../tests/fixtures/server-1.12.2.properties")),
+        ] {
+            let path = dir.join(name);
+            std::fs::write(&path, golden).unwrap();
+
+            let mut props = ServerProperties::new();
+            props.load_from_file(&path).await.unwrap();
+            props.save_to_file(&path).await.unwrap();
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), golden);
+        }
+
+        let path = dir.join("server-1.21.properties");
+        let mut props = ServerProperties::new();
+        props.load_from_file(&path).await.unwrap();
+        assert_eq!(props.get_property("log-ips").as_deref(), Some("true"));
+        assert_eq!(props.rcon_port, 25575);
+
+        props.max_players = 42;
+        props.set_property("initial-enabled-packs", "vanilla,bundle").unwrap();
+        props.save_to_file(&path).await.unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        let golden = include_str!("../tests/fixtures/server-1.21.properties");
+        let changed: Vec<(&str, &str)> = golden.lines().zip(saved.lines()).filter(|(a, b)| a != b).collect();
+        assert_eq!(changed, vec![
+            ("initial-enabled-packs=vanilla", "initial-enabled-packs=vanilla,bundle"),
+            ("max-players=20", "max-players=42"),
+        ]);
+    }
 } 
\ No newline at end of file
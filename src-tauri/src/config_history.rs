@@ -0,0 +1,449 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+use crate::yaml_config::ConfigFileKind;
+
+/// Plugin config files larger than this are not tracked.
+pub(crate) const MAX_TRACKED_FILE_SIZE: u64 = 1024 * 1024;
+const PLUGIN_CONFIG_EXTENSIONS: [&str; 7] = ["yml", "yaml", "json", "properties", "toml", "conf", "cfg"];
+/// Plugin folders that hold per-player or runtime data rather than configuration.
+const PLUGIN_DATA_DIRS: [&str; 5] = ["userdata", "data", "playerdata", "storage", "cache"];
+const DIFF_CONTEXT: usize = 3;
+/// Changed regions longer than this, in lines, are reported without a diff, since the
+/// LCS table grows with the square of the length.
+const MAX_DIFF_LINES: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    pub author: String,
+    pub message: String,
+    /// Server-relative path (with `/` separators) to the SHA-256 of its content.
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FileChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigFileDiff {
+    pub path: String,
+    pub change: FileChangeKind,
+    /// Unified diff of the file, empty for binary content.
+    pub diff: String,
+}
+
+/// Snapshots of a server's configuration files, stored under `.mcsm/config-history`.
+/// File contents are gzipped and stored once per distinct SHA-256, so unchanged
+/// files cost nothing in later snapshots.
+pub struct ConfigHistory {
+    server_path: PathBuf,
+    history_path: PathBuf,
+}
+
+impl ConfigHistory {
+    pub fn new(server_path: &PathBuf) -> Self {
+        ConfigHistory {
+            server_path: server_path.clone(),
+            history_path: server_path.join(".mcsm").join("config-history"),
+        }
+    }
+
+    fn index_file(&self) -> PathBuf {
+        self.history_path.join("index.json")
+    }
+
+    fn object_file(&self, hash: &str) -> PathBuf {
+        self.history_path.join("objects").join(format!("{}.gz", hash))
+    }
+
+    /// All snapshots, oldest first.
+    pub async fn list(&self) -> Result<Vec<ConfigSnapshot>, String> {
+        if !self.index_file().exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(self.index_file())
+            .await
+            .map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    }
+
+    pub async fn get(&self, id: u64) -> Result<ConfigSnapshot, String> {
+        self.list()
+            .await?
+            .into_iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| format!("Config snapshot {} not found", id))
+    }
+
+    /// Records the current state of the tracked files. When nothing changed since the
+    /// latest snapshot, that snapshot is returned instead of recording a duplicate.
+    pub async fn snapshot(&self, author: &str, message: &str) -> Result<ConfigSnapshot, String> {
+        let mut snapshots = self.list().await?;
+        let mut files = BTreeMap::new();
+        for relative in self.tracked_files().await? {
+            let content = fs::read(self.server_path.join(&relative))
+                .await
+                .map_err(|e| format!("Failed to read {}: {}", relative, e))?;
+            files.insert(relative, self.store_object(&content).await?);
+        }
+
+        if let Some(latest) = snapshots.last() {
+            if latest.files == files {
+                return Ok(latest.clone());
+            }
+        }
+
+        let snapshot = ConfigSnapshot {
+            id: snapshots.last().map(|s| s.id + 1).unwrap_or(1),
+            timestamp: Utc::now(),
+            author: author.to_string(),
+            message: message.to_string(),
+            files,
+        };
+        snapshots.push(snapshot.clone());
+        self.save_index(&snapshots).await?;
+        Ok(snapshot)
+    }
+
+    /// Records a baseline before MCSM writes to a server's configs for the first time,
+    /// so the first change has something to be compared against.
+    pub async fn ensure_baseline(&self, author: &str) -> Result<(), String> {
+        if self.list().await?.is_empty() {
+            self.snapshot(author, "Initial state").await?;
+        }
+        Ok(())
+    }
+
+    /// Per-file unified diffs going from snapshot `from` to snapshot `to`.
+    pub async fn diff(&self, from: u64, to: u64) -> Result<Vec<ConfigFileDiff>, String> {
+        let from = self.get(from).await?;
+        let to = self.get(to).await?;
+
+        let mut paths: Vec<&String> = from.files.keys().chain(to.files.keys()).collect();
+        paths.sort();
+        paths.dedup();
+
+        let mut diffs = Vec::new();
+        for path in paths {
+            let old_hash = from.files.get(path);
+            let new_hash = to.files.get(path);
+            let change = match (old_hash, new_hash) {
+                (Some(a), Some(b)) if a == b => continue,
+                (Some(_), Some(_)) => FileChangeKind::Modified,
+                (None, Some(_)) => FileChangeKind::Added,
+                (Some(_), None) => FileChangeKind::Removed,
+                (None, None) => continue,
+            };
+
+            let old = match old_hash {
+                Some(hash) => self.load_object(hash).await?,
+                None => Vec::new(),
+            };
+            let new = match new_hash {
+                Some(hash) => self.load_object(hash).await?,
+                None => Vec::new(),
+            };
+            let diff = match (String::from_utf8(old), String::from_utf8(new)) {
+                (Ok(old), Ok(new)) => unified_diff(path, &old, &new, change),
+                _ => String::new(),
+            };
+            diffs.push(ConfigFileDiff { path: path.clone(), change, diff });
+        }
+        Ok(diffs)
+    }
+
+    /// Restores the files recorded in snapshot `id`. Files added since are left in
+    /// place, as plugins recreate missing configs anyway. The current state is
+    /// snapshotted first, so a rollback can itself be rolled back.
+    pub async fn rollback(&self, id: u64, author: &str) -> Result<ConfigSnapshot, String> {
+        let target = self.get(id).await?;
+        self.snapshot(author, &format!("Before rollback to #{}", id)).await?;
+
+        for (relative, hash) in &target.files {
+            let path = self.server_path.join(relative);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+            }
+            let content = self.load_object(hash).await?;
+            fs::write(&path, content)
+                .await
+                .map_err(|e| format!("Failed to restore {}: {}", relative, e))?;
+        }
+
+        self.snapshot(author, &format!("Rolled back to #{}", id)).await
+    }
+
+    /// `server.properties`, the Bukkit/Spigot/Paper YAML configs and the config files
+    /// plugins keep in their data folders, skipping player and runtime data.
+    async fn tracked_files(&self) -> Result<Vec<String>, String> {
+        let mut files: Vec<String> = std::iter::once("server.properties")
+            .chain(ConfigFileKind::all().iter().map(|kind| kind.relative_path()))
+            .filter(|relative| self.server_path.join(relative).is_file())
+            .map(|relative| relative.to_string())
+            .collect();
+
+        let plugins_path = self.server_path.join("plugins");
+        let mut pending = Vec::new();
+        if plugins_path.is_dir() {
+            let mut entries = fs::read_dir(&plugins_path).await.map_err(|e| e.to_string())?;
+            while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+                // Jars live directly in plugins/; configs live in the per-plugin folders.
+                if entry.path().is_dir() {
+                    pending.push(entry.path());
+                }
+            }
+        }
+
+        while let Some(dir) = pending.pop() {
+            let mut entries = fs::read_dir(&dir).await.map_err(|e| e.to_string())?;
+            while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+                let path = entry.path();
+                let metadata = entry.metadata().await.map_err(|e| e.to_string())?;
+                if metadata.is_dir() {
+                    if !is_plugin_data_dir(&path) {
+                        pending.push(path);
+                    }
+                } else if metadata.len() <= MAX_TRACKED_FILE_SIZE && is_plugin_config(&path) {
+                    if let Ok(relative) = path.strip_prefix(&self.server_path) {
+                        files.push(relative_path_string(relative));
+                    }
+                }
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+
+    async fn store_object(&self, content: &[u8]) -> Result<String, String> {
+        let hash = format!("{:x}", Sha256::digest(content));
+        let path = self.object_file(&hash);
+        if path.exists() {
+            return Ok(hash);
+        }
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).map_err(|e| e.to_string())?;
+        let compressed = encoder.finish().map_err(|e| e.to_string())?;
+
+        fs::create_dir_all(self.history_path.join("objects"))
+            .await
+            .map_err(|e| e.to_string())?;
+        fs::write(&path, compressed).await.map_err(|e| e.to_string())?;
+        Ok(hash)
+    }
+
+    async fn load_object(&self, hash: &str) -> Result<Vec<u8>, String> {
+        let compressed = fs::read(self.object_file(hash))
+            .await
+            .map_err(|e| format!("Missing history object {}: {}", hash, e))?;
+        let mut content = Vec::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut content)
+            .map_err(|e| e.to_string())?;
+        Ok(content)
+    }
+
+    async fn save_index(&self, snapshots: &[ConfigSnapshot]) -> Result<(), String> {
+        fs::create_dir_all(&self.history_path)
+            .await
+            .map_err(|e| e.to_string())?;
+        let content = serde_json::to_string_pretty(snapshots).map_err(|e| e.to_string())?;
+        fs::write(self.index_file(), content)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+pub(crate) fn is_plugin_config(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| PLUGIN_CONFIG_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+pub(crate) fn is_plugin_data_dir(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| PLUGIN_DATA_DIRS.contains(&n.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+pub(crate) fn relative_path_string(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DiffOp {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Line diff: common prefix and suffix are trimmed, the rest is diffed with an LCS table.
+/// `None` when the rest is longer than `MAX_DIFF_LINES`.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Option<Vec<(DiffOp, &'a str)>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let (n, m) = (old_mid.len(), new_mid.len());
+    if n.max(m) > MAX_DIFF_LINES {
+        return None;
+    }
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_mid[i] == new_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<(DiffOp, &str)> = old[..prefix].iter().map(|l| (DiffOp::Equal, *l)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_mid[i] == new_mid[j] {
+            ops.push((DiffOp::Equal, old_mid[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            // Deletions go first so replaced lines read `-old` then `+new`.
+            ops.push((DiffOp::Delete, old_mid[i]));
+            i += 1;
+        } else {
+            ops.push((DiffOp::Insert, new_mid[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[old.len() - suffix..].iter().map(|l| (DiffOp::Equal, *l)));
+    Some(ops)
+}
+
+fn unified_diff(path: &str, old: &str, new: &str, change: FileChangeKind) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let mut out = format!(
+        "--- {}\n+++ {}\n",
+        if change == FileChangeKind::Added { "/dev/null".to_string() } else { format!("a/{}", path) },
+        if change == FileChangeKind::Removed { "/dev/null".to_string() } else { format!("b/{}", path) },
+    );
+    let Some(ops) = diff_lines(&old_lines, &new_lines) else {
+        out.push_str("Too large to diff\n");
+        return out;
+    };
+
+    // Group changes into hunks, merging those whose context would overlap.
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _))| *op != DiffOp::Equal)
+        .map(|(i, _)| i)
+        .collect();
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for index in changed {
+        let start = index.saturating_sub(DIFF_CONTEXT);
+        let end = (index + DIFF_CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    for (start, end) in hunks {
+        let old_start = ops[..start].iter().filter(|(op, _)| *op != DiffOp::Insert).count();
+        let new_start = ops[..start].iter().filter(|(op, _)| *op != DiffOp::Delete).count();
+        let old_count = ops[start..end].iter().filter(|(op, _)| *op != DiffOp::Insert).count();
+        let new_count = ops[start..end].iter().filter(|(op, _)| *op != DiffOp::Delete).count();
+
+        // Empty ranges are numbered from the line before them, as GNU diff does.
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            if old_count == 0 { old_start } else { old_start + 1 },
+            old_count,
+            if new_count == 0 { new_start } else { new_start + 1 },
+            new_count,
+        ));
+        for (op, line) in &ops[start..end] {
+            let marker = match op {
+                DiffOp::Equal => ' ',
+                DiffOp::Delete => '-',
+                DiffOp::Insert => '+',
+            };
+            out.push(marker);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snapshot_diff_and_rollback() {
+        let server_path = std::env::temp_dir().join("mcsm_test_config_history");
+        let _ = std::fs::remove_dir_all(&server_path);
+        std::fs::create_dir_all(server_path.join("plugins").join("Essentials")).unwrap();
+        std::fs::write(server_path.join("plugins").join("Essentials.jar"), b"PK").unwrap();
+        std::fs::write(server_path.join("plugins").join("Essentials").join("config.yml"), "teleport-cooldown: 0\n").unwrap();
+        std::fs::create_dir_all(server_path.join("plugins").join("Essentials").join("userdata")).unwrap();
+        std::fs::write(server_path.join("plugins").join("Essentials").join("userdata").join("uuid-steve.yml"), "money: 10\n").unwrap();
+        std::fs::write(server_path.join("server.properties"), "max-players=20\nmotd=Hi\npvp=true\n").unwrap();
+
+        let history = ConfigHistory::new(&server_path);
+        let first = history.snapshot("alice", "Initial").await.unwrap();
+        assert_eq!(first.files.len(), 2);
+        assert_eq!(history.snapshot("alice", "No changes").await.unwrap().id, first.id);
+
+        std::fs::write(server_path.join("server.properties"), "max-players=50\nmotd=Hi\npvp=true\n").unwrap();
+        std::fs::write(server_path.join("spigot.yml"), "settings:\n  debug: false\n").unwrap();
+        let second = history.snapshot("bob", "More slots").await.unwrap();
+        assert_eq!(second.id, 2);
+
+        let diffs = history.diff(first.id, second.id).await.unwrap();
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].path, "server.properties");
+        assert_eq!(
+            diffs[0].diff,
+            "--- a/server.properties\n+++ b/server.properties\n@@ -1,3 +1,3 @@\n-max-players=20\n+max-players=50\n motd=Hi\n pvp=true\n"
+        );
+        assert_eq!(diffs[1].change, FileChangeKind::Added);
+        assert!(diffs[1].diff.contains("@@ -0,0 +1,2 @@\n+settings:\n"));
+        let old: String = (0..3000).map(|i| format!("{}\n", i)).collect();
+        let new: String = (0..3000).map(|i| format!("{}\n", i * 2)).collect();
+        assert!(unified_diff("big.yml", &old, &new, FileChangeKind::Modified).ends_with("+++ b/big.yml\nToo large to diff\n"));
+
+        let restored = history.rollback(first.id, "carol").await.unwrap();
+        assert_eq!(restored.files["server.properties"], first.files["server.properties"]);
+        assert_eq!(std::fs::read_to_string(server_path.join("server.properties")).unwrap(), "max-players=20\nmotd=Hi\npvp=true\n");
+        assert!(server_path.join("spigot.yml").exists());
+        assert_eq!(history.list().await.unwrap().len(), 3);
+    }
+}
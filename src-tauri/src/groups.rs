@@ -0,0 +1,456 @@
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use tokio::fs;
+use std::collections::{BTreeMap, HashMap};
+use crate::players::PlayerList;
+use crate::rcon::RconClient;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PlayerListKind {
+    Whitelist,
+    Ops,
+    Bans,
+}
+
+/// A single whitelist/op/ban change, applied to group members either live or on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListChange {
+    pub kind: PlayerListKind,
+    pub uuid: String,
+    pub name: String,
+    pub added: bool,
+    pub reason: Option<String>,
+    pub op_level: Option<u32>,
+}
+
+impl ListChange {
+    fn console_command(&self) -> String {
+        match (self.kind, self.added) {
+            (PlayerListKind::Whitelist, true) => format!("whitelist add {}", self.name),
+            (PlayerListKind::Whitelist, false) => format!("whitelist remove {}", self.name),
+            (PlayerListKind::Ops, true) => format!("op {}", self.name),
+            (PlayerListKind::Ops, false) => format!("deop {}", self.name),
+            (PlayerListKind::Bans, true) => match &self.reason {
+                Some(reason) => format!("ban {} {}", self.name, reason),
+                None => format!("ban {}", self.name),
+            },
+            (PlayerListKind::Bans, false) => format!("pardon {}", self.name),
+        }
+    }
+
+    fn apply(&self, players: &mut PlayerList) {
+        players.set_player_name(&self.uuid, &self.name);
+        match (self.kind, self.added) {
+            (PlayerListKind::Whitelist, true) => players.add_to_whitelist(self.uuid.clone()),
+            (PlayerListKind::Whitelist, false) => players.remove_from_whitelist(&self.uuid),
+            (PlayerListKind::Ops, true) => {
+                players.add_op(self.uuid.clone());
+                players.set_op_level(&self.uuid, self.op_level.unwrap_or(4));
+            }
+            (PlayerListKind::Ops, false) => players.remove_op(&self.uuid),
+            (PlayerListKind::Bans, true) => {
+                players.add_to_blacklist(self.uuid.clone());
+                if let Some(reason) = &self.reason {
+                    players.set_ban_reason(&self.uuid, reason, "MCSM group sync");
+                }
+            }
+            (PlayerListKind::Bans, false) => players.remove_from_blacklist(&self.uuid),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerGroup {
+    pub name: String,
+    pub members: Vec<PathBuf>,
+}
+
+/// Writes a command to a server's console, such as the stdin of a server MCSM started.
+pub type ConsoleSender<'a> = Box<dyn FnMut(&str) -> Result<(), String> + Send + 'a>;
+
+/// How a running member receives changes instead of having its files rewritten.
+pub enum LiveConnection<'a> {
+    Console(ConsoleSender<'a>),
+    Rcon(&'a mut RconClient),
+}
+
+impl<'a> LiveConnection<'a> {
+    async fn send(&mut self, command: &str) -> Result<(), String> {
+        match self {
+            LiveConnection::Console(send) => send(command),
+            LiveConnection::Rcon(client) => client.command(command).await.map(|_| ()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ConflictStrategy {
+    /// Every entry found on any member is added to all members. Bans always spread.
+    /// Removals leave no trace, so an entry removed on some members but not others
+    /// is added back; removals must reach every member, as `propagate` does.
+    Union,
+    /// Members are made identical to the given member.
+    Mirror(PathBuf),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DriftEntry {
+    pub kind: PlayerListKind,
+    pub uuid: String,
+    pub name: String,
+    pub present_on: Vec<PathBuf>,
+    pub missing_on: Vec<PathBuf>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AppliedChange {
+    pub member: PathBuf,
+    pub change: ListChange,
+    pub live: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub applied: Vec<AppliedChange>,
+    pub errors: Vec<String>,
+}
+
+pub struct GroupManager {
+    groups: HashMap<String, ServerGroup>,
+    config_path: PathBuf,
+}
+
+impl GroupManager {
+    pub fn new(data_dir: &PathBuf) -> Self {
+        GroupManager {
+            groups: HashMap::new(),
+            config_path: data_dir.join("groups.json"),
+        }
+    }
+
+    pub async fn load(&mut self) -> Result<(), String> {
+        if !self.config_path.exists() {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&self.config_path)
+            .await
+            .map_err(|e| e.to_string())?;
+        let groups: Vec<ServerGroup> = serde_json::from_str(&content)
+            .map_err(|e| e.to_string())?;
+        self.groups = groups.into_iter().map(|g| (g.name.clone(), g)).collect();
+
+        Ok(())
+    }
+
+    pub async fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.config_path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        let groups: Vec<&ServerGroup> = self.groups.values().collect();
+        let json = serde_json::to_string_pretty(&groups).map_err(|e| e.to_string())?;
+        fs::write(&self.config_path, json)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn create_group(&mut self, name: &str) -> Result<(), String> {
+        if self.groups.contains_key(name) {
+            return Err(format!("Group {} already exists", name));
+        }
+        self.groups.insert(name.to_string(), ServerGroup {
+            name: name.to_string(),
+            members: Vec::new(),
+        });
+        Ok(())
+    }
+
+    pub fn remove_group(&mut self, name: &str) {
+        self.groups.remove(name);
+    }
+
+    pub fn add_member(&mut self, group: &str, server_path: PathBuf) -> Result<(), String> {
+        let group = self.groups
+            .get_mut(group)
+            .ok_or_else(|| format!("Group {} not found", group))?;
+        if !group.members.contains(&server_path) {
+            group.members.push(server_path);
+        }
+        Ok(())
+    }
+
+    pub fn remove_member(&mut self, group: &str, server_path: &PathBuf) -> Result<(), String> {
+        let group = self.groups
+            .get_mut(group)
+            .ok_or_else(|| format!("Group {} not found", group))?;
+        group.members.retain(|m| m != server_path);
+        Ok(())
+    }
+
+    pub fn get_group(&self, name: &str) -> Option<&ServerGroup> {
+        self.groups.get(name)
+    }
+
+    pub fn get_all_groups(&self) -> Vec<&ServerGroup> {
+        self.groups.values().collect()
+    }
+
+    /// Groups a server belongs to, so a change made on it can be spread.
+    pub fn groups_for(&self, server_path: &PathBuf) -> Vec<&ServerGroup> {
+        self.groups
+            .values()
+            .filter(|g| g.members.contains(server_path))
+            .collect()
+    }
+
+    /// Spreads a change made on `source` to every other member of the group.
+    pub async fn propagate(
+        &self,
+        group: &str,
+        source: &PathBuf,
+        change: &ListChange,
+        live: &mut HashMap<PathBuf, LiveConnection<'_>>,
+    ) -> Result<SyncReport, String> {
+        let group = self.groups
+            .get(group)
+            .ok_or_else(|| format!("Group {} not found", group))?;
+
+        let mut report = SyncReport::default();
+        for member in group.members.iter().filter(|m| *m != source) {
+            apply_to_member(member, vec![change.clone()], live, &mut report).await;
+        }
+        Ok(report)
+    }
+
+    /// Lists every entry that isn't present on all members of the group.
+    pub async fn drift_report(&self, group: &str) -> Result<Vec<DriftEntry>, String> {
+        let group = self.groups
+            .get(group)
+            .ok_or_else(|| format!("Group {} not found", group))?;
+        let lists = load_member_lists(group).await?;
+        Ok(compute_drift(&lists))
+    }
+
+    /// Brings all members back in line according to `strategy`.
+    pub async fn reconcile(
+        &self,
+        group: &str,
+        strategy: &ConflictStrategy,
+        live: &mut HashMap<PathBuf, LiveConnection<'_>>,
+    ) -> Result<SyncReport, String> {
+        let group = self.groups
+            .get(group)
+            .ok_or_else(|| format!("Group {} not found", group))?;
+        let lists = load_member_lists(group).await?;
+
+        if let ConflictStrategy::Mirror(source) = strategy {
+            if !lists.iter().any(|(path, _)| path == source) {
+                return Err(format!("{} is not a member of {}", source.display(), group.name));
+            }
+        }
+
+        let mut changes: BTreeMap<PathBuf, Vec<ListChange>> = BTreeMap::new();
+        for drift in compute_drift(&lists) {
+            let keep = match strategy {
+                ConflictStrategy::Union => true,
+                ConflictStrategy::Mirror(source) => drift.present_on.contains(source),
+            };
+            let (targets, added) = if keep {
+                (&drift.missing_on, true)
+            } else {
+                (&drift.present_on, false)
+            };
+
+            let source_list = drift.present_on.first().and_then(|p| lists.iter().find(|(path, _)| path == p));
+            let reason = source_list.and_then(|(_, l)| l.ban_reason(&drift.uuid)).map(|r| r.to_string());
+            // Under a union the highest op level wins.
+            let op_level = lists
+                .iter()
+                .filter_map(|(path, l)| match strategy {
+                    ConflictStrategy::Mirror(source) if path != source => None,
+                    _ => l.op_level(&drift.uuid),
+                })
+                .max();
+
+            for target in targets {
+                changes.entry(target.clone()).or_default().push(ListChange {
+                    kind: drift.kind,
+                    uuid: drift.uuid.clone(),
+                    name: drift.name.clone(),
+                    added,
+                    reason: reason.clone(),
+                    op_level,
+                });
+            }
+        }
+
+        let mut report = SyncReport::default();
+        for (member, member_changes) in changes {
+            apply_to_member(&member, member_changes, live, &mut report).await;
+        }
+        Ok(report)
+    }
+}
+
+/// Applies a change to a single server, live when it has a connection in `live`.
+pub async fn apply_change(
+    server_path: &PathBuf,
+    change: &ListChange,
+    live: &mut HashMap<PathBuf, LiveConnection<'_>>,
+) -> SyncReport {
+    let mut report = SyncReport::default();
+    apply_to_member(server_path, vec![change.clone()], live, &mut report).await;
+    report
+}
+
+async fn load_member_lists(group: &ServerGroup) -> Result<Vec<(PathBuf, PlayerList)>, String> {
+    let mut lists = Vec::new();
+    for member in &group.members {
+        let players = PlayerList::load(member)
+            .await
+            .map_err(|e| format!("Failed to load player lists of {}: {}", member.display(), e))?;
+        lists.push((member.clone(), players));
+    }
+    Ok(lists)
+}
+
+fn compute_drift(lists: &[(PathBuf, PlayerList)]) -> Vec<DriftEntry> {
+    let mut entries: BTreeMap<(PlayerListKind, String), (String, Vec<PathBuf>)> = BTreeMap::new();
+    for (path, players) in lists {
+        let kinds = [
+            (PlayerListKind::Whitelist, players.get_whitelist()),
+            (PlayerListKind::Ops, players.get_ops()),
+            (PlayerListKind::Bans, players.get_banned()),
+        ];
+        for (kind, uuids) in kinds {
+            for uuid in uuids {
+                let entry = entries
+                    .entry((kind, uuid.clone()))
+                    .or_insert_with(|| (String::new(), Vec::new()));
+                if entry.0.is_empty() {
+                    entry.0 = players.player_name(uuid).unwrap_or_default().to_string();
+                }
+                entry.1.push(path.clone());
+            }
+        }
+    }
+
+    entries
+        .into_iter()
+        .filter(|(_, (_, present_on))| present_on.len() < lists.len())
+        .map(|((kind, uuid), (name, present_on))| DriftEntry {
+            kind,
+            uuid,
+            name,
+            missing_on: lists
+                .iter()
+                .map(|(path, _)| path.clone())
+                .filter(|path| !present_on.contains(path))
+                .collect(),
+            present_on,
+        })
+        .collect()
+}
+
+async fn apply_to_member(
+    member: &PathBuf,
+    changes: Vec<ListChange>,
+    live: &mut HashMap<PathBuf, LiveConnection<'_>>,
+    report: &mut SyncReport,
+) {
+    if let Some(connection) = live.get_mut(member) {
+        for change in changes {
+            match connection.send(&change.console_command()).await {
+                Ok(()) => report.applied.push(AppliedChange {
+                    member: member.clone(),
+                    change,
+                    live: true,
+                }),
+                Err(e) => report.errors.push(format!("{}: {}", member.display(), e)),
+            }
+        }
+        return;
+    }
+
+    let mut players = match PlayerList::load(member).await {
+        Ok(players) => players,
+        Err(e) => {
+            report.errors.push(format!("{}: {}", member.display(), e));
+            return;
+        }
+    };
+    for change in &changes {
+        change.apply(&mut players);
+    }
+    if let Err(e) = players.save_to_files(member).await {
+        report.errors.push(format!("{}: {}", member.display(), e));
+        return;
+    }
+
+    report.applied.extend(changes.into_iter().map(|change| AppliedChange {
+        member: member.clone(),
+        change,
+        live: false,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_group_sync() {
+        let root = std::env::temp_dir().join("mcsm_test_groups");
+        let _ = std::fs::remove_dir_all(&root);
+        let lobby = root.join("lobby");
+        let survival = root.join("survival");
+        std::fs::create_dir_all(&lobby).unwrap();
+        std::fs::create_dir_all(&survival).unwrap();
+        std::fs::write(lobby.join("whitelist.json"), r#"[{"uuid":"uuid-a","name":"Alice"}]"#).unwrap();
+        std::fs::write(survival.join("banned-players.json"), r#"[{"uuid":"uuid-g","name":"Griefer","created":"2024-01-01 00:00:00 +0000","source":"Server","expires":"forever","reason":"Griefing"}]"#).unwrap();
+
+        let mut manager = GroupManager::new(&root);
+        manager.create_group("network").unwrap();
+        manager.add_member("network", lobby.clone()).unwrap();
+        manager.add_member("network", survival.clone()).unwrap();
+
+        let drift = manager.drift_report("network").await.unwrap();
+        assert_eq!(drift.len(), 2);
+
+        let mut live = HashMap::new();
+        let report = manager.reconcile("network", &ConflictStrategy::Union, &mut live).await.unwrap();
+        assert!(report.errors.is_empty());
+        assert!(manager.drift_report("network").await.unwrap().is_empty());
+
+        let mut lobby_players = PlayerList::new();
+        lobby_players.load_from_files(&lobby).await.unwrap();
+        assert!(lobby_players.is_banned("uuid-g"));
+        assert_eq!(lobby_players.ban_reason("uuid-g"), Some("Griefing"));
+
+        let change = ListChange {
+            kind: PlayerListKind::Whitelist,
+            uuid: "uuid-a".to_string(),
+            name: "Alice".to_string(),
+            added: false,
+            reason: None,
+            op_level: None,
+        };
+        manager.propagate("network", &lobby, &change, &mut live).await.unwrap();
+        let mut survival_players = PlayerList::new();
+        survival_players.load_from_files(&survival).await.unwrap();
+        assert!(!survival_players.is_whitelisted("uuid-a"));
+
+        // The source still whitelists Alice, so a union brings her back everywhere.
+        manager.reconcile("network", &ConflictStrategy::Union, &mut live).await.unwrap();
+        assert!(PlayerList::load(&survival).await.unwrap().is_whitelisted("uuid-a"));
+
+        assert!(apply_change(&lobby, &change, &mut live).await.errors.is_empty());
+        manager.propagate("network", &lobby, &change, &mut live).await.unwrap();
+        manager.reconcile("network", &ConflictStrategy::Union, &mut live).await.unwrap();
+        assert!(!PlayerList::load(&lobby).await.unwrap().is_whitelisted("uuid-a"));
+        assert!(!PlayerList::load(&survival).await.unwrap().is_whitelisted("uuid-a"));
+    }
+}
@@ -0,0 +1,408 @@
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use tokio::fs;
+use crate::nbt::{NbtFile, NbtTag};
+use crate::players::PlayerList;
+
+/// 1.20.5 replaced the item `tag` compound with data components and `Count` with `count`.
+const COMPONENTS_DATA_VERSION: i64 = 3837;
+/// 1.21.5 moved armor and offhand out of `Inventory` into an `equipment` compound.
+const EQUIPMENT_DATA_VERSION: i64 = 4325;
+
+const ARMOR_KEYS: [&str; 4] = ["feet", "legs", "chest", "head"];
+const OFFHAND_SLOT: i64 = -106;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum InventorySection {
+    Inventory,
+    Armor,
+    Offhand,
+    EnderChest,
+}
+
+impl InventorySection {
+    fn max_slot(&self) -> i32 {
+        match self {
+            InventorySection::Inventory => 35,
+            InventorySection::Armor => 3,
+            InventorySection::Offhand => 0,
+            InventorySection::EnderChest => 26,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryItem {
+    pub section: InventorySection,
+    /// Slot within the section: 0-35 for the inventory, 0 (feet) to 3 (head) for armor,
+    /// 0 for the offhand and 0-26 for the ender chest.
+    pub slot: i32,
+    pub id: String,
+    pub count: i32,
+    /// Data components (1.20.5+) or the legacy `tag` compound.
+    pub components: Option<NbtTag>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlayerInventory {
+    pub uuid: String,
+    pub data_version: i64,
+    pub inventory: Vec<InventoryItem>,
+    pub armor: Vec<InventoryItem>,
+    pub offhand: Option<InventoryItem>,
+    pub ender_chest: Vec<InventoryItem>,
+}
+
+/// Loads the player list of `server_path` for an inventory edit. Refused while the
+/// server runs, since it rewrites player data from memory on save and logout.
+pub async fn players_for_edit(server_path: &PathBuf, server_running: bool) -> Result<PlayerList, String> {
+    if server_running {
+        return Err("Stop the server before editing player data".to_string());
+    }
+    PlayerList::load(server_path).await
+}
+
+/// Reads and edits `playerdata/<uuid>.dat` for offline players.
+pub struct PlayerDataEditor {
+    playerdata_path: PathBuf,
+    backup_path: PathBuf,
+}
+
+impl PlayerDataEditor {
+    pub fn new(server_path: &PathBuf, world_name: &str) -> Self {
+        PlayerDataEditor {
+            playerdata_path: server_path.join(world_name).join("playerdata"),
+            backup_path: server_path.join(".mcsm").join("backups").join("playerdata"),
+        }
+    }
+
+    fn player_file(&self, uuid: &str) -> PathBuf {
+        self.playerdata_path.join(format!("{}.dat", uuid))
+    }
+
+    async fn read_player_data(&self, uuid: &str) -> Result<NbtFile, String> {
+        let path = self.player_file(uuid);
+        if !path.exists() {
+            return Err(format!("No player data found for {}", uuid));
+        }
+
+        let bytes = fs::read(&path)
+            .await
+            .map_err(|e| e.to_string())?;
+        NbtFile::from_bytes(&bytes)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub async fn get_inventory(&self, uuid: &str) -> Result<PlayerInventory, String> {
+        let data = self.read_player_data(uuid).await?;
+        let data_version = data_version(&data.root);
+
+        let mut inventory = PlayerInventory {
+            uuid: uuid.to_string(),
+            data_version,
+            inventory: Vec::new(),
+            armor: Vec::new(),
+            offhand: None,
+            ender_chest: Vec::new(),
+        };
+
+        for tag in list_items(&data.root, "Inventory") {
+            let raw_slot = tag.get("Slot").and_then(|s| s.as_i64()).unwrap_or(0);
+            let (section, slot) = match raw_slot {
+                0..=35 => (InventorySection::Inventory, raw_slot),
+                100..=103 => (InventorySection::Armor, raw_slot - 100),
+                OFFHAND_SLOT => (InventorySection::Offhand, 0),
+                _ => continue,
+            };
+            if let Some(item) = parse_item(section, slot as i32, tag) {
+                match section {
+                    InventorySection::Inventory => inventory.inventory.push(item),
+                    InventorySection::Armor => inventory.armor.push(item),
+                    _ => inventory.offhand = Some(item),
+                }
+            }
+        }
+
+        if let Some(equipment) = data.root.get("equipment") {
+            for (slot, key) in ARMOR_KEYS.iter().enumerate() {
+                if let Some(item) = equipment.get(key).and_then(|tag| parse_item(InventorySection::Armor, slot as i32, tag)) {
+                    inventory.armor.push(item);
+                }
+            }
+            if let Some(item) = equipment.get("offhand").and_then(|tag| parse_item(InventorySection::Offhand, 0, tag)) {
+                inventory.offhand = Some(item);
+            }
+        }
+
+        for tag in list_items(&data.root, "EnderItems") {
+            let slot = tag.get("Slot").and_then(|s| s.as_i64()).unwrap_or(0);
+            if let Some(item) = parse_item(InventorySection::EnderChest, slot as i32, tag) {
+                inventory.ender_chest.push(item);
+            }
+        }
+
+        Ok(inventory)
+    }
+
+    /// Removes the item in `section`/`slot` and returns it.
+    pub async fn remove_item(
+        &self,
+        uuid: &str,
+        section: InventorySection,
+        slot: i32,
+        players: &PlayerList,
+    ) -> Result<InventoryItem, String> {
+        let inventory = self.get_inventory(uuid).await?;
+        let existing = find_item(&inventory, section, slot)
+            .cloned()
+            .ok_or_else(|| format!("No item in {:?} slot {}", section, slot))?;
+
+        let mut data = self.read_player_data(uuid).await?;
+        let version = data_version(&data.root);
+        match location(section, slot, version) {
+            ItemLocation::Listed { list, slot } => {
+                if let Some(NbtTag::List { items, .. }) = data.root.get_mut(list) {
+                    items.retain(|tag| tag.get("Slot").and_then(|s| s.as_i64()) != Some(slot));
+                }
+            }
+            ItemLocation::Equipment(key) => {
+                if let Some(equipment) = data.root.get_mut("equipment") {
+                    equipment.remove(key);
+                }
+            }
+        }
+
+        self.write_player_data(uuid, &data, players).await?;
+        Ok(existing)
+    }
+
+    /// Places a new item into an empty slot.
+    pub async fn add_item(&self, uuid: &str, item: InventoryItem, players: &PlayerList) -> Result<(), String> {
+        if item.slot < 0 || item.slot > item.section.max_slot() {
+            return Err(format!("Slot {} is out of range for {:?}", item.slot, item.section));
+        }
+        if item.count < 1 || item.count > 99 {
+            return Err(format!("Invalid item count: {}", item.count));
+        }
+        if item.id.trim().is_empty() {
+            return Err("Item id is required".to_string());
+        }
+
+        let inventory = self.get_inventory(uuid).await?;
+        if find_item(&inventory, item.section, item.slot).is_some() {
+            return Err(format!("{:?} slot {} is already occupied", item.section, item.slot));
+        }
+
+        let mut data = self.read_player_data(uuid).await?;
+        let version = data_version(&data.root);
+        match location(item.section, item.slot, version) {
+            ItemLocation::Listed { list, slot } => {
+                let tag = build_item(&item, version, Some(slot as i8));
+                match data.root.get_mut(list) {
+                    Some(NbtTag::List { element_type, items }) => {
+                        *element_type = 10;
+                        items.push(tag);
+                    }
+                    _ => data.root.insert(list, NbtTag::List { element_type: 10, items: vec![tag] }),
+                }
+            }
+            ItemLocation::Equipment(key) => {
+                let tag = build_item(&item, version, None);
+                match data.root.get_mut("equipment") {
+                    Some(equipment) => equipment.insert(key, tag),
+                    None => data.root.insert("equipment", NbtTag::Compound(vec![(key.to_string(), tag)])),
+                }
+            }
+        }
+
+        self.write_player_data(uuid, &data, players).await
+    }
+
+    /// Copies the current `.dat` into the MCSM backup folder and returns the copy's path.
+    pub async fn backup_player_data(&self, uuid: &str) -> Result<PathBuf, String> {
+        fs::create_dir_all(&self.backup_path)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let backup_file = self.backup_path.join(format!(
+            "{}_{}.dat",
+            uuid,
+            chrono::Local::now().format("%Y%m%d_%H%M%S%3f")
+        ));
+        fs::copy(self.player_file(uuid), &backup_file)
+            .await
+            .map_err(|e| format!("Failed to back up player data: {}", e))?;
+
+        Ok(backup_file)
+    }
+
+    async fn write_player_data(&self, uuid: &str, data: &NbtFile, players: &PlayerList) -> Result<(), String> {
+        if players.get_player(uuid).map(|p| p.is_online).unwrap_or(false) {
+            return Err("Cannot edit player data while the player is online".to_string());
+        }
+
+        self.backup_player_data(uuid).await?;
+
+        // Write next to the original and rename so a failed write can't truncate the file.
+        let path = self.player_file(uuid);
+        let tmp_path = path.with_extension("dat.mcsm-tmp");
+        fs::write(&tmp_path, data.to_bytes()?)
+            .await
+            .map_err(|e| e.to_string())?;
+        fs::rename(&tmp_path, &path)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+}
+
+enum ItemLocation {
+    Listed { list: &'static str, slot: i64 },
+    Equipment(&'static str),
+}
+
+fn location(section: InventorySection, slot: i32, data_version: i64) -> ItemLocation {
+    let equipment = data_version >= EQUIPMENT_DATA_VERSION;
+    match section {
+        InventorySection::Inventory => ItemLocation::Listed { list: "Inventory", slot: slot as i64 },
+        InventorySection::EnderChest => ItemLocation::Listed { list: "EnderItems", slot: slot as i64 },
+        InventorySection::Armor if equipment => ItemLocation::Equipment(ARMOR_KEYS[slot as usize]),
+        InventorySection::Armor => ItemLocation::Listed { list: "Inventory", slot: 100 + slot as i64 },
+        InventorySection::Offhand if equipment => ItemLocation::Equipment("offhand"),
+        InventorySection::Offhand => ItemLocation::Listed { list: "Inventory", slot: OFFHAND_SLOT },
+    }
+}
+
+fn data_version(root: &NbtTag) -> i64 {
+    root.get("DataVersion").and_then(|v| v.as_i64()).unwrap_or(0)
+}
+
+fn list_items<'a>(root: &'a NbtTag, key: &str) -> &'a [NbtTag] {
+    match root.get(key) {
+        Some(NbtTag::List { items, .. }) => items,
+        _ => &[],
+    }
+}
+
+fn find_item(inventory: &PlayerInventory, section: InventorySection, slot: i32) -> Option<&InventoryItem> {
+    match section {
+        InventorySection::Inventory => inventory.inventory.iter().find(|i| i.slot == slot),
+        InventorySection::Armor => inventory.armor.iter().find(|i| i.slot == slot),
+        InventorySection::Offhand => inventory.offhand.as_ref(),
+        InventorySection::EnderChest => inventory.ender_chest.iter().find(|i| i.slot == slot),
+    }
+}
+
+fn parse_item(section: InventorySection, slot: i32, tag: &NbtTag) -> Option<InventoryItem> {
+    let id = tag.get("id")?.as_str()?.to_string();
+    let count = tag
+        .get("count")
+        .or_else(|| tag.get("Count"))
+        .and_then(|c| c.as_i64())
+        .unwrap_or(1) as i32;
+    let components = tag.get("components").or_else(|| tag.get("tag")).cloned();
+
+    Some(InventoryItem { section, slot, id, count, components })
+}
+
+fn build_item(item: &InventoryItem, data_version: i64, slot: Option<i8>) -> NbtTag {
+    let mut tag = NbtTag::Compound(Vec::new());
+    if let Some(slot) = slot {
+        tag.insert("Slot", NbtTag::Byte(slot));
+    }
+    tag.insert("id", NbtTag::String(item.id.clone()));
+
+    if data_version >= COMPONENTS_DATA_VERSION {
+        tag.insert("count", NbtTag::Int(item.count));
+        if let Some(components) = &item.components {
+            tag.insert("components", components.clone());
+        }
+    } else {
+        tag.insert("Count", NbtTag::Byte(item.count as i8));
+        if let Some(components) = &item.components {
+            tag.insert("tag", components.clone());
+        }
+    }
+    tag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::players::Player;
+
+    #[tokio::test]
+    async fn test_edit_offline_inventory() {
+        let server_path = std::env::temp_dir().join("mcsm_test_inventory");
+        let _ = std::fs::remove_dir_all(&server_path);
+        std::fs::create_dir_all(server_path.join("world").join("playerdata")).unwrap();
+
+        let data = NbtFile {
+            name: String::new(),
+            root: NbtTag::Compound(vec![
+                ("DataVersion".to_string(), NbtTag::Int(3700)),
+                ("Inventory".to_string(), NbtTag::List { element_type: 10, items: vec![
+                    NbtTag::Compound(vec![
+                        ("Slot".to_string(), NbtTag::Byte(0)),
+                        ("id".to_string(), NbtTag::String("minecraft:tnt".to_string())),
+                        ("Count".to_string(), NbtTag::Byte(64)),
+                    ]),
+                    NbtTag::Compound(vec![
+                        ("Slot".to_string(), NbtTag::Byte(103)),
+                        ("id".to_string(), NbtTag::String("minecraft:diamond_helmet".to_string())),
+                        ("Count".to_string(), NbtTag::Byte(1)),
+                    ]),
+                ] }),
+                ("EnderItems".to_string(), NbtTag::List { element_type: 0, items: Vec::new() }),
+            ]),
+            compressed: true,
+        };
+        std::fs::write(
+            server_path.join("world").join("playerdata").join("uuid-griefer.dat"),
+            data.to_bytes().unwrap(),
+        ).unwrap();
+
+        let editor = PlayerDataEditor::new(&server_path, "world");
+        let mut players = PlayerList::new();
+
+        let inventory = editor.get_inventory("uuid-griefer").await.unwrap();
+        assert_eq!(inventory.inventory[0].id, "minecraft:tnt");
+        assert_eq!(inventory.armor[0].slot, 3);
+
+        let removed = editor.remove_item("uuid-griefer", InventorySection::Inventory, 0, &players).await.unwrap();
+        assert_eq!(removed.count, 64);
+
+        editor.add_item("uuid-griefer", InventoryItem {
+            section: InventorySection::EnderChest,
+            slot: 4,
+            id: "minecraft:dirt".to_string(),
+            count: 1,
+            components: None,
+        }, &players).await.unwrap();
+
+        let inventory = editor.get_inventory("uuid-griefer").await.unwrap();
+        assert!(inventory.inventory.is_empty());
+        assert_eq!(inventory.ender_chest[0].slot, 4);
+        assert_eq!(std::fs::read_dir(server_path.join(".mcsm").join("backups").join("playerdata")).unwrap().count(), 2);
+
+        players.add_player(Player {
+            uuid: "uuid-griefer".to_string(),
+            name: "Griefer".to_string(),
+            is_online: true,
+            is_op: false,
+            last_seen: String::new(),
+            playtime: 0,
+            deaths: 0,
+            kills: 0,
+        });
+        assert!(editor.remove_item("uuid-griefer", InventorySection::EnderChest, 4, &players).await.is_err());
+
+        assert_eq!(
+            players_for_edit(&server_path, true).await.unwrap_err(),
+            "Stop the server before editing player data"
+        );
+        let players = players_for_edit(&server_path, false).await.unwrap();
+        assert!(editor.remove_item("uuid-griefer", InventorySection::EnderChest, 4, &players).await.is_ok());
+    }
+}
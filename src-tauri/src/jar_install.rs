@@ -0,0 +1,215 @@
+use std::io::{Cursor, Read};
+use std::path::PathBuf;
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use tokio::fs;
+use crate::jar_scan::{blocked, JarScanner, ScanFinding};
+use crate::lockfile::{sha256_hex, sha512_hex, ExtensionKind, ExtensionSource, LockEntry, Lockfile};
+use crate::plugins::PluginActionOutcome;
+
+/// A plugin or mod jar that was installed or upgraded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallResult {
+    pub kind: ExtensionKind,
+    /// The plugin name or mod id.
+    pub name: String,
+    pub version: String,
+    pub file_path: PathBuf,
+    pub sha256: String,
+    /// The version that was replaced, when this was an upgrade.
+    pub upgraded_from: Option<String>,
+    pub outcome: PluginActionOutcome,
+    /// What the jar scanner flagged; see `JarScanner`.
+    pub findings: Vec<ScanFinding>,
+}
+
+/// A downloaded or copied jar, written to a hidden file next to where it will go so
+/// placing it is a rename.
+pub struct StagedJar {
+    pub bytes: Vec<u8>,
+    pub file_name: String,
+    pub sha256: String,
+    temp_path: PathBuf,
+}
+
+fn file_name_of(source: &ExtensionSource) -> Option<String> {
+    let name = match source {
+        ExtensionSource::Url { url } => url
+            .split(['?', '#'])
+            .next()
+            .and_then(|u| u.rsplit('/').next())
+            .map(|s| s.to_string()),
+        ExtensionSource::File { path } => path.file_name().map(|n| n.to_string_lossy().into_owned()),
+        ExtensionSource::Modrinth { file_name, .. } | ExtensionSource::Hangar { file_name, .. } => Some(file_name.clone()),
+        ExtensionSource::Modpack { path, .. } => path.rsplit('/').next().map(|s| s.to_string()),
+    }?;
+    Some(name).filter(|n| n.ends_with(".jar") && !n.starts_with('.') && !n.contains(['/', '\\']))
+}
+
+/// Fetches the jar at `source` into `folder` and checks it against `expected_hash`, a
+/// hex SHA-256 or SHA-512, when given. Nothing is left behind on failure.
+pub async fn stage_jar(source: &ExtensionSource, expected_hash: Option<&str>, folder: &PathBuf) -> Result<StagedJar, String> {
+    let file_name = file_name_of(source).ok_or("The source doesn't name a .jar file")?;
+    let bytes = match source {
+        ExtensionSource::Url { url }
+        | ExtensionSource::Modrinth { url, .. }
+        | ExtensionSource::Hangar { url, .. } => {
+            let response = reqwest::get(url)
+                .await
+                .map_err(|e| format!("Failed to download {}: {}", file_name, e))?;
+            if !response.status().is_success() {
+                return Err(format!("Failed to download {}: HTTP {}", file_name, response.status()));
+            }
+            response.bytes().await.map_err(|e| e.to_string())?.to_vec()
+        }
+        ExtensionSource::File { path } => fs::read(path)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?,
+        ExtensionSource::Modpack { pack, path } => {
+            let archive = fs::read(pack)
+                .await
+                .map_err(|e| format!("Failed to read {}: {}", pack.display(), e))?;
+            let mut zip = zip::ZipArchive::new(Cursor::new(archive)).map_err(|e| e.to_string())?;
+            let mut entry = zip
+                .by_name(path)
+                .map_err(|e| format!("{} has no {}: {}", pack.display(), path, e))?;
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+            bytes
+        }
+    };
+
+    if let Some(expected) = expected_hash.map(str::trim) {
+        let actual = match expected.len() {
+            64 => sha256_hex(&bytes),
+            128 => sha512_hex(&bytes),
+            _ => return Err(format!("Unsupported hash for {}: {}", file_name, expected)),
+        };
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(format!("Checksum mismatch for {}: expected {}, got {}", file_name, expected, actual));
+        }
+    }
+
+    StagedJar::write(bytes, file_name, folder).await
+}
+
+impl StagedJar {
+    /// Stages jar bytes that were fetched some other way.
+    pub async fn write(bytes: Vec<u8>, file_name: String, folder: &PathBuf) -> Result<Self, String> {
+        fs::create_dir_all(folder).await.map_err(|e| e.to_string())?;
+        let temp_path = folder.join(format!(".{}.part", file_name));
+        fs::write(&temp_path, &bytes).await.map_err(|e| e.to_string())?;
+        Ok(StagedJar { sha256: sha256_hex(&bytes), bytes, file_name, temp_path })
+    }
+
+    /// Moves the jar to `target`. `replacing` is the jar of the version being upgraded,
+    /// which is removed once the new one is in place; any other file already at
+    /// `target` is an error.
+    pub async fn place(self, target: &PathBuf, replacing: Option<&PathBuf>) -> Result<(), String> {
+        if target.exists() && replacing != Some(target) {
+            self.discard().await;
+            return Err(format!("{} already exists", target.display()));
+        }
+        if let Err(e) = fs::rename(&self.temp_path, target).await {
+            self.discard().await;
+            return Err(e.to_string());
+        }
+        if let Some(old) = replacing.filter(|old| *old != target) {
+            fs::remove_file(old).await.map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Scans the jar, refusing it when it's on the blocklist. Other findings become
+    /// warnings on `outcome`.
+    pub async fn scan(self, scanner: &JarScanner, outcome: &mut PluginActionOutcome) -> Result<(Self, Vec<ScanFinding>), String> {
+        let findings = match scanner.check(&self.bytes, &self.file_name).await {
+            Ok(findings) => findings,
+            Err(e) => {
+                self.discard().await;
+                return Err(e);
+            }
+        };
+        if let Some(finding) = blocked(&findings) {
+            let message = format!("Refusing to install {}: {}", finding.file, finding.details);
+            self.discard().await;
+            return Err(message);
+        }
+        outcome
+            .warnings
+            .extend(findings.iter().map(|f| format!("{}: {}", f.file, f.details)));
+        Ok((self, findings))
+    }
+
+    pub async fn discard(self) {
+        let _ = fs::remove_file(&self.temp_path).await;
+    }
+}
+
+/// Records an install in the server's lockfile.
+pub async fn record_install(server_path: &PathBuf, result: &InstallResult, source: ExtensionSource) -> Result<(), String> {
+    let mut lockfile = Lockfile::load(server_path).await?;
+    lockfile.upsert(LockEntry {
+        name: result.name.clone(),
+        kind: result.kind,
+        version: result.version.clone(),
+        file_name: result
+            .file_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        sha256: result.sha256.clone(),
+        source,
+        installed_at: Utc::now(),
+    });
+    lockfile.save(server_path).await
+}
+
+/// Lock entries for installed jars, given as `(name, version, file)`, as they are now:
+/// the server's own entry when the jar still matches it, otherwise the jar itself as
+/// the source.
+pub async fn export_entries(server_path: &PathBuf, kind: ExtensionKind, installed: &[(String, String, PathBuf)]) -> Result<Vec<LockEntry>, String> {
+    let lockfile = Lockfile::load(server_path).await?;
+    let mut entries = Vec::new();
+    for (name, version, file) in installed {
+        let bytes = fs::read(file).await.map_err(|e| e.to_string())?;
+        let sha256 = sha256_hex(&bytes);
+        match lockfile.get(kind, name) {
+            Some(entry) if entry.sha256.eq_ignore_ascii_case(&sha256) => entries.push(entry.clone()),
+            _ => entries.push(LockEntry {
+                name: name.clone(),
+                kind,
+                version: version.clone(),
+                file_name: file
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                sha256,
+                source: ExtensionSource::File { path: file.clone() },
+                installed_at: Utc::now(),
+            }),
+        }
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+/// Whether `file` is the jar `entry` locks.
+pub async fn matches_entry(file: &PathBuf, entry: &LockEntry) -> bool {
+    match fs::read(file).await {
+        Ok(bytes) => sha256_hex(&bytes).eq_ignore_ascii_case(&entry.sha256),
+        Err(_) => false,
+    }
+}
+
+/// Adds entries for jars that were already in place to the server's lockfile.
+pub async fn record_existing(server_path: &PathBuf, entries: Vec<LockEntry>) -> Result<(), String> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut lockfile = Lockfile::load(server_path).await?;
+    for entry in entries {
+        lockfile.upsert(entry);
+    }
+    lockfile.save(server_path).await
+}
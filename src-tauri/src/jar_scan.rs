@@ -0,0 +1,433 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read};
+use std::path::PathBuf;
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use crate::lockfile::{jar_folders, list_jars, sha256_hex, ExtensionKind};
+use crate::security::{SecurityEvent, SecurityEventType, SecuritySeverity};
+
+pub const BLOCKLIST_NAME: &str = "jar_blocklist.txt";
+pub const SECURITY_LOG_NAME: &str = "security_events.jsonl";
+
+/// Jars inside jars (Fabric's META-INF/jars, shaded plugins) are scanned this deep.
+const MAX_NESTING: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ScanRule {
+    /// The jar's SHA-256 is on the local blocklist.
+    Blocklisted,
+    /// Starts external processes.
+    ProcessExecution,
+    /// Loads classes in code that also fetches data over the network.
+    RemoteClassLoading,
+    /// Defines classes from decoded or decrypted bytes.
+    ObfuscatedLoader,
+    /// Writes jar files.
+    JarModification,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanFinding {
+    /// The jar, with `!/` separating nested jars.
+    pub file: String,
+    /// The class the pattern was found in.
+    pub class: Option<String>,
+    pub rule: ScanRule,
+    pub severity: SecuritySeverity,
+    pub details: String,
+}
+
+impl ScanFinding {
+    pub fn to_event(&self) -> SecurityEvent {
+        SecurityEvent {
+            timestamp: Utc::now(),
+            event_type: SecurityEventType::PluginInstallation,
+            source: self.file.clone(),
+            details: match &self.class {
+                Some(class) => format!("{} ({})", self.details, class),
+                None => self.details.clone(),
+            },
+            severity: self.severity,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JarScanReport {
+    pub file: PathBuf,
+    pub sha256: String,
+    pub findings: Vec<ScanFinding>,
+}
+
+/// The method references and string constants of a class file.
+#[derive(Default)]
+struct ClassRefs {
+    /// `owner.name`, e.g. `java/lang/Runtime.exec`.
+    methods: HashSet<String>,
+    strings: Vec<String>,
+}
+
+impl ClassRefs {
+    fn calls(&self, method: &str) -> bool {
+        self.methods.contains(method)
+    }
+
+    fn calls_any(&self, methods: &[&str]) -> bool {
+        methods.iter().any(|m| self.calls(m))
+    }
+}
+
+/// Reads the constant pool of a class file. Returns `None` for anything that isn't one.
+fn class_refs(bytes: &[u8]) -> Option<ClassRefs> {
+    let u2 = |i: usize| -> Option<usize> { Some(u16::from_be_bytes([*bytes.get(i)?, *bytes.get(i + 1)?]) as usize) };
+    if bytes.get(0..4)? != [0xCA, 0xFE, 0xBA, 0xBE] {
+        return None;
+    }
+    let count = u2(8)?;
+    let mut utf8: Vec<Option<String>> = vec![None; count];
+    // (tag, first index, second index) for the entries that point at others.
+    let mut entries = vec![(0u8, 0usize, 0usize); count];
+    let mut pos = 10;
+    let mut i = 1;
+    while i < count {
+        let tag = *bytes.get(pos)?;
+        match tag {
+            1 => {
+                let len = u2(pos + 1)?;
+                utf8[i] = Some(String::from_utf8_lossy(bytes.get(pos + 3..pos + 3 + len)?).into_owned());
+                pos += 3 + len;
+            }
+            7 | 8 | 16 | 19 | 20 => {
+                entries[i] = (tag, u2(pos + 1)?, 0);
+                pos += 3;
+            }
+            9..=12 | 17 | 18 => {
+                entries[i] = (tag, u2(pos + 1)?, u2(pos + 3)?);
+                pos += 5;
+            }
+            3 | 4 => pos += 5,
+            // Longs and doubles take two slots.
+            5 | 6 => {
+                pos += 9;
+                i += 1;
+            }
+            15 => pos += 4,
+            _ => return None,
+        }
+        i += 1;
+    }
+
+    let text = |i: usize| utf8.get(i).and_then(|s| s.as_deref());
+    let mut refs = ClassRefs::default();
+    for &(tag, a, b) in &entries {
+        match tag {
+            8 => refs.strings.extend(text(a).map(str::to_string)),
+            10 | 11 => {
+                let owner = entries.get(a).filter(|e| e.0 == 7).and_then(|e| text(e.1));
+                let name = entries.get(b).filter(|e| e.0 == 12).and_then(|e| text(e.1));
+                if let (Some(owner), Some(name)) = (owner, name) {
+                    refs.methods.insert(format!("{}.{}", owner, name));
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(refs)
+}
+
+const PROCESS: &[&str] = &["java/lang/Runtime.exec", "java/lang/ProcessBuilder.start"];
+const CLASS_LOADING: &[&str] = &[
+    "java/lang/Class.forName",
+    "java/lang/ClassLoader.loadClass",
+    "java/net/URLClassLoader.<init>",
+    "java/net/URLClassLoader.newInstance",
+];
+const NETWORK: &[&str] = &[
+    "java/net/URL.openStream",
+    "java/net/URL.openConnection",
+    "java/net/Socket.getInputStream",
+    "java/net/http/HttpClient.send",
+    "java/net/http/HttpClient.sendAsync",
+];
+const DECODING: &[&str] = &[
+    "java/util/Base64$Decoder.decode",
+    "javax/crypto/Cipher.doFinal",
+    "java/util/zip/Inflater.inflate",
+];
+const JAR_WRITERS: &[&str] = &["java/util/jar/JarOutputStream.<init>", "java/util/zip/ZipOutputStream.<init>"];
+
+/// The suspicious patterns in one class.
+fn check_class(refs: &ClassRefs) -> Vec<(ScanRule, SecuritySeverity, &'static str)> {
+    let mut found = Vec::new();
+    if refs.calls_any(PROCESS) {
+        found.push((ScanRule::ProcessExecution, SecuritySeverity::Warning, "Runs external commands"));
+    }
+    let defines = refs.methods.iter().any(|m| m.ends_with(".defineClass"));
+    if (defines || refs.calls_any(CLASS_LOADING)) && refs.calls_any(NETWORK) {
+        found.push((ScanRule::RemoteClassLoading, SecuritySeverity::Error, "Loads classes and downloads data in the same class"));
+    }
+    if defines && refs.calls_any(DECODING) {
+        found.push((ScanRule::ObfuscatedLoader, SecuritySeverity::Error, "Defines classes from decoded bytes"));
+    }
+    if refs.calls_any(JAR_WRITERS) && refs.strings.iter().any(|s| s.contains(".jar")) {
+        found.push((ScanRule::JarModification, SecuritySeverity::Error, "Writes jar files"));
+    }
+    found
+}
+
+/// Scans jars for known-bad hashes and for class patterns common in backdoored
+/// plugins. Patterns are hints, not proof; only blocklisted jars are refused.
+#[derive(Debug, Clone, Default)]
+pub struct JarScanner {
+    /// Lowercase SHA-256 to the reason it was blocked.
+    blocklist: HashMap<String, String>,
+    log_path: Option<PathBuf>,
+}
+
+impl JarScanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a blocklist of one SHA-256 per line, optionally followed by a note.
+    /// Blank lines and lines starting with `#` are ignored; a missing file is empty.
+    pub async fn load(path: &PathBuf) -> Result<Self, String> {
+        let mut scanner = Self::new();
+        if !path.exists() {
+            return Ok(scanner);
+        }
+        let content = fs::read_to_string(path).await.map_err(|e| e.to_string())?;
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (hash, note) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            scanner.block(hash, note.trim());
+        }
+        Ok(scanner)
+    }
+
+    /// Logs the findings of `check` and `scan_server` to `log_path`.
+    pub fn with_log(mut self, log_path: &PathBuf) -> Self {
+        self.log_path = Some(log_path.clone());
+        self
+    }
+
+    pub fn block(&mut self, sha256: &str, note: &str) {
+        self.blocklist.insert(sha256.to_ascii_lowercase(), note.to_string());
+    }
+
+    pub fn scan(&self, bytes: &[u8], file_name: &str) -> Vec<ScanFinding> {
+        let mut findings = Vec::new();
+        self.scan_into(bytes, file_name, 0, &mut findings);
+        findings
+    }
+
+    /// Scans a jar and logs what was found.
+    pub async fn check(&self, bytes: &[u8], file_name: &str) -> Result<Vec<ScanFinding>, String> {
+        let findings = self.scan(bytes, file_name);
+        if let Some(log_path) = &self.log_path {
+            log_findings(log_path, &findings).await?;
+        }
+        Ok(findings)
+    }
+
+    fn scan_into(&self, bytes: &[u8], file: &str, depth: usize, findings: &mut Vec<ScanFinding>) {
+        if let Some(note) = self.blocklist.get(&sha256_hex(bytes)) {
+            findings.push(ScanFinding {
+                file: file.to_string(),
+                class: None,
+                rule: ScanRule::Blocklisted,
+                severity: SecuritySeverity::Critical,
+                details: if note.is_empty() {
+                    "On the jar blocklist".to_string()
+                } else {
+                    format!("On the jar blocklist: {}", note)
+                },
+            });
+        }
+        let Ok(mut zip) = zip::ZipArchive::new(Cursor::new(bytes)) else {
+            return;
+        };
+        for i in 0..zip.len() {
+            let Ok(mut entry) = zip.by_index(i) else {
+                continue;
+            };
+            let name = entry.name().to_string();
+            let nested = name.ends_with(".jar") && depth < MAX_NESTING;
+            if !name.ends_with(".class") && !nested {
+                continue;
+            }
+            let mut content = Vec::new();
+            if entry.read_to_end(&mut content).is_err() {
+                continue;
+            }
+            if nested {
+                self.scan_into(&content, &format!("{}!/{}", file, name), depth + 1, findings);
+            } else if let Some(refs) = class_refs(&content) {
+                for (rule, severity, details) in check_class(&refs) {
+                    findings.push(ScanFinding {
+                        file: file.to_string(),
+                        class: Some(name.trim_end_matches(".class").replace('/', ".")),
+                        rule,
+                        severity,
+                        details: details.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Scans every plugin and mod jar in a server, disabled plugins included, and logs
+    /// what was found.
+    pub async fn scan_server(&self, server_path: &PathBuf) -> Result<Vec<JarScanReport>, String> {
+        let mut reports = Vec::new();
+        for kind in [ExtensionKind::Plugin, ExtensionKind::Mod] {
+            for folder in jar_folders(server_path, kind) {
+                for file in list_jars(&folder).await? {
+                    let bytes = fs::read(&file).await.map_err(|e| e.to_string())?;
+                    let name = file.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                    reports.push(JarScanReport {
+                        sha256: sha256_hex(&bytes),
+                        findings: self.check(&bytes, &name).await?,
+                        file,
+                    });
+                }
+            }
+        }
+        Ok(reports)
+    }
+}
+
+/// The blocklist reason when `findings` include a blocklisted jar.
+pub fn blocked(findings: &[ScanFinding]) -> Option<&ScanFinding> {
+    findings.iter().find(|f| f.rule == ScanRule::Blocklisted)
+}
+
+/// Appends findings to a JSON-lines security log as `PluginInstallation` events.
+pub async fn log_findings(log_path: &PathBuf, findings: &[ScanFinding]) -> Result<(), String> {
+    if findings.is_empty() {
+        return Ok(());
+    }
+    if let Some(parent) = log_path.parent() {
+        fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+    }
+    let mut lines = String::new();
+    for finding in findings {
+        lines.push_str(&serde_json::to_string(&finding.to_event()).map_err(|e| e.to_string())?);
+        lines.push('\n');
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+        .await
+        .map_err(|e| e.to_string())?;
+    file.write_all(lines.as_bytes()).await.map_err(|e| e.to_string())?;
+    file.flush().await.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// A class file whose constant pool references `methods` and holds `strings`.
+    fn class_file(methods: &[(&str, &str)], strings: &[&str]) -> Vec<u8> {
+        let mut pool: Vec<Vec<u8>> = Vec::new();
+        let mut add = |entry: Vec<u8>| {
+            pool.push(entry);
+            pool.len() as u16
+        };
+        let utf8 = |s: &str| {
+            let mut entry = vec![1];
+            entry.extend((s.len() as u16).to_be_bytes());
+            entry.extend(s.as_bytes());
+            entry
+        };
+        let pair = |tag: u8, a: u16, b: u16| [vec![tag], a.to_be_bytes().to_vec(), b.to_be_bytes().to_vec()].concat();
+        for (owner, name) in methods {
+            let owner_name = add(utf8(owner));
+            let class = add([vec![7], owner_name.to_be_bytes().to_vec()].concat());
+            let method_name = add(utf8(name));
+            let descriptor = add(utf8("()V"));
+            let name_and_type = add(pair(12, method_name, descriptor));
+            add(pair(10, class, name_and_type));
+        }
+        add(vec![5, 0, 0, 0, 0, 0, 0, 0, 42]);
+        add(Vec::new());
+        for s in strings {
+            let text = add(utf8(s));
+            add([vec![8], text.to_be_bytes().to_vec()].concat());
+        }
+        let mut class = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61];
+        class.extend((pool.len() as u16 + 1).to_be_bytes());
+        class.extend(pool.concat());
+        class
+    }
+
+    fn jar(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut jar = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in entries {
+            jar.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            jar.write_all(content).unwrap();
+        }
+        jar.finish().unwrap().into_inner()
+    }
+
+    #[tokio::test]
+    async fn test_scan_jars() {
+        let clean = jar(&[
+            ("plugin.yml", b"name: Clean\n".to_vec()),
+            ("a/Clean.class", class_file(&[("java/lang/Class", "forName"), ("org/bukkit/Bukkit", "getServer")], &["config.yml"])),
+        ]);
+        let scanner = JarScanner::new();
+        assert!(scanner.scan(&clean, "Clean.jar").is_empty());
+
+        let loader = class_file(
+            &[("java/net/URL", "openStream"), ("java/lang/ClassLoader", "defineClass"), ("java/util/Base64$Decoder", "decode")],
+            &[],
+        );
+        let infector = class_file(&[("java/util/jar/JarOutputStream", "<init>"), ("java/lang/Runtime", "exec")], &["plugins", ".jar"]);
+        let library = jar(&[("b/Infect.class", infector)]);
+        let bad = jar(&[("a/Loader.class", loader), ("META-INF/jars/lib.jar", library.clone())]);
+        let findings = scanner.scan(&bad, "Bad.jar");
+        let rules: Vec<(&str, ScanRule)> = findings.iter().map(|f| (f.file.as_str(), f.rule)).collect();
+        assert_eq!(rules, [
+            ("Bad.jar", ScanRule::RemoteClassLoading),
+            ("Bad.jar", ScanRule::ObfuscatedLoader),
+            ("Bad.jar!/META-INF/jars/lib.jar", ScanRule::ProcessExecution),
+            ("Bad.jar!/META-INF/jars/lib.jar", ScanRule::JarModification),
+        ]);
+        assert_eq!(findings[0].class.as_deref(), Some("a.Loader"));
+        assert!(blocked(&findings).is_none());
+
+        let dir = std::env::temp_dir().join("mcsm_test_scan_jars");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(BLOCKLIST_NAME),
+            format!("# known backdoors\n\n{}  fractureiser stage 0\n", sha256_hex(&library).to_uppercase()),
+        )
+        .unwrap();
+        let scanner = JarScanner::load(&dir.join(BLOCKLIST_NAME)).await.unwrap();
+        let findings = scanner.scan(&bad, "Bad.jar");
+        let blocked = blocked(&findings).unwrap();
+        assert_eq!(blocked.file, "Bad.jar!/META-INF/jars/lib.jar");
+        assert_eq!(blocked.details, "On the jar blocklist: fractureiser stage 0");
+        assert_eq!(blocked.severity, SecuritySeverity::Critical);
+
+        let log = dir.join(SECURITY_LOG_NAME);
+        scanner.with_log(&log).check(&bad, "Bad.jar").await.unwrap();
+        let events: Vec<SecurityEvent> = std::fs::read_to_string(&log)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(events.len(), 5);
+        assert!(events.iter().all(|e| e.event_type == SecurityEventType::PluginInstallation));
+        assert_eq!(events[0].details, "Loads classes and downloads data in the same class (a.Loader)");
+    }
+}
@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use tokio::fs;
+use tokio::process::Command;
+use crate::jvm::parse_java_major;
+use crate::version::MinecraftVersion;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JavaVendor {
+    Temurin,
+    Zulu,
+    Corretto,
+    GraalVM,
+    Oracle,
+    Microsoft,
+    Liberica,
+    Semeru,
+    OpenJDK,
+    Other(String),
+}
+
+impl JavaVendor {
+    /// Maps the `IMPLEMENTOR` of a `release` file (or the `java.vendor` property) to a vendor.
+    fn from_implementor(implementor: &str, vm_name: &str) -> Self {
+        let lower = implementor.to_lowercase();
+        if lower.contains("graalvm") || vm_name.contains("GraalVM") {
+            JavaVendor::GraalVM
+        } else if lower.contains("adoptium") || lower.contains("temurin") {
+            JavaVendor::Temurin
+        } else if lower.contains("azul") {
+            JavaVendor::Zulu
+        } else if lower.contains("amazon") {
+            JavaVendor::Corretto
+        } else if lower.contains("microsoft") {
+            JavaVendor::Microsoft
+        } else if lower.contains("bellsoft") {
+            JavaVendor::Liberica
+        } else if lower.contains("ibm") || lower.contains("international business machines") {
+            JavaVendor::Semeru
+        } else if lower.contains("oracle") {
+            JavaVendor::Oracle
+        } else if lower.is_empty() || lower == "n/a" || lower.contains("openjdk") {
+            JavaVendor::OpenJDK
+        } else {
+            JavaVendor::Other(implementor.to_string())
+        }
+    }
+}
+
+/// A Java runtime found on this machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaInfo {
+    /// Canonical path of the `java` executable.
+    pub path: PathBuf,
+    /// The runtime's home directory (the parent of `bin`).
+    pub home: PathBuf,
+    pub version: String,
+    pub major_version: u32,
+    pub vendor: JavaVendor,
+    /// Normalised CPU architecture: `x86_64`, `aarch64`, `x86`, ...
+    pub architecture: String,
+    pub is_jdk: bool,
+}
+
+impl JavaInfo {
+    pub fn is_64bit(&self) -> bool {
+        matches!(self.architecture.as_str(), "x86_64" | "aarch64" | "ppc64le" | "s390x" | "riscv64")
+    }
+}
+
+pub(crate) fn java_executable_name() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "java.exe"
+    } else {
+        "java"
+    }
+}
+
+fn normalize_arch(arch: &str) -> String {
+    match arch.trim() {
+        "amd64" | "x86_64" | "x64" => "x86_64".to_string(),
+        "aarch64" | "arm64" => "aarch64".to_string(),
+        "x86" | "i386" | "i586" | "i686" => "x86".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Parses a `release` file (`KEY="value"` lines) or `-XshowSettings:properties` output
+/// (`key = value` lines).
+fn parse_key_values(text: &str, separator: char) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(separator)?;
+            Some((key.trim().to_string(), value.trim().trim_matches('"').to_string()))
+        })
+        .collect()
+}
+
+/// Finds Java runtimes in JAVA_HOME, PATH, the usual install folders, SDKMAN and asdf,
+/// listing each runtime once however many ways it can be reached.
+#[derive(Debug, Clone)]
+pub struct JavaDetector {
+    search_dirs: Vec<PathBuf>,
+    search_environment: bool,
+}
+
+impl JavaDetector {
+    pub fn new() -> Self {
+        let mut search_dirs: Vec<PathBuf> = if cfg!(target_os = "windows") {
+            [
+                "C:\\Program Files\\Java",
+                "C:\\Program Files (x86)\\Java",
+                "C:\\Program Files\\Eclipse Adoptium",
+                "C:\\Program Files\\Zulu",
+                "C:\\Program Files\\Amazon Corretto",
+                "C:\\Program Files\\Microsoft",
+                "C:\\Program Files\\BellSoft",
+            ]
+            .iter()
+            .map(PathBuf::from)
+            .collect()
+        } else {
+            [
+                "/usr/lib/jvm",
+                "/usr/java",
+                "/opt/java",
+                "/opt",
+                "/Library/Java/JavaVirtualMachines",
+            ]
+            .iter()
+            .map(PathBuf::from)
+            .collect()
+        };
+
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")).map(PathBuf::from);
+        let sdkman = std::env::var_os("SDKMAN_DIR")
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|h| h.join(".sdkman")));
+        let asdf = std::env::var_os("ASDF_DATA_DIR")
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|h| h.join(".asdf")));
+        search_dirs.extend(sdkman.map(|d| d.join("candidates").join("java")));
+        search_dirs.extend(asdf.map(|d| d.join("installs").join("java")));
+
+        JavaDetector { search_dirs, search_environment: true }
+    }
+
+    /// A detector that only looks in `dirs`, without JAVA_HOME or PATH.
+    pub fn with_search_dirs(dirs: Vec<PathBuf>) -> Self {
+        JavaDetector { search_dirs: dirs, search_environment: false }
+    }
+
+    /// Also searches `dir`, whose subfolders are runtime homes.
+    pub fn add_search_dir(&mut self, dir: PathBuf) {
+        self.search_dirs.push(dir);
+    }
+
+    fn environment_candidates(&self) -> Vec<PathBuf> {
+        let mut candidates = Vec::new();
+        if !self.search_environment {
+            return candidates;
+        }
+        if let Some(java_home) = std::env::var_os("JAVA_HOME") {
+            candidates.push(PathBuf::from(java_home).join("bin").join(java_executable_name()));
+        }
+        if let Some(path) = std::env::var_os("PATH") {
+            for dir in std::env::split_paths(&path) {
+                candidates.push(dir.join(java_executable_name()));
+            }
+        }
+        candidates
+    }
+
+    async fn directory_candidates(&self) -> Vec<PathBuf> {
+        let mut candidates = Vec::new();
+        for dir in &self.search_dirs {
+            let Ok(mut entries) = fs::read_dir(dir).await else {
+                continue;
+            };
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let home = entry.path();
+                // macOS bundles keep the runtime in Contents/Home.
+                for home in [home.clone(), home.join("Contents").join("Home")] {
+                    candidates.push(home.join("bin").join(java_executable_name()));
+                }
+            }
+        }
+        candidates
+    }
+
+    pub async fn detect(&self) -> Vec<JavaInfo> {
+        let mut candidates = self.environment_candidates();
+        candidates.extend(self.directory_candidates().await);
+
+        let mut seen = Vec::new();
+        let mut runtimes = Vec::new();
+        for candidate in candidates {
+            // Canonicalising resolves /usr/bin/java, `current` links and the like.
+            let Ok(executable) = fs::canonicalize(&candidate).await else {
+                continue;
+            };
+            if seen.contains(&executable) {
+                continue;
+            }
+            seen.push(executable.clone());
+            if let Some(info) = Self::inspect(&executable).await {
+                runtimes.push(info);
+            }
+        }
+
+        runtimes.sort_by(|a, b| b.major_version.cmp(&a.major_version).then(a.path.cmp(&b.path)));
+        runtimes
+    }
+
+    /// Describes the runtime of a `java` executable, preferring its `release` file and
+    /// falling back to asking the JVM.
+    pub async fn inspect(executable: &Path) -> Option<JavaInfo> {
+        let executable = fs::canonicalize(executable).await.ok()?;
+        if !executable.is_file() {
+            return None;
+        }
+        let home = executable.parent()?.parent()?.to_path_buf();
+        let is_jdk = home
+            .join("bin")
+            .join(if cfg!(target_os = "windows") { "javac.exe" } else { "javac" })
+            .exists();
+
+        let (version, implementor, vm_name, arch) = match fs::read_to_string(home.join("release")).await {
+            Ok(release) => {
+                let values = parse_key_values(&release, '=');
+                let vm_name = if values.contains_key("GRAALVM_VERSION") { "GraalVM" } else { "" };
+                (
+                    values.get("JAVA_VERSION")?.clone(),
+                    values.get("IMPLEMENTOR").cloned().unwrap_or_default(),
+                    vm_name.to_string(),
+                    values.get("OS_ARCH").cloned().unwrap_or_default(),
+                )
+            }
+            Err(_) => {
+                let output = Command::new(&executable)
+                    .args(["-XshowSettings:properties", "-version"])
+                    .output()
+                    .await
+                    .ok()?;
+                let values = parse_key_values(&String::from_utf8_lossy(&output.stderr), '=');
+                (
+                    values.get("java.version")?.clone(),
+                    values.get("java.vendor").cloned().unwrap_or_default(),
+                    values.get("java.vm.name").cloned().unwrap_or_default(),
+                    values.get("os.arch").cloned().unwrap_or_default(),
+                )
+            }
+        };
+
+        Some(JavaInfo {
+            major_version: parse_java_major(&version)?,
+            vendor: JavaVendor::from_implementor(&implementor, &vm_name),
+            architecture: normalize_arch(&arch),
+            path: executable,
+            home,
+            version,
+            is_jdk,
+        })
+    }
+}
+
+impl Default for JavaDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Java major version a Minecraft release needs: 8 up to 1.16, 16 for 1.17, 17 from
+/// 1.18 and 21 from 1.20.5. Snapshots and other unparseable versions get the newest.
+pub fn required_java_major(minecraft_version: &str) -> u32 {
+    match MinecraftVersion::parse(minecraft_version) {
+        Ok(v) if v < MinecraftVersion::new(1, 17, 0) => 8,
+        Ok(v) if v < MinecraftVersion::new(1, 18, 0) => 16,
+        Ok(v) if v < MinecraftVersion::new(1, 20, 5) => 17,
+        _ => 21,
+    }
+}
+
+/// Picks the runtime for `required_major`: that exact version if installed, otherwise
+/// the oldest newer one, preferring 64-bit runtimes.
+pub fn select_runtime(runtimes: &[JavaInfo], required_major: u32) -> Option<&JavaInfo> {
+    runtimes
+        .iter()
+        .filter(|r| r.major_version >= required_major)
+        .min_by_key(|r| (r.major_version, !r.is_64bit()))
+}
+
+/// Resolves the Java a server should run with: its pinned runtime if set, otherwise the
+/// best detected one for its Minecraft version.
+pub async fn resolve_java(detector: &JavaDetector, minecraft_version: &str, pinned: Option<&Path>) -> Result<JavaInfo, String> {
+    let required = required_java_major(minecraft_version);
+
+    if let Some(pinned) = pinned {
+        let runtime = JavaDetector::inspect(pinned)
+            .await
+            .ok_or_else(|| format!("The pinned Java runtime {} doesn't exist or isn't working", pinned.display()))?;
+        if runtime.major_version < required {
+            return Err(format!(
+                "The pinned Java runtime {} is Java {}, but Minecraft {} needs Java {} or newer",
+                runtime.path.display(), runtime.major_version, minecraft_version, required
+            ));
+        }
+        return Ok(runtime);
+    }
+
+    let runtimes = detector.detect().await;
+    if let Some(runtime) = select_runtime(&runtimes, required) {
+        return Ok(runtime.clone());
+    }
+
+    let installed = if runtimes.is_empty() {
+        "none was found".to_string()
+    } else {
+        let found: Vec<String> = runtimes
+            .iter()
+            .map(|r| format!("Java {} at {}", r.major_version, r.path.display()))
+            .collect();
+        format!("only found {}", found.join(", "))
+    };
+    Err(format!(
+        "Minecraft {} needs Java {} or newer, but {}. Install Java {}, let MCSM download it, or pin a runtime for this server.",
+        minecraft_version, required, installed, required
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_runtime(home: &Path, release: &str, jdk: bool) {
+        std::fs::create_dir_all(home.join("bin")).unwrap();
+        std::fs::write(home.join("bin").join(java_executable_name()), b"").unwrap();
+        if jdk {
+            std::fs::write(home.join("bin").join("javac"), b"").unwrap();
+        }
+        std::fs::write(home.join("release"), release).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_detect_runtimes() {
+        let root = std::env::temp_dir().join("mcsm_test_java");
+        let _ = std::fs::remove_dir_all(&root);
+        let jvm = root.join("jvm");
+        fake_runtime(
+            &jvm.join("temurin-21"),
+            "IMPLEMENTOR=\"Eclipse Adoptium\"\nJAVA_VERSION=\"21.0.2\"\nOS_ARCH=\"amd64\"\n",
+            true,
+        );
+        fake_runtime(
+            &jvm.join("zulu-8"),
+            "IMPLEMENTOR=\"Azul Systems, Inc.\"\nJAVA_VERSION=\"1.8.0_402\"\nOS_ARCH=\"aarch64\"\n",
+            false,
+        );
+        fake_runtime(
+            &jvm.join("graal-17"),
+            "IMPLEMENTOR=\"GraalVM Community\"\nJAVA_VERSION=\"17.0.9\"\nGRAALVM_VERSION=\"23.0.2\"\nOS_ARCH=\"x86_64\"\n",
+            true,
+        );
+        // SDKMAN-style `current` link to a runtime that's also listed directly.
+        let sdkman = root.join("sdkman");
+        std::fs::create_dir_all(&sdkman).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(jvm.join("temurin-21"), sdkman.join("current")).unwrap();
+
+        let detector = JavaDetector::with_search_dirs(vec![jvm.clone(), sdkman, jvm]);
+        let runtimes = detector.detect().await;
+        assert_eq!(runtimes.len(), 3);
+
+        assert_eq!(runtimes[0].major_version, 21);
+        assert_eq!(runtimes[0].vendor, JavaVendor::Temurin);
+        assert_eq!(runtimes[0].architecture, "x86_64");
+        assert!(runtimes[0].is_jdk);
+        assert_eq!(runtimes[1].vendor, JavaVendor::GraalVM);
+        assert_eq!(runtimes[2].major_version, 8);
+        assert_eq!(runtimes[2].vendor, JavaVendor::Zulu);
+        assert_eq!(runtimes[2].version, "1.8.0_402");
+        assert!(!runtimes[2].is_jdk);
+        assert!(runtimes[2].is_64bit());
+
+        assert_eq!(JavaVendor::from_implementor("Amazon.com Inc.", ""), JavaVendor::Corretto);
+        assert_eq!(JavaVendor::from_implementor("Oracle Corporation", "Java HotSpot(TM) 64-Bit Server VM"), JavaVendor::Oracle);
+        assert_eq!(JavaVendor::from_implementor("Oracle Corporation", "GraalVM CE"), JavaVendor::GraalVM);
+
+        assert_eq!(required_java_major("1.16.5"), 8);
+        assert_eq!(required_java_major("1.17.1"), 16);
+        assert_eq!(required_java_major("1.20.4"), 17);
+        assert_eq!(required_java_major("1.20.5"), 21);
+        assert_eq!(required_java_major("24w14a"), 21);
+        assert_eq!(select_runtime(&runtimes, 16).unwrap().major_version, 17);
+        assert_eq!(select_runtime(&runtimes, 8).unwrap().major_version, 8);
+        assert!(select_runtime(&runtimes, 22).is_none());
+
+        let selected = resolve_java(&detector, "1.21.1", None).await.unwrap();
+        assert_eq!(selected.vendor, JavaVendor::Temurin);
+        let pinned = root.join("jvm").join("zulu-8").join("bin").join(java_executable_name());
+        let error = resolve_java(&detector, "1.20.4", Some(&pinned)).await.unwrap_err();
+        assert!(error.contains("is Java 8"));
+        assert_eq!(resolve_java(&detector, "1.12.2", Some(&pinned)).await.unwrap().major_version, 8);
+        let error = resolve_java(&JavaDetector::with_search_dirs(Vec::new()), "1.20.4", None).await.unwrap_err();
+        assert!(error.contains("needs Java 17 or newer, but none was found"));
+    }
+}
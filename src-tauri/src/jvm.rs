@@ -0,0 +1,440 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use tokio::fs;
+use crate::property_schema::{IssueSeverity, ValidationIssue};
+use crate::MemoryConfig;
+
+/// Heaps above this get Aikar's large-heap G1 values.
+const AIKAR_LARGE_HEAP_MB: u32 = 12 * 1024;
+/// Below this ZGC rarely beats tuned G1 for a Minecraft server.
+const ZGC_MIN_HEAP_MB: u32 = 8 * 1024;
+const GC_LOG_FILE: &str = "logs/gc.log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GcPreset {
+    /// Aikar's tuned G1 flags.
+    Aikar,
+    Zgc,
+    GenerationalZgc,
+    /// G1 with the JVM's defaults.
+    Minimal,
+}
+
+/// Aikar's G1 flags, with his adjusted values for heaps above 12 GB.
+pub fn aikar_flags(max_heap_mb: u32) -> Vec<String> {
+    let large = max_heap_mb > AIKAR_LARGE_HEAP_MB;
+    let flags = [
+        "-XX:+UseG1GC",
+        "-XX:+ParallelRefProcEnabled",
+        "-XX:MaxGCPauseMillis=200",
+        "-XX:+UnlockExperimentalVMOptions",
+        "-XX:+DisableExplicitGC",
+        if large { "-XX:G1NewSizePercent=40" } else { "-XX:G1NewSizePercent=30" },
+        if large { "-XX:G1MaxNewSizePercent=50" } else { "-XX:G1MaxNewSizePercent=40" },
+        if large { "-XX:G1HeapRegionSize=16M" } else { "-XX:G1HeapRegionSize=8M" },
+        if large { "-XX:G1ReservePercent=15" } else { "-XX:G1ReservePercent=20" },
+        "-XX:G1HeapWastePercent=5",
+        "-XX:G1MixedGCCountTarget=4",
+        if large { "-XX:InitiatingHeapOccupancyPercent=20" } else { "-XX:InitiatingHeapOccupancyPercent=15" },
+        "-XX:G1MixedGCLiveThresholdPercent=90",
+        "-XX:G1RSetUpdatingPauseTimePercent=5",
+        "-XX:SurvivorRatio=32",
+        "-XX:+PerfDisableSharedMem",
+        "-XX:MaxTenuringThreshold=1",
+    ];
+    flags.iter().map(|f| f.to_string()).collect()
+}
+
+/// Java version support for a flag, by the flag's name (`UseZGC`, `-Xloggc`, `--add-opens`).
+struct FlagRule {
+    name: &'static str,
+    introduced: Option<u32>,
+    deprecated: Option<u32>,
+    removed: Option<u32>,
+}
+
+const FLAG_RULES: [FlagRule; 12] = [
+    FlagRule { name: "UseConcMarkSweepGC", introduced: None, deprecated: Some(9), removed: Some(14) },
+    FlagRule { name: "CMSClassUnloadingEnabled", introduced: None, deprecated: Some(9), removed: Some(14) },
+    FlagRule { name: "CMSInitiatingOccupancyFraction", introduced: None, deprecated: Some(9), removed: Some(14) },
+    FlagRule { name: "UseParNewGC", introduced: None, deprecated: Some(9), removed: Some(10) },
+    FlagRule { name: "AggressiveOpts", introduced: None, deprecated: Some(11), removed: Some(12) },
+    FlagRule { name: "PrintGCDateStamps", introduced: None, deprecated: None, removed: Some(9) },
+    FlagRule { name: "PrintGCDetails", introduced: None, deprecated: Some(9), removed: None },
+    FlagRule { name: "-Xloggc", introduced: None, deprecated: Some(9), removed: None },
+    FlagRule { name: "-Xlog", introduced: Some(9), deprecated: None, removed: None },
+    FlagRule { name: "UseZGC", introduced: Some(15), deprecated: None, removed: None },
+    FlagRule { name: "ZGenerational", introduced: Some(21), deprecated: Some(23), removed: None },
+    FlagRule { name: "--add-opens", introduced: Some(9), deprecated: None, removed: None },
+];
+
+/// `-XX:+UseZGC` -> `UseZGC`, `-Xloggc:gc.log` -> `-Xloggc`, `--add-opens=x` -> `--add-opens`.
+fn flag_name(flag: &str) -> &str {
+    match flag.strip_prefix("-XX:") {
+        Some(rest) => {
+            let rest = rest.trim_start_matches(['+', '-']);
+            rest.split('=').next().unwrap_or(rest)
+        }
+        None => flag.split([':', '=']).next().unwrap_or(flag),
+    }
+}
+
+/// `-XX:+UseG1GC`, `-XX:+UseZGC` and the like.
+fn selects_gc(flag: &str) -> bool {
+    let name = flag_name(flag);
+    flag.starts_with("-XX:+") && name.starts_with("Use") && name.ends_with("GC")
+}
+
+/// Major Java version from a `java -version` string: `1.8.0_392` -> 8, `21.0.2` -> 21.
+pub fn parse_java_major(version: &str) -> Option<u32> {
+    let mut parts = version.trim().split(['.', '_', '-', '+']);
+    match parts.next()?.parse::<u32>().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
+
+/// Per-server JVM and server launch options, stored in `.mcsm/jvm.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JvmOptions {
+    pub preset: GcPreset,
+    pub always_pre_touch: bool,
+    pub gc_logging: bool,
+    pub custom_flags: Vec<String>,
+    /// Passed as `-Dkey=value`.
+    pub system_properties: BTreeMap<String, String>,
+    /// Arguments after the jar, such as `nogui` or `--forceUpgrade`.
+    pub server_args: Vec<String>,
+    /// Pins the server to this `java` executable instead of picking one by Minecraft version.
+    #[serde(default)]
+    pub java_path: Option<PathBuf>,
+    /// An argument file launched in place of `-jar`, such as Forge's
+    /// `libraries/.../unix_args.txt`.
+    #[serde(default)]
+    pub args_file: Option<String>,
+}
+
+impl Default for JvmOptions {
+    fn default() -> Self {
+        JvmOptions {
+            preset: GcPreset::Aikar,
+            always_pre_touch: true,
+            gc_logging: false,
+            custom_flags: Vec::new(),
+            system_properties: BTreeMap::new(),
+            server_args: vec!["nogui".to_string()],
+            java_path: None,
+            args_file: None,
+        }
+    }
+}
+
+impl JvmOptions {
+    /// Options equivalent to a plain list of JVM flags, such as a template's.
+    /// Aikar's flags become the Aikar preset; heap sizes are dropped since they
+    /// come from the server's memory settings.
+    pub fn from_flags(flags: &[String], memory: &MemoryConfig) -> Self {
+        let aikar = aikar_flags(memory.max_mb);
+        let preset = if aikar.iter().all(|f| flags.contains(f)) {
+            GcPreset::Aikar
+        } else {
+            GcPreset::Minimal
+        };
+
+        let mut options = JvmOptions { preset, always_pre_touch: false, ..Default::default() };
+        for flag in flags {
+            if preset == GcPreset::Aikar && aikar.contains(flag) {
+                continue;
+            }
+            if flag == "-XX:+AlwaysPreTouch" {
+                options.always_pre_touch = true;
+            } else if let Some((key, value)) = flag.strip_prefix("-D").and_then(|p| p.split_once('=')) {
+                options.system_properties.insert(key.to_string(), value.to_string());
+            } else if !flag.starts_with("-Xms") && !flag.starts_with("-Xmx") {
+                options.custom_flags.push(flag.clone());
+            }
+        }
+        options
+    }
+
+    fn options_file(server_path: &PathBuf) -> PathBuf {
+        server_path.join(".mcsm").join("jvm.json")
+    }
+
+    /// Loads a server's options, falling back to the defaults when none were saved.
+    pub async fn load(server_path: &PathBuf) -> Result<Self, String> {
+        let path = Self::options_file(server_path);
+        if !path.exists() {
+            return Ok(JvmOptions::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .await
+            .map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    }
+
+    pub async fn save(&self, server_path: &PathBuf) -> Result<(), String> {
+        let path = Self::options_file(server_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).await.map_err(|e| e.to_string())
+    }
+
+    fn preset_flags(&self, memory: &MemoryConfig, java_major: Option<u32>) -> Vec<String> {
+        match self.preset {
+            GcPreset::Aikar => aikar_flags(memory.max_mb),
+            GcPreset::Zgc => vec!["-XX:+UseZGC".to_string()],
+            // Generational mode is opt-in on 21 and 22 and the only mode from 23 on.
+            GcPreset::GenerationalZgc => match java_major {
+                Some(21) | Some(22) => vec!["-XX:+UseZGC".to_string(), "-XX:+ZGenerational".to_string()],
+                _ => vec!["-XX:+UseZGC".to_string()],
+            },
+            // Custom flags may pick another collector, which G1 would conflict with.
+            GcPreset::Minimal if self.custom_flags.iter().any(|f| selects_gc(f)) => Vec::new(),
+            GcPreset::Minimal => vec!["-XX:+UseG1GC".to_string()],
+        }
+    }
+
+    fn gc_logging_flags(java_major: Option<u32>) -> Vec<String> {
+        match java_major {
+            Some(major) if major < 9 => vec![
+                "-XX:+PrintGCDetails".to_string(),
+                "-XX:+PrintGCDateStamps".to_string(),
+                format!("-Xloggc:{}", GC_LOG_FILE),
+                "-XX:+UseGCLogFileRotation".to_string(),
+                "-XX:NumberOfGCLogFiles=5".to_string(),
+                "-XX:GCLogFileSize=1M".to_string(),
+            ],
+            Some(_) => vec![format!("-Xlog:gc*:file={}:time,uptime:filecount=5,filesize=1M", GC_LOG_FILE)],
+            // Java 8 rejects `-Xlog` and Java 9+ warns on the old flags, so log nothing.
+            None => Vec::new(),
+        }
+    }
+
+    /// JVM arguments, in launch order, before `-jar`.
+    pub fn jvm_args(&self, memory: &MemoryConfig, java_major: Option<u32>) -> Vec<String> {
+        let mut args = vec![
+            format!("-Xms{}M", memory.min_mb),
+            format!("-Xmx{}M", memory.max_mb),
+        ];
+        args.extend(self.preset_flags(memory, java_major));
+        if self.always_pre_touch {
+            args.push("-XX:+AlwaysPreTouch".to_string());
+        }
+        if self.gc_logging {
+            args.extend(Self::gc_logging_flags(java_major));
+        }
+        args.extend(self.custom_flags.iter().cloned());
+        for (key, value) in &self.system_properties {
+            args.push(format!("-D{}={}", key, value));
+        }
+        args
+    }
+
+    /// The full command line after the `java` executable.
+    pub fn command_args(&self, memory: &MemoryConfig, java_major: Option<u32>, jar: &str) -> Vec<String> {
+        let mut args = self.jvm_args(memory, java_major);
+        match &self.args_file {
+            Some(file) => args.push(format!("@{}", file)),
+            None => args.extend(["-jar".to_string(), jar.to_string()]),
+        }
+        args.extend(self.server_args.iter().cloned());
+        args
+    }
+
+    /// Checks the options against the heap size and, when known, the Java version.
+    pub fn check(&self, memory: &MemoryConfig, java_major: Option<u32>) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let mut issue = |key: &str, severity, message: String| {
+            issues.push(ValidationIssue { key: key.to_string(), message, severity });
+        };
+
+        if memory.min_mb > memory.max_mb {
+            issue("memory", IssueSeverity::Error, "Minimum heap is larger than maximum heap".to_string());
+        }
+        if matches!(self.preset, GcPreset::Zgc | GcPreset::GenerationalZgc) && memory.max_mb < ZGC_MIN_HEAP_MB {
+            issue("preset", IssueSeverity::Warning, format!(
+                "ZGC is meant for large heaps; Aikar's flags usually do better below {} GB",
+                ZGC_MIN_HEAP_MB / 1024
+            ));
+        }
+        if self.gc_logging && java_major.is_none() {
+            issue("gc_logging", IssueSeverity::Warning,
+                "GC logging is left off until the server's Java version is known".to_string());
+        }
+        if let Some(major) = java_major {
+            match self.preset {
+                GcPreset::Zgc if major < 15 => {
+                    issue("preset", IssueSeverity::Error, format!("ZGC needs Java 15 or newer, this server uses Java {}", major));
+                }
+                GcPreset::GenerationalZgc if major < 21 => {
+                    issue("preset", IssueSeverity::Error, format!("Generational ZGC needs Java 21 or newer, this server uses Java {}", major));
+                }
+                _ => {}
+            }
+        }
+
+        let mut has_gc_flag = false;
+        for flag in &self.custom_flags {
+            if !flag.starts_with('-') {
+                issue(flag, IssueSeverity::Error, format!("{} is not a JVM option; server arguments go after the jar", flag));
+                continue;
+            }
+            if flag.starts_with("-Xmx") || flag.starts_with("-Xms") {
+                issue(flag, IssueSeverity::Error, "Heap size is set from the server's memory settings".to_string());
+            }
+            if selects_gc(flag) {
+                has_gc_flag = true;
+            }
+            let name = flag_name(flag);
+
+            let (Some(major), Some(rule)) = (java_major, FLAG_RULES.iter().find(|r| r.name == name)) else {
+                continue;
+            };
+            if rule.introduced.map(|v| major < v).unwrap_or(false) {
+                issue(flag, IssueSeverity::Error, format!(
+                    "{} needs Java {} or newer, this server uses Java {}",
+                    flag, rule.introduced.unwrap_or(0), major
+                ));
+            } else if rule.removed.map(|v| major >= v).unwrap_or(false) {
+                issue(flag, IssueSeverity::Error, format!(
+                    "{} was removed in Java {}, this server uses Java {}",
+                    flag, rule.removed.unwrap_or(0), major
+                ));
+            } else if rule.deprecated.map(|v| major >= v).unwrap_or(false) {
+                issue(flag, IssueSeverity::Warning, format!(
+                    "{} is deprecated since Java {}", flag, rule.deprecated.unwrap_or(0)
+                ));
+            }
+        }
+        if has_gc_flag && self.preset != GcPreset::Minimal {
+            issue("custom_flags", IssueSeverity::Error,
+                "Custom flags select a garbage collector that conflicts with the preset".to_string());
+        }
+
+        for key in self.system_properties.keys() {
+            if key.is_empty() || key.contains(char::is_whitespace) || key.contains('=') {
+                issue(key, IssueSeverity::Error, format!("Invalid system property name \"{}\"", key));
+            }
+        }
+        issues
+    }
+
+    /// A start script running `java` with these options.
+    pub fn start_script(&self, memory: &MemoryConfig, java_major: Option<u32>, java: &str, jar: &str) -> String {
+        let windows = cfg!(target_os = "windows");
+        let mut words = vec![shell_quote(java, windows)];
+        words.extend(
+            self.command_args(memory, java_major, jar)
+                .iter()
+                .map(|arg| shell_quote(arg, windows)),
+        );
+
+        if windows {
+            format!("@echo off\n{}\n", words.join(" "))
+        } else {
+            format!("#!/bin/bash\n{}\n", words.join(" "))
+        }
+    }
+
+    /// Writes `start.sh` (or `start.bat`) in the server directory.
+    pub async fn write_start_script(&self, server_path: &PathBuf, memory: &MemoryConfig, java_major: Option<u32>, java: &str) -> Result<(), String> {
+        let script_path = server_path.join(if cfg!(target_os = "windows") {
+            "start.bat"
+        } else {
+            "start.sh"
+        });
+        fs::write(&script_path, self.start_script(memory, java_major, java, "server.jar"))
+            .await
+            .map_err(|e| format!("Failed to write start script: {}", e))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755))
+                .await
+                .map_err(|e| format!("Failed to make script executable: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+fn shell_quote(arg: &str, windows: bool) -> String {
+    let safe = !arg.is_empty()
+        && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_=+.,:/@%*".contains(c));
+    if safe {
+        arg.to_string()
+    } else if windows {
+        format!("\"{}\"", arg.replace('"', "\"\""))
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jvm_arguments() {
+        let memory = MemoryConfig { min_mb: 4096, max_mb: 4096 };
+        let mut options = JvmOptions { gc_logging: true, ..Default::default() };
+        options.system_properties.insert("paper.playerconnection.keepalive".to_string(), "60".to_string());
+        options.server_args.push("--forceUpgrade".to_string());
+
+        let args = options.command_args(&memory, Some(21), "server.jar");
+        assert_eq!(&args[..3], ["-Xms4096M", "-Xmx4096M", "-XX:+UseG1GC"]);
+        assert!(args.contains(&"-XX:G1HeapRegionSize=8M".to_string()));
+        assert!(args.contains(&"-XX:+AlwaysPreTouch".to_string()));
+        assert!(args.iter().any(|a| a.starts_with("-Xlog:gc*:file=logs/gc.log")));
+        assert!(args.ends_with(&[
+            "-Dpaper.playerconnection.keepalive=60".to_string(),
+            "-jar".to_string(),
+            "server.jar".to_string(),
+            "nogui".to_string(),
+            "--forceUpgrade".to_string(),
+        ]));
+        options.args_file = Some("libraries/net/minecraftforge/forge/1.20.1-47.2.0/unix_args.txt".to_string());
+        assert!(options.command_args(&memory, Some(21), "server.jar").ends_with(&[
+            "@libraries/net/minecraftforge/forge/1.20.1-47.2.0/unix_args.txt".to_string(),
+            "nogui".to_string(),
+            "--forceUpgrade".to_string(),
+        ]));
+        options.args_file = None;
+        assert!(options.jvm_args(&memory, Some(8)).contains(&"-Xloggc:logs/gc.log".to_string()));
+        assert!(!options.jvm_args(&memory, None).iter().any(|a| a.contains("gc.log")));
+        assert!(options.check(&memory, None).iter().any(|i| i.key == "gc_logging"));
+        assert!(options.check(&memory, Some(21)).is_empty());
+
+        options.preset = GcPreset::GenerationalZgc;
+        options.custom_flags = vec!["-XX:+UseConcMarkSweepGC".to_string(), "-Xmx8G".to_string()];
+        assert!(options.jvm_args(&memory, Some(21)).contains(&"-XX:+ZGenerational".to_string()));
+        assert!(!options.jvm_args(&memory, Some(23)).contains(&"-XX:+ZGenerational".to_string()));
+
+        let issues = options.check(&memory, Some(17));
+        let errors: Vec<&str> = issues
+            .iter()
+            .filter(|i| i.severity == IssueSeverity::Error)
+            .map(|i| i.key.as_str())
+            .collect();
+        assert_eq!(errors, vec!["preset", "-XX:+UseConcMarkSweepGC", "-Xmx8G", "custom_flags"]);
+        assert!(issues.iter().any(|i| i.key == "preset" && i.severity == IssueSeverity::Warning));
+
+        let mut flags = aikar_flags(4096);
+        flags.extend(["-XX:+AlwaysPreTouch", "-Xmx4G", "-Dfile.encoding=UTF-8"].map(String::from));
+        let options = JvmOptions::from_flags(&flags, &memory);
+        assert_eq!(options.preset, GcPreset::Aikar);
+        assert!(options.always_pre_touch && options.custom_flags.is_empty());
+        assert_eq!(options.system_properties["file.encoding"], "UTF-8");
+        let options = JvmOptions::from_flags(&["-XX:+UseZGC".to_string()], &memory);
+        assert_eq!(options.jvm_args(&memory, Some(21))[2..], ["-XX:+UseZGC"]);
+
+        assert_eq!(parse_java_major("1.8.0_392"), Some(8));
+        assert_eq!(parse_java_major("21.0.2"), Some(21));
+        assert_eq!(parse_java_major("17"), Some(17));
+    }
+}
@@ -8,11 +8,84 @@ fn greet(name: &str) -> String {
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet])
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            commands::catalog::search_catalog,
+            commands::catalog::install_from_catalog,
+            commands::catalog::check_updates,
+            commands::catalog::update_extension,
+            commands::config::get_properties_schema,
+            commands::config::get_server_properties,
+            commands::config::validate_server_properties,
+            commands::config::update_server_properties,
+            commands::config::get_performance_settings,
+            commands::config::update_performance_settings,
+            commands::groups::get_server_groups,
+            commands::groups::create_server_group,
+            commands::groups::delete_server_group,
+            commands::groups::add_server_group_member,
+            commands::groups::remove_server_group_member,
+            commands::groups::get_server_group_drift,
+            commands::groups::sync_server_group,
+            commands::history::get_config_history,
+            commands::history::create_config_snapshot,
+            commands::history::diff_config_snapshots,
+            commands::history::rollback_config_snapshot,
+            commands::inventory::get_player_inventory,
+            commands::inventory::remove_player_item,
+            commands::inventory::add_player_item,
+            commands::java::check_java_installation,
+            commands::java::get_java_version,
+            commands::java::get_server_java,
+            commands::java::ensure_server_java,
+            commands::java::list_managed_java,
+            commands::java::install_managed_java,
+            commands::java::remove_managed_java,
+            commands::jvm::get_jvm_options,
+            commands::jvm::preview_jvm_options,
+            commands::jvm::update_jvm_options,
+            commands::lockfile::check_lockfile_drift,
+            commands::lockfile::export_lockfile,
+            commands::lockfile::import_lockfile,
+            commands::lockfile::recreate_extensions,
+            commands::modpack::import_modpack_server,
+            commands::mods::get_mods,
+            commands::mods::install_mod,
+            commands::players::set_player_whitelisted,
+            commands::players::set_player_op,
+            commands::players::set_player_banned,
+            commands::plugins::get_plugins,
+            commands::plugins::install_plugin,
+            commands::plugins::enable_plugin,
+            commands::plugins::disable_plugin,
+            commands::plugins::uninstall_plugin,
+            commands::plugins::get_plugin_config_files,
+            commands::plugins::read_plugin_config,
+            commands::plugins::update_plugin_config,
+            commands::scan::scan_extensions,
+            commands::scan::block_jar_hash,
+            commands::server::create_server,
+            commands::server::start_server,
+            commands::server::stop_server,
+            commands::server::get_server_status,
+            commands::server::get_server_list,
+            commands::server::delete_server,
+            commands::server::clone_server,
+            commands::server::check_start_memory,
+            commands::server::recommend_server_memory,
+            commands::system::get_system_info,
+            commands::templates::get_server_templates,
+            commands::templates::save_server_template,
+            commands::templates::delete_server_template,
+            commands::templates::export_server_template,
+            commands::templates::import_server_template,
+            commands::templates::create_server_from_template,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
+pub mod commands;
 pub mod server;
 pub mod console;
 pub mod players;
@@ -23,30 +96,59 @@ pub mod monitoring;
 pub mod security;
 pub mod advanced;
 pub mod utils;
+pub mod sessions;
+pub mod nbt;
+pub mod inventory;
+pub mod rcon;
+pub mod groups;
+pub mod properties;
+pub mod property_schema;
+pub mod version;
+pub mod yaml_config;
+pub mod config_history;
+pub mod templates;
+pub mod registry;
+pub mod server_clone;
+pub mod jvm;
+pub mod memory;
+pub mod java;
+pub mod managed_java;
+pub mod mods;
+pub mod plugin_graph;
+pub mod lockfile;
+pub mod jar_install;
+pub mod jar_scan;
+pub mod catalog;
+pub mod modpack;
 
 use serde::{Serialize, Deserialize};
 use std::path::PathBuf;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub name: String,
     pub version: String,
     pub server_type: ServerType,
+    /// The Fabric, Quilt, Forge or NeoForge version, when MCSM knows it.
+    #[serde(default)]
+    pub loader_version: Option<String>,
     pub path: PathBuf,
     pub memory: MemoryConfig,
     pub port: u16,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ServerType {
     Vanilla,
     Spigot,
     Paper,
     Forge,
     Fabric,
+    Quilt,
+    NeoForge,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryConfig {
     pub min_mb: u32,
     pub max_mb: u32,
@@ -81,3 +183,27 @@ pub use monitoring::*;
 pub use security::*;
 pub use advanced::*;
 pub use utils::*;
+pub use sessions::*;
+pub use nbt::*;
+pub use inventory::*;
+pub use rcon::*;
+pub use groups::*;
+pub use properties::*;
+pub use property_schema::*;
+pub use version::*;
+pub use yaml_config::*;
+pub use config_history::*;
+pub use templates::*;
+pub use registry::*;
+pub use server_clone::*;
+pub use jvm::*;
+pub use memory::*;
+pub use java::*;
+pub use managed_java::*;
+pub use mods::*;
+pub use plugin_graph::*;
+pub use lockfile::*;
+pub use jar_install::*;
+pub use jar_scan::*;
+pub use catalog::*;
+pub use modpack::*;
@@ -0,0 +1,205 @@
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256, Sha512};
+use tokio::fs;
+
+pub const LOCKFILE_NAME: &str = "mcsm.lock";
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ExtensionKind {
+    Plugin,
+    Mod,
+}
+
+impl ExtensionKind {
+    pub fn folder(&self) -> &'static str {
+        match self {
+            ExtensionKind::Plugin => "plugins",
+            ExtensionKind::Mod => "mods",
+        }
+    }
+}
+
+/// Where a plugin or mod jar came from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ExtensionSource {
+    Url { url: String },
+    File { path: PathBuf },
+    Modrinth { project_id: String, version_id: String, url: String, file_name: String },
+    /// Hangar has no version ids; `version` is the version name.
+    Hangar { project: String, version: String, url: String, file_name: String },
+    /// A jar bundled in a modpack archive, at `path` inside it.
+    Modpack { pack: PathBuf, path: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockEntry {
+    /// The plugin name or mod id.
+    pub name: String,
+    pub kind: ExtensionKind,
+    pub version: String,
+    pub file_name: String,
+    pub sha256: String,
+    pub source: ExtensionSource,
+    pub installed_at: DateTime<Utc>,
+}
+
+/// `mcsm.lock` in the server folder: what MCSM installed, from where, and the hash of
+/// each jar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub format_version: u32,
+    pub entries: Vec<LockEntry>,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Lockfile {
+            format_version: FORMAT_VERSION,
+            entries: Vec::new(),
+        }
+    }
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn sha512_hex(bytes: &[u8]) -> String {
+    Sha512::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A locked jar whose file no longer has the recorded hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifiedJar {
+    pub name: String,
+    pub file: PathBuf,
+    pub expected_sha256: String,
+    pub actual_sha256: String,
+}
+
+/// Differences between a server's jars and its lockfile.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LockfileDrift {
+    /// Jars MCSM didn't install.
+    pub untracked: Vec<PathBuf>,
+    pub modified: Vec<ModifiedJar>,
+    /// Locked plugins and mods whose jar is gone.
+    pub missing: Vec<String>,
+}
+
+impl LockfileDrift {
+    pub fn is_clean(&self) -> bool {
+        self.untracked.is_empty() && self.modified.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// The folders a kind of jar can be in: disabled plugins live in `plugins/.disabled`.
+pub(crate) fn jar_folders(server_path: &PathBuf, kind: ExtensionKind) -> Vec<PathBuf> {
+    let folder = server_path.join(kind.folder());
+    match kind {
+        ExtensionKind::Plugin => vec![folder.join(".disabled"), folder],
+        ExtensionKind::Mod => vec![folder],
+    }
+}
+
+pub(crate) async fn list_jars(folder: &PathBuf) -> Result<Vec<PathBuf>, String> {
+    let mut jars = Vec::new();
+    if !folder.exists() {
+        return Ok(jars);
+    }
+    let mut dir = fs::read_dir(folder).await.map_err(|e| e.to_string())?;
+    while let Some(entry) = dir.next_entry().await.map_err(|e| e.to_string())? {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("jar") {
+            jars.push(path);
+        }
+    }
+    jars.sort();
+    Ok(jars)
+}
+
+impl Lockfile {
+    pub async fn load(server_path: &PathBuf) -> Result<Self, String> {
+        let path = server_path.join(LOCKFILE_NAME);
+        if !path.exists() {
+            return Ok(Lockfile::default());
+        }
+        let content = fs::read_to_string(&path)
+            .await
+            .map_err(|e| e.to_string())?;
+        let lockfile: Lockfile = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid {}: {}", LOCKFILE_NAME, e))?;
+        if lockfile.format_version > FORMAT_VERSION {
+            return Err(format!(
+                "{} was written by a newer MCSM (format {})",
+                LOCKFILE_NAME, lockfile.format_version
+            ));
+        }
+        Ok(lockfile)
+    }
+
+    pub async fn save(&self, server_path: &PathBuf) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(server_path.join(LOCKFILE_NAME), content)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn get(&self, kind: ExtensionKind, name: &str) -> Option<&LockEntry> {
+        self.entries.iter().find(|e| e.kind == kind && e.name == name)
+    }
+
+    /// Adds `entry`, replacing any entry for the same plugin or mod.
+    pub fn upsert(&mut self, entry: LockEntry) {
+        self.remove(entry.kind, &entry.name);
+        self.entries.push(entry);
+        self.entries.sort_by(|a, b| (a.kind as u8, &a.name).cmp(&(b.kind as u8, &b.name)));
+    }
+
+    pub fn remove(&mut self, kind: ExtensionKind, name: &str) -> Option<LockEntry> {
+        let index = self.entries.iter().position(|e| e.kind == kind && e.name == name)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Compares the jars in `server_path` with the lockfile, finding jars added,
+    /// replaced or deleted outside MCSM.
+    pub async fn check_drift(&self, server_path: &PathBuf) -> Result<LockfileDrift, String> {
+        let mut drift = LockfileDrift::default();
+        for kind in [ExtensionKind::Plugin, ExtensionKind::Mod] {
+            let mut jars = Vec::new();
+            for folder in jar_folders(server_path, kind) {
+                jars.extend(list_jars(&folder).await?);
+            }
+            let entries: Vec<&LockEntry> = self.entries.iter().filter(|e| e.kind == kind).collect();
+
+            for entry in &entries {
+                let file = jars
+                    .iter()
+                    .find(|j| j.file_name().is_some_and(|n| n.to_string_lossy() == entry.file_name));
+                let Some(file) = file else {
+                    drift.missing.push(entry.name.clone());
+                    continue;
+                };
+                let bytes = fs::read(file).await.map_err(|e| e.to_string())?;
+                let actual = sha256_hex(&bytes);
+                if !actual.eq_ignore_ascii_case(&entry.sha256) {
+                    drift.modified.push(ModifiedJar {
+                        name: entry.name.clone(),
+                        file: file.clone(),
+                        expected_sha256: entry.sha256.clone(),
+                        actual_sha256: actual,
+                    });
+                }
+            }
+            drift.untracked.extend(jars.into_iter().filter(|j| {
+                let name = j.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                !entries.iter().any(|e| e.file_name == name)
+            }));
+        }
+        Ok(drift)
+    }
+}
@@ -15,6 +15,30 @@ mod monitoring;
 mod security;
 mod advanced;
 mod utils;
+mod sessions;
+mod nbt;
+mod inventory;
+mod rcon;
+mod groups;
+mod properties;
+mod property_schema;
+mod version;
+mod yaml_config;
+mod config_history;
+mod templates;
+mod registry;
+mod server_clone;
+mod jvm;
+mod memory;
+mod java;
+mod managed_java;
+mod mods;
+mod plugin_graph;
+mod lockfile;
+mod jar_install;
+mod jar_scan;
+mod catalog;
+mod modpack;
 
 use server::MinecraftServer;
 use config::ServerConfig;
@@ -30,8 +54,14 @@ struct AppState {
 }
 
 #[tauri::command]
-async fn start_server(state: tauri::State<'_, AppState>, config: ServerConfig) -> Result<(), String> {
-    let server = MinecraftServer::new(config);
+async fn start_server(app: tauri::AppHandle, state: tauri::State<'_, AppState>, config: ServerConfig) -> Result<(), String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    let server = MinecraftServer::new(config)
+        .with_java_detector(managed_java::ManagedJava::new(&data_dir).detector());
+    server.start().await?;
     let mut state_server = state.server.lock().await;
     *state_server = Some(server);
     Ok(())
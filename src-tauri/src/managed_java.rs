@@ -0,0 +1,345 @@
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+use crate::java::{java_executable_name, JavaDetector};
+use crate::jvm::JvmOptions;
+
+/// Adoptium's API; any endpoint answering `<url>/<major>/hotspot?os=..&architecture=..&image_type=jdk`
+/// with the same JSON works.
+pub const DEFAULT_JAVA_METADATA_URL: &str = "https://api.adoptium.net/v3/assets/latest";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaPackage {
+    pub name: String,
+    pub link: String,
+    /// SHA-256 of the archive, hex encoded.
+    pub checksum: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct JavaBinary {
+    package: JavaPackage,
+}
+
+#[derive(Debug, Deserialize)]
+struct JavaAsset {
+    binary: JavaBinary,
+    release_name: String,
+}
+
+/// A JDK downloaded and unpacked by MCSM.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedRuntime {
+    /// The release name, e.g. `jdk-21.0.2+13`, which is also its folder name.
+    pub id: String,
+    pub major_version: u32,
+    pub home: PathBuf,
+    pub checksum: String,
+    pub installed_at: DateTime<Utc>,
+}
+
+/// Downloads JDKs into `<data dir>/java/runtimes/<id>` and keeps an index of them.
+pub struct ManagedJava {
+    root: PathBuf,
+    metadata_url: String,
+}
+
+fn current_os() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "mac",
+        os => os,
+    }
+}
+
+fn current_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "x64",
+        arch => arch,
+    }
+}
+
+/// Finds the runtime home inside an unpacked archive: the folder with `bin/java`,
+/// which macOS archives keep under `Contents/Home`.
+fn find_java_home(dir: &Path) -> Option<PathBuf> {
+    let candidates = std::fs::read_dir(dir).ok()?.flatten().map(|e| e.path());
+    for top in std::iter::once(dir.to_path_buf()).chain(candidates) {
+        for home in [top.clone(), top.join("Contents").join("Home")] {
+            if home.join("bin").join(java_executable_name()).is_file() {
+                return Some(home);
+            }
+        }
+    }
+    None
+}
+
+fn unpack(name: &str, bytes: Vec<u8>, destination: &Path) -> Result<(), String> {
+    if name.ends_with(".zip") {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+        archive.extract(destination).map_err(|e| e.to_string())
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        let decoder = flate2::read::GzDecoder::new(Cursor::new(bytes));
+        tar::Archive::new(decoder).unpack(destination).map_err(|e| e.to_string())
+    } else {
+        Err(format!("Unsupported archive format: {}", name))
+    }
+}
+
+impl ManagedJava {
+    pub fn new(data_dir: &PathBuf) -> Self {
+        ManagedJava {
+            root: data_dir.join("java"),
+            metadata_url: DEFAULT_JAVA_METADATA_URL.to_string(),
+        }
+    }
+
+    pub fn with_metadata_url(mut self, url: &str) -> Self {
+        self.metadata_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn runtimes_dir(&self) -> PathBuf {
+        self.root.join("runtimes")
+    }
+
+    fn index_file(&self) -> PathBuf {
+        self.root.join("runtimes.json")
+    }
+
+    /// A detector that also finds the managed runtimes.
+    pub fn detector(&self) -> JavaDetector {
+        let mut detector = JavaDetector::new();
+        detector.add_search_dir(self.runtimes_dir());
+        detector
+    }
+
+    pub async fn list(&self) -> Result<Vec<ManagedRuntime>, String> {
+        if !self.index_file().exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(self.index_file())
+            .await
+            .map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    }
+
+    async fn save(&self, runtimes: &[ManagedRuntime]) -> Result<(), String> {
+        fs::create_dir_all(&self.root).await.map_err(|e| e.to_string())?;
+        let content = serde_json::to_string_pretty(runtimes).map_err(|e| e.to_string())?;
+        fs::write(self.index_file(), content)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Looks up the latest JDK for `major` on this OS and architecture.
+    pub async fn find_package(&self, major: u32) -> Result<(String, JavaPackage), String> {
+        let url = format!(
+            "{}/{}/hotspot?os={}&architecture={}&image_type=jdk",
+            self.metadata_url, major, current_os(), current_arch()
+        );
+        let response = reqwest::get(&url)
+            .await
+            .map_err(|e| format!("Failed to fetch Java {} metadata: {}", major, e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch Java {} metadata: HTTP {}", major, response.status()));
+        }
+        let assets: Vec<JavaAsset> = response.json().await.map_err(|e| e.to_string())?;
+
+        assets
+            .into_iter()
+            .find(|a| [".tar.gz", ".tgz", ".zip"].iter().any(|ext| a.binary.package.name.ends_with(ext)))
+            .map(|a| (a.release_name, a.binary.package))
+            .ok_or_else(|| format!("No Java {} download is available for {} {}", major, current_os(), current_arch()))
+    }
+
+    /// Downloads, verifies and unpacks the latest JDK for `major`. Returns the existing
+    /// runtime when that release is already installed.
+    pub async fn install(&self, major: u32) -> Result<ManagedRuntime, String> {
+        let (release_name, package) = self.find_package(major).await?;
+        let mut runtimes = self.list().await?;
+        if let Some(existing) = runtimes.iter().find(|r| r.id == release_name) {
+            return Ok(existing.clone());
+        }
+        if release_name.is_empty() || release_name.contains(['/', '\\']) || release_name.starts_with('.') {
+            return Err(format!("Invalid release name: {}", release_name));
+        }
+
+        let response = reqwest::get(&package.link)
+            .await
+            .map_err(|e| format!("Failed to download {}: {}", package.name, e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to download {}: HTTP {}", package.name, response.status()));
+        }
+        let bytes = response.bytes().await.map_err(|e| e.to_string())?.to_vec();
+
+        let checksum: String = Sha256::digest(&bytes).iter().map(|b| format!("{:02x}", b)).collect();
+        if !checksum.eq_ignore_ascii_case(package.checksum.trim()) {
+            return Err(format!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                package.name, package.checksum, checksum
+            ));
+        }
+
+        // Unpack next to the final location so the move is a rename.
+        let staging = self.root.join(format!(".staging-{}", release_name));
+        let _ = fs::remove_dir_all(&staging).await;
+        fs::create_dir_all(&staging).await.map_err(|e| e.to_string())?;
+        let name = package.name.clone();
+        let target = staging.clone();
+        let unpacked = tokio::task::spawn_blocking(move || unpack(&name, bytes, &target))
+            .await
+            .map_err(|e| e.to_string())?;
+        let result = match unpacked {
+            Ok(()) => self.finish_install(&staging, &release_name, &package).await,
+            Err(e) => Err(format!("Failed to unpack {}: {}", package.name, e)),
+        };
+        let _ = fs::remove_dir_all(&staging).await;
+        let runtime = result?;
+
+        runtimes.push(runtime.clone());
+        self.save(&runtimes).await?;
+        Ok(runtime)
+    }
+
+    async fn finish_install(&self, staging: &Path, release_name: &str, package: &JavaPackage) -> Result<ManagedRuntime, String> {
+        let staged_home = find_java_home(staging)
+            .ok_or_else(|| format!("{} doesn't contain a Java runtime", package.name))?;
+        // Move the archive's top folder, which keeps macOS bundles intact.
+        let relative = staged_home.strip_prefix(staging).map_err(|e| e.to_string())?;
+        let top = match relative.components().next() {
+            Some(first) => staging.join(first),
+            None => staging.to_path_buf(),
+        };
+        let folder = self.runtimes_dir().join(release_name);
+        fs::create_dir_all(self.runtimes_dir()).await.map_err(|e| e.to_string())?;
+        let _ = fs::remove_dir_all(&folder).await;
+        fs::rename(&top, &folder).await.map_err(|e| e.to_string())?;
+
+        let home = find_java_home(&folder)
+            .ok_or_else(|| format!("{} doesn't contain a Java runtime", package.name))?;
+        let info = JavaDetector::inspect(&home.join("bin").join(java_executable_name()))
+            .await
+            .ok_or_else(|| format!("The Java runtime in {} doesn't work", package.name))?;
+
+        Ok(ManagedRuntime {
+            id: release_name.to_string(),
+            major_version: info.major_version,
+            home: info.home,
+            checksum: package.checksum.to_lowercase(),
+            installed_at: Utc::now(),
+        })
+    }
+
+    /// Removes a runtime, refusing while one of `server_paths` pins it in its JVM options
+    /// or start script.
+    pub async fn remove(&self, id: &str, server_paths: &[PathBuf]) -> Result<(), String> {
+        let mut runtimes = self.list().await?;
+        let index = runtimes
+            .iter()
+            .position(|r| r.id == id)
+            .ok_or_else(|| format!("No managed Java runtime {}", id))?;
+        let runtime = runtimes.remove(index);
+
+        let folder = self.runtimes_dir().join(&runtime.id);
+        for server_path in server_paths {
+            if uses_runtime(server_path, &[&folder, &runtime.home]).await? {
+                return Err(format!(
+                    "{} is still used by the server at {}; choose another Java for it first",
+                    id,
+                    server_path.display()
+                ));
+            }
+        }
+        if folder.exists() {
+            fs::remove_dir_all(&folder).await.map_err(|e| e.to_string())?;
+        }
+        self.save(&runtimes).await
+    }
+}
+
+/// Whether a server's pinned Java or start script points into one of `dirs`.
+async fn uses_runtime(server_path: &PathBuf, dirs: &[&Path]) -> Result<bool, String> {
+    let options = JvmOptions::load(server_path).await?;
+    if options.java_path.is_some_and(|java| dirs.iter().any(|dir| java.starts_with(dir))) {
+        return Ok(true);
+    }
+    for script in ["start.sh", "start.bat"] {
+        if let Ok(content) = fs::read_to_string(server_path.join(script)).await {
+            if dirs.iter().any(|dir| content.contains(&*dir.to_string_lossy())) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jdk_archive() -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+        let files: [(&str, &[u8], u32); 2] = [
+            ("jdk-17.0.9+9/bin/java", b"", 0o755),
+            ("jdk-17.0.9+9/release", b"IMPLEMENTOR=\"Eclipse Adoptium\"\nJAVA_VERSION=\"17.0.9\"\n", 0o644),
+        ];
+        for (path, content, mode) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(mode);
+            header.set_cksum();
+            builder.append_data(&mut header, path, content).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_install_managed_java() {
+        let data_dir = std::env::temp_dir().join("mcsm_test_managed_java");
+        let _ = std::fs::remove_dir_all(&data_dir);
+
+        let archive = jdk_archive();
+        let checksum: String = Sha256::digest(&archive).iter().map(|b| format!("{:02x}", b)).collect();
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+        let metadata = |checksum: &str| {
+            serde_json::json!([{
+                "release_name": "jdk-17.0.9+9",
+                "binary": { "package": {
+                    "name": "OpenJDK17U-jdk_x64_linux_hotspot_17.0.9_9.tar.gz",
+                    "link": format!("{}/jdk.tar.gz", url),
+                    "checksum": checksum,
+                    "size": archive.len(),
+                }},
+            }])
+            .to_string()
+        };
+        server.mock("GET", "/17/hotspot").match_query(mockito::Matcher::Any).with_body(metadata(&checksum)).create_async().await;
+        server.mock("GET", "/16/hotspot").match_query(mockito::Matcher::Any).with_body(metadata(&"0".repeat(64))).create_async().await;
+        server.mock("GET", "/jdk.tar.gz").with_body(&archive).create_async().await;
+
+        let managed = ManagedJava::new(&data_dir).with_metadata_url(&url);
+        let error = managed.install(16).await.unwrap_err();
+        assert!(error.contains("Checksum mismatch"));
+        assert!(managed.list().await.unwrap().is_empty());
+
+        let runtime = managed.install(17).await.unwrap();
+        assert_eq!(runtime.id, "jdk-17.0.9+9");
+        assert_eq!(runtime.major_version, 17);
+        assert_eq!(managed.list().await.unwrap().len(), 1);
+        let detected = JavaDetector::with_search_dirs(vec![managed.runtimes_dir()]).detect().await;
+        assert_eq!(detected[0].home, runtime.home);
+
+        let servers = [data_dir.join("server")];
+        let options = JvmOptions { java_path: Some(runtime.home.join("bin").join(java_executable_name())), ..Default::default() };
+        options.save(&servers[0]).await.unwrap();
+        assert!(managed.remove("jdk-17.0.9+9", &servers).await.unwrap_err().contains("still used"));
+        JvmOptions::default().save(&servers[0]).await.unwrap();
+        managed.remove("jdk-17.0.9+9", &servers).await.unwrap();
+        assert!(managed.list().await.unwrap().is_empty());
+        assert!(!managed.runtimes_dir().join("jdk-17.0.9+9").exists());
+    }
+}
@@ -0,0 +1,202 @@
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use sysinfo::{System, SystemExt};
+use tokio::fs;
+use crate::{MemoryConfig, ServerType};
+
+const MB: u64 = 1024 * 1024;
+/// Heaps are recommended in steps of this size.
+const STEP_MB: u64 = 512;
+const MIN_HEAP_MB: u64 = 1024;
+/// Past this, longer GC pauses outweigh the extra headroom for a single server.
+const MAX_RECOMMENDED_HEAP_MB: u64 = 16 * 1024;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HostMemory {
+    pub total_mb: u64,
+    pub available_mb: u64,
+}
+
+impl HostMemory {
+    pub fn detect() -> Self {
+        let mut system = System::new();
+        system.refresh_memory();
+        HostMemory {
+            total_mb: system.total_memory() / MB,
+            available_mb: system.available_memory() / MB,
+        }
+    }
+
+    /// Memory left for the OS and everything that isn't a Minecraft heap: 15% of RAM,
+    /// at least 1 GB and at most 4 GB. The JVM itself also needs some memory
+    /// beyond `-Xmx`.
+    pub fn os_reserve_mb(&self) -> u64 {
+        (self.total_mb * 15 / 100).clamp(1024, 4096)
+    }
+
+    pub fn heap_budget_mb(&self) -> u64 {
+        self.total_mb.saturating_sub(self.os_reserve_mb())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryRecommendation {
+    pub recommended: MemoryConfig,
+    /// What the server would ideally get with no other servers on the host.
+    pub ideal_mb: u64,
+    /// Heap still unclaimed by other registered servers.
+    pub available_budget_mb: u64,
+    pub notes: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+fn round_up(mb: u64) -> u64 {
+    mb.div_ceil(STEP_MB) * STEP_MB
+}
+
+fn round_down(mb: u64) -> u64 {
+    mb / STEP_MB * STEP_MB
+}
+
+/// Baseline heap and extra heap per installed plugin or mod.
+fn type_profile(server_type: &ServerType) -> (u64, u64, &'static str) {
+    match server_type {
+        ServerType::Vanilla => (2048, 0, "mods"),
+        ServerType::Spigot | ServerType::Paper => (2048, 64, "plugins"),
+        ServerType::Fabric | ServerType::Quilt => (3072, 64, "mods"),
+        ServerType::Forge | ServerType::NeoForge => (4096, 128, "mods"),
+    }
+}
+
+/// Recommends a heap for a server from host RAM, what other registered servers
+/// already claim, the server type and how many plugins or mods it loads.
+pub fn recommend_memory(host: &HostMemory, other_servers_mb: u64, server_type: &ServerType, extension_count: usize) -> MemoryRecommendation {
+    let (base, per_extension, extension_kind) = type_profile(server_type);
+    let ideal_mb = round_up(base + per_extension * extension_count as u64).min(MAX_RECOMMENDED_HEAP_MB);
+    let available_budget_mb = host.heap_budget_mb().saturating_sub(other_servers_mb);
+
+    let mut notes = vec![format!(
+        "{:?} servers start around {} MB",
+        server_type, base
+    )];
+    if per_extension > 0 && extension_count > 0 {
+        notes.push(format!(
+            "{} {} add about {} MB",
+            extension_count, extension_kind, per_extension * extension_count as u64
+        ));
+    }
+    notes.push(format!(
+        "{} MB of {} MB RAM is left for the operating system",
+        host.os_reserve_mb(), host.total_mb
+    ));
+    if other_servers_mb > 0 {
+        notes.push(format!("Other servers are allocated {} MB", other_servers_mb));
+    }
+
+    let mut warnings = Vec::new();
+    let max_mb = if available_budget_mb >= ideal_mb {
+        ideal_mb
+    } else if round_down(available_budget_mb) >= MIN_HEAP_MB {
+        warnings.push(format!(
+            "Only {} MB is free for this server; {} MB would be better",
+            round_down(available_budget_mb), ideal_mb
+        ));
+        round_down(available_budget_mb)
+    } else {
+        warnings.push(format!(
+            "The host doesn't have enough free memory for this server; it needs at least {} MB",
+            MIN_HEAP_MB
+        ));
+        MIN_HEAP_MB
+    };
+    if base + per_extension * extension_count as u64 > MAX_RECOMMENDED_HEAP_MB {
+        warnings.push(format!(
+            "Heaps above {} MB cause long GC pauses; consider splitting the server",
+            MAX_RECOMMENDED_HEAP_MB
+        ));
+    }
+
+    // Equal -Xms and -Xmx avoid heap resizing pauses.
+    MemoryRecommendation {
+        recommended: MemoryConfig { min_mb: max_mb as u32, max_mb: max_mb as u32 },
+        ideal_mb,
+        available_budget_mb,
+        notes,
+        warnings,
+    }
+}
+
+/// Warns when starting a server would push the combined `-Xmx` of running servers
+/// past the host's heap budget.
+pub fn check_overcommit(host: &HostMemory, running: &[(String, u32)], starting_max_mb: u32) -> Option<String> {
+    let running_mb: u64 = running.iter().map(|(_, mb)| *mb as u64).sum();
+    let combined = running_mb + starting_max_mb as u64;
+    if combined <= host.heap_budget_mb() {
+        return None;
+    }
+
+    let names: Vec<&str> = running.iter().map(|(name, _)| name.as_str()).collect();
+    Some(format!(
+        "Running servers would use up to {} MB of heap with this one, but the host has {} MB RAM ({} MB after the OS reserve){}",
+        combined,
+        host.total_mb,
+        host.heap_budget_mb(),
+        if names.is_empty() {
+            String::new()
+        } else {
+            format!("; already running: {}", names.join(", "))
+        }
+    ))
+}
+
+/// Number of plugin or mod jars a server loads.
+pub async fn count_extensions(server_path: &PathBuf, server_type: &ServerType) -> Result<usize, String> {
+    let folder = match server_type {
+        ServerType::Vanilla => return Ok(0),
+        ServerType::Spigot | ServerType::Paper => server_path.join("plugins"),
+        ServerType::Forge | ServerType::NeoForge | ServerType::Fabric | ServerType::Quilt => server_path.join("mods"),
+    };
+    if !folder.exists() {
+        return Ok(0);
+    }
+
+    let mut count = 0;
+    let mut entries = fs::read_dir(&folder).await.map_err(|e| e.to_string())?;
+    while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+        if entry.path().extension().and_then(|e| e.to_str()) == Some("jar") {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_recommendation() {
+        let host = HostMemory { total_mb: 16384, available_mb: 12000 };
+        assert_eq!(host.os_reserve_mb(), 2457);
+
+        let paper = recommend_memory(&host, 0, &ServerType::Paper, 30);
+        assert_eq!(paper.ideal_mb, 4096);
+        assert_eq!(paper.recommended.max_mb, 4096);
+        assert_eq!(paper.recommended.min_mb, 4096);
+        assert!(paper.warnings.is_empty());
+
+        let forge = recommend_memory(&host, 8192, &ServerType::Forge, 40);
+        assert_eq!(forge.ideal_mb, 9216);
+        assert_eq!(forge.recommended.max_mb, 5632);
+        assert_eq!(forge.warnings.len(), 1);
+
+        let starved = recommend_memory(&host, 14000, &ServerType::Vanilla, 0);
+        assert_eq!(starved.recommended.max_mb, 1024);
+        assert!(starved.warnings[0].contains("doesn't have enough"));
+
+        assert!(check_overcommit(&host, &[("Lobby".to_string(), 4096)], 4096).is_none());
+        let warning = check_overcommit(&host, &[("Lobby".to_string(), 4096), ("SMP".to_string(), 8192)], 4096).unwrap();
+        assert!(warning.contains("16384 MB of heap"));
+        assert!(warning.contains("Lobby, SMP"));
+    }
+}
@@ -0,0 +1,674 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use tokio::fs;
+use crate::config::ServerProperties;
+use crate::jar_scan::{blocked, JarScanner};
+use crate::jvm::{aikar_flags, JvmOptions};
+use crate::lockfile::{sha256_hex, sha512_hex, ExtensionKind, ExtensionSource, LockEntry, Lockfile};
+use crate::mods::{read_mod_jar, ModLoader};
+use crate::templates::{ServerTemplate, TemplateVariables};
+use crate::{MemoryConfig, ServerConfig, ServerType};
+
+/// What the user picks for a server created from a modpack.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModpackOptions {
+    pub name: String,
+    pub port: u16,
+    pub memory: MemoryConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModpackImport {
+    pub config: ServerConfig,
+    pub pack_name: String,
+    pub pack_version: Option<String>,
+    /// Files downloaded from the pack's index.
+    pub downloaded: Vec<String>,
+    /// Client-only files the pack lists, which were left out.
+    pub skipped: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MrpackIndex {
+    format_version: u32,
+    game: String,
+    version_id: Option<String>,
+    name: String,
+    files: Vec<MrpackFile>,
+    dependencies: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct MrpackFile {
+    path: String,
+    hashes: HashMap<String, String>,
+    env: Option<MrpackEnv>,
+    downloads: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct MrpackEnv {
+    server: String,
+}
+
+/// The loader and Minecraft version a pack runs on.
+struct PackTarget {
+    server_type: ServerType,
+    minecraft_version: String,
+    loader_version: Option<String>,
+}
+
+/// `path` as a relative path that stays inside the folder it's joined to.
+fn safe_relative(path: &str) -> Option<PathBuf> {
+    let path = Path::new(path);
+    let safe = !path.as_os_str().is_empty() && path.components().all(|c| matches!(c, Component::Normal(_)));
+    safe.then(|| path.to_path_buf())
+}
+
+fn read_entry(zip: &mut zip::ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Result<Option<String>, String> {
+    let mut file = match zip.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut text = String::new();
+    file.read_to_string(&mut text).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(Some(text))
+}
+
+/// Writes the entries under each of `prefixes` to `destination`, later prefixes
+/// overwriting earlier ones. Returns the paths written, relative to `destination`.
+fn extract(bytes: Vec<u8>, prefixes: &[&str], destination: &Path) -> Result<Vec<String>, String> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let mut written = Vec::new();
+    for prefix in prefixes {
+        for i in 0..zip.len() {
+            let mut entry = zip.by_index(i).map_err(|e| e.to_string())?;
+            if entry.is_dir() {
+                continue;
+            }
+            let Some(relative) = entry.name().strip_prefix(prefix) else {
+                continue;
+            };
+            let relative = relative.to_string();
+            let target = safe_relative(&relative)
+                .map(|p| destination.join(p))
+                .ok_or_else(|| format!("Refusing to extract {}", entry.name()))?;
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let mut out = std::fs::File::create(&target).map_err(|e| e.to_string())?;
+            std::io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+            written.push(relative);
+        }
+    }
+    Ok(written)
+}
+
+/// Where a Modrinth CDN URL points, as `(project id, version id)`.
+fn modrinth_cdn_ids(url: &str) -> Option<(String, String)> {
+    let rest = url.split("cdn.modrinth.com/data/").nth(1)?;
+    let mut parts = rest.split('/');
+    let project = parts.next()?.to_string();
+    (parts.next()? == "versions").then_some(())?;
+    Some((project, parts.next()?.to_string()))
+}
+
+/// A lockfile entry for a mod jar the pack put at `relative`.
+fn mod_lock_entry(relative: &str, bytes: &[u8], source: ExtensionSource, loader: Option<ModLoader>) -> LockEntry {
+    let file_name = relative.rsplit('/').next().unwrap_or(relative).to_string();
+    let (name, version) = read_mod_jar(bytes, &PathBuf::from(relative), loader)
+        .ok()
+        .and_then(|mods| mods.into_iter().next())
+        .map(|m| (m.id, m.version))
+        .unwrap_or_else(|| (file_name.trim_end_matches(".jar").to_string(), String::new()));
+    LockEntry {
+        name,
+        kind: ExtensionKind::Mod,
+        version,
+        file_name,
+        sha256: sha256_hex(bytes),
+        source,
+        installed_at: Utc::now(),
+    }
+}
+
+fn is_mod_jar(relative: &str) -> bool {
+    relative.starts_with("mods/") && !relative[5..].contains('/') && relative.ends_with(".jar")
+}
+
+/// The Minecraft release a NeoForge version is built for: `20.4.80` is for 1.20.4 and
+/// `21.0.1` for 1.21.
+fn neoforge_minecraft_version(version: &str) -> Option<String> {
+    let mut parts = version.split('.');
+    let (minor, patch) = (parts.next()?, parts.next()?);
+    Some(if patch == "0" { format!("1.{}", minor) } else { format!("1.{}.{}", minor, patch) })
+}
+
+/// The server type for a loader name as packs write it (`forge`, `fabric-loader`, ...).
+fn loader_server_type(loader: &str) -> Option<ServerType> {
+    match loader.to_ascii_lowercase().as_str() {
+        "forge" => Some(ServerType::Forge),
+        "neoforge" => Some(ServerType::NeoForge),
+        "fabric" | "fabric-loader" => Some(ServerType::Fabric),
+        "quilt" | "quilt-loader" => Some(ServerType::Quilt),
+        _ => None,
+    }
+}
+
+fn mrpack_target(index: &MrpackIndex) -> Result<PackTarget, String> {
+    let minecraft_version = index
+        .dependencies
+        .get("minecraft")
+        .cloned()
+        .ok_or("The pack doesn't say which Minecraft version it's for")?;
+    // Quilt packs may also list Fabric for compatibility, so check it first.
+    for key in ["neoforge", "forge", "quilt-loader", "fabric-loader"] {
+        if let Some(version) = index.dependencies.get(key) {
+            return Ok(PackTarget {
+                server_type: loader_server_type(key).unwrap_or(ServerType::Vanilla),
+                minecraft_version,
+                loader_version: Some(version.clone()),
+            });
+        }
+    }
+    Ok(PackTarget { server_type: ServerType::Vanilla, minecraft_version, loader_version: None })
+}
+
+/// Works out a CurseForge server pack's loader from its `manifest.json`, the
+/// `variables.txt` of ServerPackCreator packs, or the libraries and installers it ships.
+fn curseforge_target(names: &[String], manifest: Option<&str>, variables: Option<&str>) -> Result<PackTarget, String> {
+    if let Some(manifest) = manifest {
+        let manifest: serde_json::Value = serde_json::from_str(manifest).map_err(|e| format!("Invalid manifest.json: {}", e))?;
+        let minecraft = &manifest["minecraft"];
+        let loaders = minecraft["modLoaders"].as_array().cloned().unwrap_or_default();
+        let primary = loaders
+            .iter()
+            .find(|l| l["primary"].as_bool() == Some(true))
+            .or_else(|| loaders.first())
+            .and_then(|l| l["id"].as_str());
+        if let (Some(version), Some((loader, loader_version))) = (minecraft["version"].as_str(), primary.and_then(|id| id.split_once('-'))) {
+            if let Some(server_type) = loader_server_type(loader) {
+                return Ok(PackTarget {
+                    server_type,
+                    minecraft_version: version.to_string(),
+                    loader_version: Some(loader_version.to_string()),
+                });
+            }
+        }
+    }
+
+    if let Some(variables) = variables {
+        let values: HashMap<&str, &str> = variables
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(k, v)| (k.trim(), v.trim().trim_matches('"')))
+            .collect();
+        if let (Some(version), Some(server_type)) = (values.get("MINECRAFT_VERSION"), values.get("MODLOADER").and_then(|l| loader_server_type(l))) {
+            return Ok(PackTarget {
+                server_type,
+                minecraft_version: version.to_string(),
+                loader_version: values.get("MODLOADER_VERSION").map(|v| v.to_string()),
+            });
+        }
+    }
+
+    let folder_after = |prefix: &str| -> Option<String> {
+        names
+            .iter()
+            .find_map(|n| n.strip_prefix(prefix).and_then(|rest| rest.split('/').next()))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+    };
+    let root_jar = |prefix: &str, suffix: &str| -> Option<String> {
+        names
+            .iter()
+            .filter(|n| !n.contains('/'))
+            .find_map(|n| n.strip_prefix(prefix).and_then(|rest| rest.strip_suffix(suffix)))
+            .map(|v| v.to_string())
+    };
+
+    let forge = folder_after("libraries/net/minecraftforge/forge/").or_else(|| root_jar("forge-", "-installer.jar"));
+    if let Some((minecraft, forge)) = forge.as_deref().and_then(|v| v.split_once('-')) {
+        return Ok(PackTarget {
+            server_type: ServerType::Forge,
+            minecraft_version: minecraft.to_string(),
+            loader_version: Some(forge.to_string()),
+        });
+    }
+    let neoforge = folder_after("libraries/net/neoforged/neoforge/").or_else(|| root_jar("neoforge-", "-installer.jar"));
+    if let Some(version) = neoforge {
+        let minecraft_version = neoforge_minecraft_version(&version)
+            .ok_or_else(|| format!("Unrecognised NeoForge version {}", version))?;
+        return Ok(PackTarget { server_type: ServerType::NeoForge, minecraft_version, loader_version: Some(version) });
+    }
+    for (server_type, library) in [
+        (ServerType::Quilt, "libraries/org/quiltmc/quilt-loader/"),
+        (ServerType::Fabric, "libraries/net/fabricmc/fabric-loader/"),
+    ] {
+        if let Some(loader_version) = folder_after(library) {
+            let minecraft_version = folder_after("libraries/net/fabricmc/intermediary/")
+                .ok_or("Can't tell which Minecraft version the pack is for")?;
+            return Ok(PackTarget { server_type, minecraft_version, loader_version: Some(loader_version) });
+        }
+    }
+    Err("Can't tell which mod loader the pack uses".to_string())
+}
+
+/// The folder all entries share, when the archive wraps everything in one.
+fn common_root(names: &[String]) -> String {
+    let Some(first) = names.first().and_then(|n| n.split_once('/')).map(|(root, _)| root) else {
+        return String::new();
+    };
+    if names.iter().all(|n| n.starts_with(&format!("{}/", first))) {
+        format!("{}/", first)
+    } else {
+        String::new()
+    }
+}
+
+/// Creates the server skeleton for a pack: `server.properties` and a start script with
+/// Aikar's flags, as for a template.
+async fn create_server(server_path: &PathBuf, options: &ModpackOptions, target: &PackTarget, pack_name: &str) -> Result<ServerConfig, String> {
+    let template = ServerTemplate {
+        id: "modpack".to_string(),
+        name: pack_name.to_string(),
+        description: String::new(),
+        server_type: target.server_type,
+        version: target.minecraft_version.clone(),
+        memory: options.memory.clone(),
+        jvm_flags: aikar_flags(options.memory.max_mb),
+        properties: BTreeMap::new(),
+        plugins: Vec::new(),
+        datapacks: Vec::new(),
+        builtin: false,
+    };
+    let variables = TemplateVariables { name: options.name.clone(), port: options.port, seed: None };
+    // The skeleton has no plugins or mods to scan; the pack's jars are scanned later.
+    let mut config = template.instantiate(server_path, &variables, &JarScanner::new()).await?.config;
+    config.loader_version = target.loader_version.clone();
+    Ok(config)
+}
+
+/// Puts the chosen port back when the pack shipped its own `server.properties`.
+async fn restore_port(server_path: &PathBuf, written: &[String], port: u16) -> Result<(), String> {
+    if !written.iter().any(|p| p == "server.properties") {
+        return Ok(());
+    }
+    let path = server_path.join("server.properties");
+    let mut properties = ServerProperties::new();
+    properties.load_from_file(&path).await?;
+    properties.set_property("server-port", &port.to_string())?;
+    properties.save_to_file(&path).await
+}
+
+/// Scans the imported plugin and mod jars. Blocklisted jars are deleted and fail the
+/// import; other findings become warnings.
+async fn scan_imported(server_path: &PathBuf, scanner: &JarScanner, warnings: &mut Vec<String>) -> Result<(), String> {
+    let mut refused = Vec::new();
+    for report in scanner.scan_server(server_path).await? {
+        if let Some(finding) = blocked(&report.findings) {
+            let _ = fs::remove_file(&report.file).await;
+            refused.push(format!("{}: {}", finding.file, finding.details));
+            continue;
+        }
+        warnings.extend(report.findings.iter().map(|f| format!("{}: {}", f.file, f.details)));
+    }
+    if refused.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Refusing to import the pack: {}", refused.join("; ")))
+    }
+}
+
+/// Removes a half-imported server, unless its folder was there before the import.
+async fn cleanup_on_error<T>(server_path: &PathBuf, existed: bool, result: Result<T, String>) -> Result<T, String> {
+    if result.is_err() && !existed {
+        let _ = fs::remove_dir_all(server_path).await;
+    }
+    result
+}
+
+async fn download_file(file: &MrpackFile) -> Result<(Vec<u8>, String), String> {
+    let expected = file
+        .hashes
+        .get("sha512")
+        .ok_or_else(|| format!("{} has no SHA-512 hash", file.path))?;
+    let mut last_error = format!("{} has no download URL", file.path);
+    for url in &file.downloads {
+        let bytes = match reqwest::get(url).await {
+            Ok(response) if response.status().is_success() => match response.bytes().await {
+                Ok(bytes) => bytes.to_vec(),
+                Err(e) => {
+                    last_error = format!("Failed to download {}: {}", file.path, e);
+                    continue;
+                }
+            },
+            Ok(response) => {
+                last_error = format!("Failed to download {}: HTTP {}", file.path, response.status());
+                continue;
+            }
+            Err(e) => {
+                last_error = format!("Failed to download {}: {}", file.path, e);
+                continue;
+            }
+        };
+        let actual = sha512_hex(&bytes);
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(format!("Checksum mismatch for {}: expected {}, got {}", file.path, expected, actual));
+        }
+        return Ok((bytes, url.clone()));
+    }
+    Err(last_error)
+}
+
+/// Creates a server at `server_path` from a Modrinth `.mrpack`: downloads the files
+/// the server needs, applies `overrides/` then `server-overrides/`, and records the
+/// mods in the lockfile. Packs with a blocklisted jar are refused.
+pub async fn import_mrpack(pack: &PathBuf, server_path: &PathBuf, options: &ModpackOptions, scanner: &JarScanner) -> Result<ModpackImport, String> {
+    let bytes = fs::read(pack)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", pack.display(), e))?;
+    let mut zip = zip::ZipArchive::new(Cursor::new(bytes.clone()))
+        .map_err(|e| format!("{} is not a valid modpack: {}", pack.display(), e))?;
+    let index = read_entry(&mut zip, "modrinth.index.json")?
+        .ok_or_else(|| format!("{} is not a Modrinth pack: it has no modrinth.index.json", pack.display()))?;
+    let index: MrpackIndex = serde_json::from_str(&index).map_err(|e| format!("Invalid modrinth.index.json: {}", e))?;
+    if index.format_version != 1 || index.game != "minecraft" {
+        return Err(format!("Unsupported pack format {} for {}", index.format_version, index.game));
+    }
+    for file in &index.files {
+        safe_relative(&file.path).ok_or_else(|| format!("Refusing to write {}", file.path))?;
+    }
+    let target = mrpack_target(&index)?;
+
+    let existed = server_path.exists();
+    let result = async {
+        let config = create_server(server_path, options, &target, &index.name).await?;
+        let loader = ModLoader::for_server(&target.server_type);
+        let mut lockfile = Lockfile::load(server_path).await?;
+        let mut import = ModpackImport {
+            config,
+            pack_name: index.name.clone(),
+            pack_version: index.version_id.clone(),
+            downloaded: Vec::new(),
+            skipped: Vec::new(),
+            warnings: Vec::new(),
+        };
+
+        for file in &index.files {
+            if file.env.as_ref().is_some_and(|env| env.server == "unsupported") {
+                import.skipped.push(file.path.clone());
+                continue;
+            }
+            let (bytes, url) = download_file(file).await?;
+            let destination = server_path.join(&file.path);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+            }
+            fs::write(&destination, &bytes).await.map_err(|e| e.to_string())?;
+            if is_mod_jar(&file.path) {
+                let file_name = file.path.rsplit('/').next().unwrap_or(&file.path).to_string();
+                let source = match modrinth_cdn_ids(&url) {
+                    Some((project_id, version_id)) => ExtensionSource::Modrinth { project_id, version_id, url, file_name },
+                    None => ExtensionSource::Url { url },
+                };
+                lockfile.upsert(mod_lock_entry(&file.path, &bytes, source, loader));
+            }
+            import.downloaded.push(file.path.clone());
+        }
+
+        let destination = server_path.clone();
+        let archive = bytes.clone();
+        let written = tokio::task::spawn_blocking(move || extract(archive, &["overrides/", "server-overrides/"], &destination))
+            .await
+            .map_err(|e| e.to_string())??;
+        for relative in written.iter().filter(|p| is_mod_jar(p)) {
+            let jar = fs::read(server_path.join(relative)).await.map_err(|e| e.to_string())?;
+            // server-overrides wins when both folders carry the jar.
+            let in_pack = if zip.by_name(&format!("server-overrides/{}", relative)).is_ok() { "server-overrides" } else { "overrides" };
+            let source = ExtensionSource::Modpack { pack: pack.clone(), path: format!("{}/{}", in_pack, relative) };
+            lockfile.upsert(mod_lock_entry(relative, &jar, source, loader));
+        }
+        restore_port(server_path, &written, options.port).await?;
+        if target.server_type != ServerType::Vanilla {
+            import.warnings.push(format!(
+                "Install the {:?} {} server for Minecraft {} as server.jar before starting",
+                target.server_type,
+                target.loader_version.as_deref().unwrap_or_default(),
+                target.minecraft_version
+            ));
+        }
+        scan_imported(server_path, scanner, &mut import.warnings).await?;
+        lockfile.save(server_path).await?;
+        Ok(import)
+    }
+    .await;
+    cleanup_on_error(server_path, existed, result).await
+}
+
+/// Creates a server at `server_path` from a CurseForge server pack, which already
+/// contains the mods and configs. Client exports, which only list mod ids, and packs
+/// with a blocklisted jar are refused.
+pub async fn import_curseforge_server_pack(pack: &PathBuf, server_path: &PathBuf, options: &ModpackOptions, scanner: &JarScanner) -> Result<ModpackImport, String> {
+    let bytes = fs::read(pack)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", pack.display(), e))?;
+    let mut zip = zip::ZipArchive::new(Cursor::new(bytes.clone()))
+        .map_err(|e| format!("{} is not a valid modpack: {}", pack.display(), e))?;
+    let all_names: Vec<String> = zip.file_names().map(|n| n.to_string()).collect();
+    let root = common_root(&all_names);
+    let names: Vec<String> = all_names.iter().filter_map(|n| n.strip_prefix(&root)).map(|n| n.to_string()).collect();
+
+    let manifest = read_entry(&mut zip, &format!("{}manifest.json", root))?;
+    let variables = read_entry(&mut zip, &format!("{}variables.txt", root))?;
+    if manifest.is_some() && !names.iter().any(|n| is_mod_jar(n)) {
+        return Err("This is a CurseForge client export; download the pack's server files instead".to_string());
+    }
+    let target = curseforge_target(&names, manifest.as_deref(), variables.as_deref())?;
+    let pack_name = manifest
+        .as_deref()
+        .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
+        .and_then(|m| m["name"].as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| pack.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default());
+    let pack_version = manifest
+        .as_deref()
+        .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
+        .and_then(|m| m["version"].as_str().map(|s| s.to_string()));
+
+    let existed = server_path.exists();
+    let result = async {
+        let config = create_server(server_path, options, &target, &pack_name).await?;
+        let destination = server_path.clone();
+        let prefix = root.clone();
+        let written = tokio::task::spawn_blocking(move || extract(bytes, &[prefix.as_str()], &destination))
+            .await
+            .map_err(|e| e.to_string())??;
+        restore_port(server_path, &written, options.port).await?;
+
+        let loader = ModLoader::for_server(&target.server_type);
+        let mut lockfile = Lockfile::load(server_path).await?;
+        for relative in written.iter().filter(|p| is_mod_jar(p)) {
+            let jar = fs::read(server_path.join(relative)).await.map_err(|e| e.to_string())?;
+            let source = ExtensionSource::Modpack { pack: pack.clone(), path: format!("{}{}", root, relative) };
+            lockfile.upsert(mod_lock_entry(relative, &jar, source, loader));
+        }
+
+        let mut warnings = Vec::new();
+        let script = if cfg!(target_os = "windows") { "start.bat" } else { "start.sh" };
+        let pack_script = written.iter().any(|p| p == script);
+        if pack_script {
+            warnings.push(format!("The pack's own {} replaced the one MCSM generated", script));
+        }
+        // Forge and NeoForge 1.17+ ship the server as libraries launched through an
+        // argument file rather than a runnable server.jar.
+        let args_name = if cfg!(target_os = "windows") { "win_args.txt" } else { "unix_args.txt" };
+        let args_file = written.iter().find(|p| p.starts_with("libraries/") && p.ends_with(&format!("/{}", args_name)));
+        if let Some(args_file) = args_file {
+            let mut jvm_options = JvmOptions::load(server_path).await?;
+            jvm_options.args_file = Some(args_file.clone());
+            jvm_options.save(server_path).await?;
+            if !pack_script {
+                jvm_options.write_start_script(server_path, &options.memory, None, "java").await?;
+            }
+        } else if !written.iter().any(|p| p == "server.jar") {
+            warnings.push(format!(
+                "Install the {:?} {} server for Minecraft {} as server.jar before starting",
+                target.server_type,
+                target.loader_version.as_deref().unwrap_or_default(),
+                target.minecraft_version
+            ));
+        }
+        scan_imported(server_path, scanner, &mut warnings).await?;
+        lockfile.save(server_path).await?;
+        Ok(ModpackImport { config, pack_name: pack_name.clone(), pack_version: pack_version.clone(), downloaded: Vec::new(), skipped: Vec::new(), warnings })
+    }
+    .await;
+    cleanup_on_error(server_path, existed, result).await
+}
+
+/// Imports a `.mrpack` or a CurseForge server pack, telling them apart by the
+/// `modrinth.index.json` every Modrinth pack has.
+pub async fn import_modpack(pack: &PathBuf, server_path: &PathBuf, options: &ModpackOptions, scanner: &JarScanner) -> Result<ModpackImport, String> {
+    let bytes = fs::read(pack)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", pack.display(), e))?;
+    let zip = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| format!("{} is not a valid modpack: {}", pack.display(), e))?;
+    if zip.file_names().any(|n| n == "modrinth.index.json") {
+        import_mrpack(pack, server_path, options, scanner).await
+    } else {
+        import_curseforge_server_pack(pack, server_path, options, scanner).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const LITHIUM: &[u8] = include_bytes!("../tests/fixtures/mods/lithium-fabric-0.11.2.jar");
+    const JEI: &[u8] = include_bytes!("../tests/fixtures/mods/jei-forge-15.3.0.jar");
+
+    fn archive(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, bytes) in entries {
+            zip.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            zip.write_all(bytes).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn options() -> ModpackOptions {
+        ModpackOptions { name: "Pack".to_string(), port: 25600, memory: MemoryConfig { min_mb: 2048, max_mb: 4096 } }
+    }
+
+    #[tokio::test]
+    async fn test_import_modpacks() {
+        let root = std::env::temp_dir().join("mcsm_test_modpacks");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+        server.mock("GET", "/data/gvQqBUqZ/versions/nMhjKWVE/lithium-fabric-0.11.2.jar").with_body(LITHIUM).create_async().await;
+
+        let index = serde_json::json!({
+            "formatVersion": 1,
+            "game": "minecraft",
+            "versionId": "1.2.0",
+            "name": "Speedy",
+            "dependencies": { "minecraft": "1.20.4", "fabric-loader": "0.15.7" },
+            "files": [
+                {
+                    "path": "mods/lithium-fabric-0.11.2.jar",
+                    "hashes": { "sha512": sha512_hex(LITHIUM) },
+                    "env": { "client": "required", "server": "required" },
+                    // The first mirror is down.
+                    "downloads": [format!("{}/missing.jar", url), format!("{}/data/gvQqBUqZ/versions/nMhjKWVE/lithium-fabric-0.11.2.jar", url)],
+                },
+                {
+                    "path": "mods/sodium-fabric-0.5.8.jar",
+                    "hashes": { "sha512": "00" },
+                    "env": { "client": "required", "server": "unsupported" },
+                    "downloads": [format!("{}/sodium.jar", url)],
+                },
+            ],
+        });
+        let mrpack = root.join("Speedy.mrpack");
+        std::fs::write(&mrpack, archive(&[
+            ("modrinth.index.json", index.to_string().as_bytes()),
+            ("overrides/config/lithium.properties", b"mixin.ai=false\n"),
+            ("overrides/server.properties", b"motd=Speedy\nserver-port=1234\n"),
+            ("server-overrides/server.properties", b"motd=Speedy server\nserver-port=1234\n"),
+        ])).unwrap();
+
+        let server_path = root.join("speedy");
+        let imported = import_modpack(&mrpack, &server_path, &options(), &JarScanner::new()).await.unwrap();
+        assert_eq!(imported.config.server_type, ServerType::Fabric);
+        assert_eq!(imported.config.version, "1.20.4");
+        assert_eq!(imported.config.loader_version.as_deref(), Some("0.15.7"));
+        assert_eq!(imported.downloaded, ["mods/lithium-fabric-0.11.2.jar"]);
+        assert_eq!(imported.skipped, ["mods/sodium-fabric-0.5.8.jar"]);
+        assert!(server_path.join("config/lithium.properties").exists());
+        let properties = std::fs::read_to_string(server_path.join("server.properties")).unwrap();
+        assert!(properties.contains("motd=Speedy server") && properties.contains("server-port=25600"));
+        let lockfile = Lockfile::load(&server_path).await.unwrap();
+        let lithium = lockfile.get(ExtensionKind::Mod, "lithium").unwrap();
+        assert_eq!(lithium.sha256, sha256_hex(LITHIUM));
+        assert!(matches!(&lithium.source, ExtensionSource::Url { url } if url.ends_with("/lithium-fabric-0.11.2.jar")));
+        assert_eq!(
+            modrinth_cdn_ids("https://cdn.modrinth.com/data/gvQqBUqZ/versions/nMhjKWVE/lithium-fabric-0.11.2.jar"),
+            Some(("gvQqBUqZ".to_string(), "nMhjKWVE".to_string()))
+        );
+
+        let evil = root.join("evil.mrpack");
+        let index = serde_json::json!({
+            "formatVersion": 1, "game": "minecraft", "name": "Evil", "dependencies": { "minecraft": "1.20.4" },
+            "files": [{ "path": "../outside.txt", "hashes": { "sha512": "00" }, "downloads": [] }],
+        });
+        std::fs::write(&evil, archive(&[("modrinth.index.json", index.to_string().as_bytes())])).unwrap();
+        assert_eq!(import_modpack(&evil, &root.join("evil"), &options(), &JarScanner::new()).await.unwrap_err(), "Refusing to write ../outside.txt");
+        assert!(!root.join("evil").exists());
+
+        let server_pack = root.join("Tech-1.0-server.zip");
+        std::fs::write(&server_pack, archive(&[
+            ("Tech 1.0/mods/jei-forge-15.3.0.jar", JEI),
+            ("Tech 1.0/libraries/net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-server.jar", b""),
+            ("Tech 1.0/libraries/net/minecraftforge/forge/1.20.1-47.2.0/unix_args.txt", b""),
+            ("Tech 1.0/libraries/net/minecraftforge/forge/1.20.1-47.2.0/win_args.txt", b""),
+            ("Tech 1.0/config/jei/jei-client.ini", b"[appearance]\n"),
+        ])).unwrap();
+        let tech_path = root.join("tech");
+        let imported = import_modpack(&server_pack, &tech_path, &options(), &JarScanner::new()).await.unwrap();
+        assert!(imported.warnings.is_empty());
+        let script = std::fs::read_to_string(tech_path.join(if cfg!(target_os = "windows") { "start.bat" } else { "start.sh" })).unwrap();
+        assert!(script.contains(" @libraries/net/minecraftforge/forge/1.20.1-47.2.0/"));
+        assert!(!script.contains("-jar"));
+        assert_eq!(imported.config.server_type, ServerType::Forge);
+        assert_eq!(imported.config.version, "1.20.1");
+        assert_eq!(imported.config.loader_version.as_deref(), Some("47.2.0"));
+        assert!(tech_path.join("config/jei/jei-client.ini").exists());
+        let lockfile = Lockfile::load(&tech_path).await.unwrap();
+        assert_eq!(
+            lockfile.get(ExtensionKind::Mod, "jei").unwrap().source,
+            ExtensionSource::Modpack { pack: server_pack.clone(), path: "Tech 1.0/mods/jei-forge-15.3.0.jar".to_string() }
+        );
+
+        let mut scanner = JarScanner::new();
+        scanner.block(&sha256_hex(JEI), "backdoored build");
+        let error = import_modpack(&server_pack, &root.join("blocked"), &options(), &scanner).await.unwrap_err();
+        assert_eq!(error, "Refusing to import the pack: jei-forge-15.3.0.jar: On the jar blocklist: backdoored build");
+        assert!(!root.join("blocked").exists());
+
+        let client_export = root.join("client.zip");
+        std::fs::write(&client_export, archive(&[
+            ("manifest.json", br#"{"minecraft":{"version":"1.20.1","modLoaders":[{"id":"forge-47.2.0","primary":true}]},"files":[{"projectID":1,"fileID":2}]}"#),
+        ])).unwrap();
+        assert!(import_modpack(&client_export, &root.join("client"), &options(), &JarScanner::new()).await.unwrap_err().contains("client export"));
+    }
+}
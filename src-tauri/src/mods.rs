@@ -0,0 +1,655 @@
+use std::io::{Cursor, Read};
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+use tokio::fs;
+use crate::catalog::Catalog;
+use crate::jar_install::{export_entries, matches_entry, record_existing, record_install, stage_jar, InstallResult, StagedJar};
+use crate::jar_scan::JarScanner;
+use crate::lockfile::{ExtensionKind, ExtensionSource, Lockfile};
+use crate::plugins::{PluginActionOutcome, PluginLoadError};
+use crate::property_schema::{IssueSeverity, ValidationIssue};
+use crate::ServerType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ModLoader {
+    Fabric,
+    Quilt,
+    Forge,
+    NeoForge,
+}
+
+impl ModLoader {
+    pub fn for_server(server_type: &ServerType) -> Option<Self> {
+        match server_type {
+            ServerType::Fabric => Some(ModLoader::Fabric),
+            ServerType::Quilt => Some(ModLoader::Quilt),
+            ServerType::Forge => Some(ModLoader::Forge),
+            ServerType::NeoForge => Some(ModLoader::NeoForge),
+            ServerType::Vanilla | ServerType::Spigot | ServerType::Paper => None,
+        }
+    }
+
+    /// Whether a server running this loader loads mods written for `other`.
+    /// Quilt also loads Fabric mods.
+    pub fn loads(&self, other: ModLoader) -> bool {
+        *self == other || (*self == ModLoader::Quilt && other == ModLoader::Fabric)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ModEnvironment {
+    Both,
+    Client,
+    Server,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DependencyKind {
+    Required,
+    Optional,
+    Incompatible,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModDependency {
+    pub id: String,
+    /// As written by the mod: a Maven range such as `[47,)` for Forge, or a version
+    /// predicate such as `>=0.15` for Fabric and Quilt.
+    pub version_range: Option<String>,
+    pub kind: DependencyKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub authors: Vec<String>,
+    pub description: String,
+    pub loader: ModLoader,
+    pub environment: ModEnvironment,
+    pub dependencies: Vec<ModDependency>,
+    pub file_path: PathBuf,
+}
+
+/// Dependency ids that name the game, Java or a loader rather than another mod.
+const PLATFORM_IDS: &[&str] = &["minecraft", "java", "fabricloader", "quilt_loader", "forge", "neoforge"];
+
+fn json_string(value: &JsonValue, key: &str) -> Option<String> {
+    value.get(key).and_then(JsonValue::as_str).map(str::to_string)
+}
+
+/// Version predicates in fabric.mod.json and quilt.mod.json may be a string or a list.
+fn json_range(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::String(s) => Some(s.clone()),
+        JsonValue::Array(items) => Some(
+            items
+                .iter()
+                .filter_map(JsonValue::as_str)
+                .collect::<Vec<_>>()
+                .join(" || "),
+        ),
+        _ => None,
+    }
+}
+
+fn parse_fabric(text: &str, file_path: &PathBuf) -> Result<Vec<ModInfo>, String> {
+    let json: JsonValue = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let id = json_string(&json, "id").ok_or("missing `id`")?;
+
+    let authors = json
+        .get("authors")
+        .and_then(JsonValue::as_array)
+        .map(|authors| {
+            authors
+                .iter()
+                .filter_map(|a| a.as_str().map(str::to_string).or_else(|| json_string(a, "name")))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut dependencies = Vec::new();
+    for (key, kind) in [
+        ("depends", DependencyKind::Required),
+        ("recommends", DependencyKind::Optional),
+        ("suggests", DependencyKind::Optional),
+        ("breaks", DependencyKind::Incompatible),
+    ] {
+        if let Some(JsonValue::Object(entries)) = json.get(key) {
+            for (dep_id, range) in entries {
+                dependencies.push(ModDependency {
+                    id: dep_id.clone(),
+                    version_range: json_range(range),
+                    kind,
+                });
+            }
+        }
+    }
+
+    let environment = match json.get("environment").and_then(JsonValue::as_str) {
+        Some("client") => ModEnvironment::Client,
+        Some("server") => ModEnvironment::Server,
+        _ => ModEnvironment::Both,
+    };
+
+    Ok(vec![ModInfo {
+        name: json_string(&json, "name").unwrap_or_else(|| id.clone()),
+        version: json_string(&json, "version").ok_or("missing `version`")?,
+        description: json_string(&json, "description").unwrap_or_default(),
+        loader: ModLoader::Fabric,
+        file_path: file_path.clone(),
+        id,
+        authors,
+        environment,
+        dependencies,
+    }])
+}
+
+fn parse_quilt(text: &str, file_path: &PathBuf) -> Result<Vec<ModInfo>, String> {
+    let json: JsonValue = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let loader = json.get("quilt_loader").ok_or("missing `quilt_loader`")?;
+    let id = json_string(loader, "id").ok_or("missing `quilt_loader.id`")?;
+    let metadata = loader.get("metadata").cloned().unwrap_or(JsonValue::Null);
+
+    let authors = metadata
+        .get("contributors")
+        .and_then(JsonValue::as_object)
+        .map(|contributors| contributors.keys().cloned().collect())
+        .unwrap_or_default();
+
+    let mut dependencies = Vec::new();
+    for (key, default_kind) in [("depends", DependencyKind::Required), ("breaks", DependencyKind::Incompatible)] {
+        let Some(JsonValue::Array(entries)) = loader.get(key) else {
+            continue;
+        };
+        for entry in entries {
+            let dependency = match entry {
+                JsonValue::String(dep_id) => ModDependency { id: dep_id.clone(), version_range: None, kind: default_kind },
+                JsonValue::Object(_) => {
+                    let Some(dep_id) = json_string(entry, "id") else {
+                        continue;
+                    };
+                    let optional = entry.get("optional").and_then(JsonValue::as_bool).unwrap_or(false);
+                    ModDependency {
+                        id: dep_id,
+                        version_range: entry.get("versions").and_then(json_range),
+                        kind: if optional && default_kind == DependencyKind::Required { DependencyKind::Optional } else { default_kind },
+                    }
+                }
+                _ => continue,
+            };
+            dependencies.push(dependency);
+        }
+    }
+
+    let environment = match json.get("minecraft").and_then(|m| m.get("environment")).and_then(JsonValue::as_str) {
+        Some("client") => ModEnvironment::Client,
+        Some("dedicated_server") => ModEnvironment::Server,
+        _ => ModEnvironment::Both,
+    };
+
+    Ok(vec![ModInfo {
+        name: json_string(&metadata, "name").unwrap_or_else(|| id.clone()),
+        version: json_string(loader, "version").ok_or("missing `quilt_loader.version`")?,
+        description: json_string(&metadata, "description").unwrap_or_default(),
+        loader: ModLoader::Quilt,
+        file_path: file_path.clone(),
+        id,
+        authors,
+        environment,
+        dependencies,
+    }])
+}
+
+/// Parses `META-INF/mods.toml` or `META-INF/neoforge.mods.toml`. `${file.jarVersion}`
+/// is replaced with the jar manifest's `Implementation-Version`.
+fn parse_mods_toml(text: &str, file_path: &PathBuf, neoforge_file: bool, jar_version: Option<&str>) -> Result<Vec<ModInfo>, String> {
+    let toml: toml::Value = toml::from_str(text).map_err(|e| e.to_string())?;
+    let toml_string = |value: &toml::Value, key: &str| value.get(key).and_then(toml::Value::as_str).map(str::to_string);
+
+    let client_only = toml.get("clientSideOnly").and_then(toml::Value::as_bool).unwrap_or(false);
+    let all_dependencies = toml.get("dependencies").and_then(toml::Value::as_table);
+    let mods = toml
+        .get("mods")
+        .and_then(toml::Value::as_array)
+        .filter(|mods| !mods.is_empty())
+        .ok_or("no [[mods]] entries")?;
+
+    let mut result = Vec::new();
+    for entry in mods {
+        let id = toml_string(entry, "modId").ok_or("missing `modId`")?;
+        let version = match toml_string(entry, "version").as_deref() {
+            Some("${file.jarVersion}") | None => jar_version.unwrap_or("unknown").to_string(),
+            Some(version) => version.to_string(),
+        };
+
+        let dependencies: Vec<ModDependency> = all_dependencies
+            .and_then(|deps| deps.get(&id))
+            .and_then(toml::Value::as_array)
+            .map(|deps| {
+                deps.iter()
+                    .filter_map(|dep| {
+                        // Forge uses `mandatory`; NeoForge replaced it with `type`.
+                        let kind = match toml_string(dep, "type").as_deref().map(str::to_lowercase).as_deref() {
+                            Some("required") => DependencyKind::Required,
+                            Some("incompatible") => DependencyKind::Incompatible,
+                            Some(_) => DependencyKind::Optional,
+                            None if dep.get("mandatory").and_then(toml::Value::as_bool).unwrap_or(false) => DependencyKind::Required,
+                            None => DependencyKind::Optional,
+                        };
+                        Some(ModDependency {
+                            id: toml_string(dep, "modId")?,
+                            version_range: toml_string(dep, "versionRange"),
+                            kind,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Before neoforge.mods.toml existed, NeoForge mods shipped a mods.toml that
+        // depends on `neoforge`.
+        let loader = if neoforge_file || dependencies.iter().any(|d| d.id == "neoforge") {
+            ModLoader::NeoForge
+        } else {
+            ModLoader::Forge
+        };
+
+        result.push(ModInfo {
+            name: toml_string(entry, "displayName").unwrap_or_else(|| id.clone()),
+            authors: toml_string(entry, "authors")
+                .map(|a| a.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+                .unwrap_or_default(),
+            description: toml_string(entry, "description").unwrap_or_default().trim().to_string(),
+            environment: if client_only { ModEnvironment::Client } else { ModEnvironment::Both },
+            file_path: file_path.clone(),
+            id,
+            version,
+            loader,
+            dependencies,
+        });
+    }
+    Ok(result)
+}
+
+fn manifest_version(manifest: &str) -> Option<String> {
+    manifest
+        .lines()
+        .find_map(|line| line.strip_prefix("Implementation-Version:"))
+        .map(|v| v.trim().to_string())
+}
+
+/// Reads the mods declared in a jar. Jars built for several loaders carry metadata for
+/// each; the one `preferred` loads is used when present.
+pub fn read_mod_jar(bytes: &[u8], file_path: &PathBuf, preferred: Option<ModLoader>) -> Result<Vec<ModInfo>, String> {
+    let file_name = file_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut jar = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| format!("{} is not a valid jar: {}", file_name, e))?;
+
+    let mut read = |entry: &str| -> Result<Option<String>, String> {
+        let mut file = match jar.by_name(entry) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(format!("{} is not a valid jar: {}", file_name, e)),
+        };
+        let mut text = String::new();
+        file.read_to_string(&mut text)
+            .map_err(|e| format!("Failed to read {} from {}: {}", entry, file_name, e))?;
+        Ok(Some(text))
+    };
+
+    let jar_version = read("META-INF/MANIFEST.MF")?.as_deref().and_then(manifest_version);
+    let mut candidates = Vec::new();
+    for entry in ["quilt.mod.json", "fabric.mod.json", "META-INF/neoforge.mods.toml", "META-INF/mods.toml"] {
+        let Some(text) = read(entry)? else {
+            continue;
+        };
+        let parsed = match entry {
+            "quilt.mod.json" => parse_quilt(&text, file_path),
+            "fabric.mod.json" => parse_fabric(&text, file_path),
+            "META-INF/neoforge.mods.toml" => parse_mods_toml(&text, file_path, true, jar_version.as_deref()),
+            _ => parse_mods_toml(&text, file_path, false, jar_version.as_deref()),
+        };
+        candidates.push(parsed.map_err(|e| format!("Invalid {} in {}: {}", entry, file_name, e))?);
+    }
+
+    if candidates.is_empty() {
+        return Err(format!("{} has no Fabric, Quilt, Forge or NeoForge metadata", file_name));
+    }
+    let preferred_index = preferred
+        .and_then(|loader| candidates.iter().position(|mods| loader.loads(mods[0].loader)))
+        .unwrap_or(0);
+    Ok(candidates.swap_remove(preferred_index))
+}
+
+/// Mods in a modded server's `mods` folder.
+pub struct ModManager {
+    mods_path: PathBuf,
+    server_type: ServerType,
+    mods: Vec<ModInfo>,
+    load_errors: Vec<PluginLoadError>,
+    scanner: JarScanner,
+}
+
+impl ModManager {
+    pub fn new(server_path: &PathBuf, server_type: ServerType) -> Self {
+        ModManager {
+            mods_path: server_path.join("mods"),
+            server_type,
+            mods: Vec::new(),
+            load_errors: Vec::new(),
+            scanner: JarScanner::new(),
+        }
+    }
+
+    /// Scans installed jars with `scanner` instead of one without a blocklist.
+    pub fn with_scanner(mut self, scanner: JarScanner) -> Self {
+        self.scanner = scanner;
+        self
+    }
+
+    fn server_path(&self) -> PathBuf {
+        self.mods_path.parent().map(PathBuf::from).unwrap_or_default()
+    }
+
+    pub async fn load_mods(&mut self) -> Result<(), String> {
+        self.mods.clear();
+        self.load_errors.clear();
+        if !self.mods_path.exists() {
+            return Ok(());
+        }
+
+        let preferred = ModLoader::for_server(&self.server_type);
+        let mut dir = fs::read_dir(&self.mods_path)
+            .await
+            .map_err(|e| e.to_string())?;
+        while let Some(entry) = dir.next_entry().await.map_err(|e| e.to_string())? {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("jar") {
+                continue;
+            }
+            let bytes = fs::read(&path).await.map_err(|e| e.to_string())?;
+            match read_mod_jar(&bytes, &path, preferred) {
+                Ok(mods) => self.mods.extend(mods),
+                Err(message) => self.load_errors.push(PluginLoadError { file: path, message }),
+            }
+        }
+        self.mods.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(())
+    }
+
+    /// Installs the mod jar at `source`, upgrading the installed jar of the same mod if
+    /// there is one, and records it in the lockfile. Client-only mods and mods for
+    /// another loader are refused.
+    pub async fn install_mod(&mut self, source: &ExtensionSource, expected_hash: Option<&str>) -> Result<InstallResult, String> {
+        let loader = ModLoader::for_server(&self.server_type)
+            .ok_or_else(|| format!("{:?} servers don't load mods", self.server_type))?;
+        self.load_mods().await?;
+        let staged = stage_jar(source, expected_hash, &self.mods_path).await?;
+        let target = self.mods_path.join(&staged.file_name);
+        let primary = match Self::check_mod_jar(&staged, &target, loader) {
+            Ok(primary) => primary,
+            Err(e) => {
+                staged.discard().await;
+                return Err(e);
+            }
+        };
+
+        let existing = self.get_mod(&primary.id).cloned();
+        let mut outcome = PluginActionOutcome { restart_required: true, ..Default::default() };
+        for dep in primary.dependencies.iter().filter(|d| d.kind == DependencyKind::Required) {
+            if !PLATFORM_IDS.contains(&dep.id.as_str()) && self.get_mod(&dep.id).is_none() {
+                outcome.warnings.push(format!("{} requires {}, which isn't installed", primary.name, dep.id));
+            }
+        }
+        let (staged, findings) = staged.scan(&self.scanner, &mut outcome).await?;
+
+        let sha256 = staged.sha256.clone();
+        staged.place(&target, existing.as_ref().map(|m| &m.file_path)).await?;
+        let result = InstallResult {
+            kind: ExtensionKind::Mod,
+            name: primary.id,
+            version: primary.version,
+            file_path: target,
+            sha256,
+            upgraded_from: existing.map(|m| m.version),
+            outcome,
+            findings,
+        };
+        record_install(&self.server_path(), &result, source.clone()).await?;
+        self.load_mods().await?;
+        Ok(result)
+    }
+
+    /// Upgrades the mod `id` to its newest compatible version from the catalog it was
+    /// installed from.
+    pub async fn update_mod(&mut self, id: &str, catalog: &Catalog, minecraft_version: &str) -> Result<InstallResult, String> {
+        let lockfile = Lockfile::load(&self.server_path()).await?;
+        let entry = lockfile
+            .get(ExtensionKind::Mod, id)
+            .ok_or_else(|| format!("{} wasn't installed by MCSM", id))?;
+        let latest = catalog
+            .update_for(entry, self.server_type, minecraft_version)
+            .await?
+            .ok_or_else(|| format!("{} is up to date", id))?;
+        self.install_mod(&latest.extension_source(), latest.hash.as_deref()).await
+    }
+
+    /// Removes the jar that provides `id` and drops it from the lockfile.
+    pub async fn uninstall_mod(&mut self, id: &str) -> Result<(), String> {
+        let path = self
+            .get_mod(id)
+            .map(|m| m.file_path.clone())
+            .ok_or_else(|| format!("{} is not installed", id))?;
+        fs::remove_file(&path).await.map_err(|e| e.to_string())?;
+        let server_path = self.server_path();
+        let mut lockfile = Lockfile::load(&server_path).await?;
+        if lockfile.remove(ExtensionKind::Mod, id).is_some() {
+            lockfile.save(&server_path).await?;
+        }
+        self.load_mods().await
+    }
+
+    /// The first mod in each jar, which names it, as `(id, version, file)`.
+    fn installed_jars(&self) -> Vec<(String, String, PathBuf)> {
+        let mut jars: Vec<(String, String, PathBuf)> = Vec::new();
+        for m in &self.mods {
+            if !jars.iter().any(|(_, _, file)| *file == m.file_path) {
+                jars.push((m.id.clone(), m.version.clone(), m.file_path.clone()));
+            }
+        }
+        jars
+    }
+
+    /// The mod set as a lockfile another server can import; see
+    /// `PluginManager::export_plugins`.
+    pub async fn export_mods(&self) -> Result<Lockfile, String> {
+        Ok(Lockfile {
+            entries: export_entries(&self.server_path(), ExtensionKind::Mod, &self.installed_jars()).await?,
+            ..Default::default()
+        })
+    }
+
+    /// Installs the mods in `set` that aren't already here with the same hash. With
+    /// `prune`, mods outside the set are removed.
+    pub async fn import_mods(&mut self, set: &Lockfile, prune: bool) -> Result<Vec<InstallResult>, String> {
+        self.load_mods().await?;
+        let mut results = Vec::new();
+        let mut unchanged = Vec::new();
+        for entry in set.entries.iter().filter(|e| e.kind == ExtensionKind::Mod) {
+            if let Some(installed) = self.get_mod(&entry.name) {
+                if matches_entry(&installed.file_path, entry).await {
+                    let mut entry = entry.clone();
+                    entry.file_name = installed.file_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                    unchanged.push(entry);
+                    continue;
+                }
+            }
+            results.push(self.install_mod(&entry.source, Some(&entry.sha256)).await?);
+        }
+        record_existing(&self.server_path(), unchanged).await?;
+
+        if prune {
+            let extra: Vec<String> = self
+                .installed_jars()
+                .into_iter()
+                .map(|(id, _, _)| id)
+                .filter(|id| set.get(ExtensionKind::Mod, id).is_none())
+                .collect();
+            for id in extra {
+                self.uninstall_mod(&id).await?;
+            }
+        }
+        Ok(results)
+    }
+
+    /// Reads a staged jar's mods and checks they can run on this server. Returns the
+    /// first mod, which names the jar.
+    fn check_mod_jar(staged: &StagedJar, target: &PathBuf, loader: ModLoader) -> Result<ModInfo, String> {
+        let mods = read_mod_jar(&staged.bytes, target, Some(loader))?;
+        let primary = mods[0].clone();
+        if !loader.loads(primary.loader) {
+            return Err(format!("{} is a {:?} mod and won't load on {:?}", primary.name, primary.loader, loader));
+        }
+        if primary.environment == ModEnvironment::Client {
+            return Err(format!("{} is client-only and would crash a dedicated server", primary.name));
+        }
+        Ok(primary)
+    }
+
+    pub fn get_all_mods(&self) -> &[ModInfo] {
+        &self.mods
+    }
+
+    pub fn get_mod(&self, id: &str) -> Option<&ModInfo> {
+        self.mods.iter().find(|m| m.id == id)
+    }
+
+    pub fn get_load_errors(&self) -> &[PluginLoadError] {
+        &self.load_errors
+    }
+
+    /// Mods that only work on the client and crash a dedicated server on startup.
+    pub fn client_only_mods(&self) -> Vec<&ModInfo> {
+        self.mods.iter().filter(|m| m.environment == ModEnvironment::Client).collect()
+    }
+
+    /// Problems that would stop the server from starting: client-only mods, mods for
+    /// another loader, missing required mods and installed incompatible ones.
+    pub fn check(&self) -> Vec<ValidationIssue> {
+        let loader = ModLoader::for_server(&self.server_type);
+        let mut issues = Vec::new();
+
+        for m in &self.mods {
+            if m.environment == ModEnvironment::Client {
+                issues.push(ValidationIssue {
+                    key: m.id.clone(),
+                    message: format!("{} is client-only and will crash a dedicated server; remove it from mods", m.name),
+                    severity: IssueSeverity::Error,
+                });
+            }
+            if let Some(loader) = loader {
+                if !loader.loads(m.loader) {
+                    issues.push(ValidationIssue {
+                        key: m.id.clone(),
+                        message: format!("{} is a {:?} mod and won't load on {:?}", m.name, m.loader, loader),
+                        severity: IssueSeverity::Error,
+                    });
+                }
+            }
+
+            for dep in &m.dependencies {
+                if PLATFORM_IDS.contains(&dep.id.as_str()) {
+                    continue;
+                }
+                let installed = self.get_mod(&dep.id);
+                let range = dep.version_range.as_deref().map(|r| format!(" {}", r)).unwrap_or_default();
+                match (dep.kind, installed) {
+                    (DependencyKind::Required, None) => issues.push(ValidationIssue {
+                        key: m.id.clone(),
+                        message: format!("{} requires {}{}, which isn't installed", m.name, dep.id, range),
+                        severity: IssueSeverity::Error,
+                    }),
+                    (DependencyKind::Incompatible, Some(other)) => issues.push(ValidationIssue {
+                        key: m.id.clone(),
+                        message: format!("{} is incompatible with {}{}", m.name, other.name, range),
+                        severity: IssueSeverity::Error,
+                    }),
+                    _ => {}
+                }
+            }
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &[(&str, &[u8])] = &[
+        ("lithium-fabric-0.11.2.jar", include_bytes!("../tests/fixtures/mods/lithium-fabric-0.11.2.jar")),
+        ("sodium-fabric-0.5.8.jar", include_bytes!("../tests/fixtures/mods/sodium-fabric-0.5.8.jar")),
+        ("qsl-utils.jar", include_bytes!("../tests/fixtures/mods/qsl-utils.jar")),
+        ("jei-forge-15.3.0.jar", include_bytes!("../tests/fixtures/mods/jei-forge-15.3.0.jar")),
+        ("ftb-neoforge.jar", include_bytes!("../tests/fixtures/mods/ftb-neoforge.jar")),
+    ];
+
+    #[tokio::test]
+    async fn test_read_mod_metadata() {
+        let server_path = std::env::temp_dir().join("mcsm_test_mods");
+        let _ = std::fs::remove_dir_all(&server_path);
+        std::fs::create_dir_all(server_path.join("mods")).unwrap();
+        for (name, bytes) in FIXTURES {
+            std::fs::write(server_path.join("mods").join(name), bytes).unwrap();
+        }
+
+        let mut manager = ModManager::new(&server_path, ServerType::Fabric);
+        manager.load_mods().await.unwrap();
+        assert!(manager.get_load_errors().is_empty());
+
+        let lithium = manager.get_mod("lithium").unwrap();
+        assert_eq!(lithium.version, "0.11.2");
+        assert_eq!(lithium.authors, ["jellysquid3"]);
+        assert_eq!(lithium.environment, ModEnvironment::Both);
+        let loader_dep = lithium.dependencies.iter().find(|d| d.id == "fabricloader").unwrap();
+        assert_eq!(loader_dep.version_range.as_deref(), Some(">=0.15.0"));
+
+        assert_eq!(manager.client_only_mods().len(), 1);
+        assert_eq!(manager.client_only_mods()[0].id, "sodium");
+
+        let qsl = manager.get_mod("qsl_utils").unwrap();
+        assert_eq!(qsl.loader, ModLoader::Quilt);
+        assert_eq!(qsl.environment, ModEnvironment::Server);
+        assert_eq!(qsl.dependencies[0].kind, DependencyKind::Optional);
+
+        let jei = manager.get_mod("jei").unwrap();
+        assert_eq!(jei.loader, ModLoader::Forge);
+        assert_eq!(jei.version, "15.3.0.4");
+        assert_eq!(jei.authors, ["mezz"]);
+        let forge_dep = jei.dependencies.iter().find(|d| d.id == "forge").unwrap();
+        assert_eq!(forge_dep.kind, DependencyKind::Required);
+        assert_eq!(forge_dep.version_range.as_deref(), Some("[47,)"));
+
+        let ftb = manager.get_mod("ftblibrary").unwrap();
+        assert_eq!(ftb.loader, ModLoader::NeoForge);
+        assert_eq!(ftb.dependencies.iter().find(|d| d.id == "architectury").unwrap().kind, DependencyKind::Required);
+
+        let messages: Vec<String> = manager.check().into_iter().map(|i| i.message).collect();
+        assert!(messages.iter().any(|m| m == "Sodium is client-only and will crash a dedicated server; remove it from mods"));
+        assert!(messages.iter().any(|m| m == "Just Enough Items is a Forge mod and won't load on Fabric"));
+        assert!(messages.iter().any(|m| m == "FTB Library requires architectury [13,), which isn't installed"));
+        assert!(messages.iter().any(|m| m.starts_with("QSL Utils is a Quilt mod")));
+        assert!(!messages.iter().any(|m| m.contains("fabricloader")));
+
+        // On Quilt the Fabric mods load, and the Quilt one is fine.
+        let mut quilt = ModManager::new(&server_path, ServerType::Quilt);
+        quilt.load_mods().await.unwrap();
+        assert!(!quilt.check().iter().any(|i| i.message.contains("Lithium") || i.message.contains("QSL")));
+    }
+}
@@ -0,0 +1,404 @@
+use std::io::{Read, Write};
+use serde::{Serialize, Deserialize};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+const TAG_END: u8 = 0;
+const TAG_BYTE: u8 = 1;
+const TAG_SHORT: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_LONG: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_DOUBLE: u8 = 6;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_STRING: u8 = 8;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+const TAG_INT_ARRAY: u8 = 11;
+const TAG_LONG_ARRAY: u8 = 12;
+
+/// A single NBT value. Compounds keep their entries in file order so a read/write
+/// cycle doesn't reshuffle the data Minecraft wrote.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum NbtTag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List { element_type: u8, items: Vec<NbtTag> },
+    Compound(Vec<(String, NbtTag)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl NbtTag {
+    pub fn type_id(&self) -> u8 {
+        match self {
+            NbtTag::Byte(_) => TAG_BYTE,
+            NbtTag::Short(_) => TAG_SHORT,
+            NbtTag::Int(_) => TAG_INT,
+            NbtTag::Long(_) => TAG_LONG,
+            NbtTag::Float(_) => TAG_FLOAT,
+            NbtTag::Double(_) => TAG_DOUBLE,
+            NbtTag::ByteArray(_) => TAG_BYTE_ARRAY,
+            NbtTag::String(_) => TAG_STRING,
+            NbtTag::List { .. } => TAG_LIST,
+            NbtTag::Compound(_) => TAG_COMPOUND,
+            NbtTag::IntArray(_) => TAG_INT_ARRAY,
+            NbtTag::LongArray(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&NbtTag> {
+        match self {
+            NbtTag::Compound(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut NbtTag> {
+        match self {
+            NbtTag::Compound(entries) => entries.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Sets `key` on a compound, replacing an existing entry in place.
+    pub fn insert(&mut self, key: &str, value: NbtTag) {
+        if let NbtTag::Compound(entries) = self {
+            match entries.iter_mut().find(|(k, _)| k == key) {
+                Some(entry) => entry.1 = value,
+                None => entries.push((key.to_string(), value)),
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<NbtTag> {
+        match self {
+            NbtTag::Compound(entries) => {
+                let index = entries.iter().position(|(k, _)| k == key)?;
+                Some(entries.remove(index).1)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            NbtTag::Byte(v) => Some(*v as i64),
+            NbtTag::Short(v) => Some(*v as i64),
+            NbtTag::Int(v) => Some(*v as i64),
+            NbtTag::Long(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            NbtTag::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// Root of an NBT file: an unnamed-or-named compound.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NbtFile {
+    pub name: String,
+    pub root: NbtTag,
+    pub compressed: bool,
+}
+
+impl NbtFile {
+    /// Parses a (possibly gzip-compressed) NBT file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let compressed = bytes.starts_with(&[0x1f, 0x8b]);
+        let data = if compressed {
+            let mut decoded = Vec::new();
+            GzDecoder::new(bytes)
+                .read_to_end(&mut decoded)
+                .map_err(|e| format!("Failed to decompress NBT data: {}", e))?;
+            decoded
+        } else {
+            bytes.to_vec()
+        };
+
+        let mut reader = NbtReader { data: &data, pos: 0 };
+        let tag_type = reader.read_u8()?;
+        if tag_type != TAG_COMPOUND {
+            return Err(format!("Expected root compound, found tag type {}", tag_type));
+        }
+        let name = reader.read_string()?;
+        let root = reader.read_payload(TAG_COMPOUND, 0)?;
+
+        Ok(NbtFile { name, root, compressed })
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut data = Vec::new();
+        data.push(TAG_COMPOUND);
+        write_string(&mut data, &self.name)?;
+        write_payload(&mut data, &self.root)?;
+
+        if !self.compressed {
+            return Ok(data);
+        }
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&data).map_err(|e| e.to_string())?;
+        encoder.finish().map_err(|e| e.to_string())
+    }
+}
+
+const MAX_DEPTH: usize = 512;
+
+struct NbtReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> NbtReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.pos + len > self.data.len() {
+            return Err("Unexpected end of NBT data".to_string());
+        }
+        let slice = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_i16(&mut self) -> Result<i16, String> {
+        Ok(i16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn read_i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_i64(&mut self) -> Result<i64, String> {
+        Ok(i64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn read_len(&mut self) -> Result<usize, String> {
+        let len = self.read_i32()?;
+        if len < 0 {
+            return Err(format!("Negative NBT array length: {}", len));
+        }
+        Ok(len as usize)
+    }
+
+    fn read_string(&mut self) -> Result<String, String> {
+        let len = u16::from_be_bytes(self.take(2)?.try_into().unwrap()) as usize;
+        decode_modified_utf8(self.take(len)?)
+    }
+
+    fn read_payload(&mut self, tag_type: u8, depth: usize) -> Result<NbtTag, String> {
+        if depth > MAX_DEPTH {
+            return Err("NBT data nested too deeply".to_string());
+        }
+
+        Ok(match tag_type {
+            TAG_BYTE => NbtTag::Byte(self.read_u8()? as i8),
+            TAG_SHORT => NbtTag::Short(self.read_i16()?),
+            TAG_INT => NbtTag::Int(self.read_i32()?),
+            TAG_LONG => NbtTag::Long(self.read_i64()?),
+            TAG_FLOAT => NbtTag::Float(f32::from_bits(self.read_i32()? as u32)),
+            TAG_DOUBLE => NbtTag::Double(f64::from_bits(self.read_i64()? as u64)),
+            TAG_BYTE_ARRAY => {
+                let len = self.read_len()?;
+                NbtTag::ByteArray(self.take(len)?.iter().map(|b| *b as i8).collect())
+            }
+            TAG_STRING => NbtTag::String(self.read_string()?),
+            TAG_LIST => {
+                let element_type = self.read_u8()?;
+                let len = self.read_len()?;
+                let mut items = Vec::new();
+                for _ in 0..len {
+                    items.push(self.read_payload(element_type, depth + 1)?);
+                }
+                NbtTag::List { element_type, items }
+            }
+            TAG_COMPOUND => {
+                let mut entries = Vec::new();
+                loop {
+                    let entry_type = self.read_u8()?;
+                    if entry_type == TAG_END {
+                        break;
+                    }
+                    let name = self.read_string()?;
+                    entries.push((name, self.read_payload(entry_type, depth + 1)?));
+                }
+                NbtTag::Compound(entries)
+            }
+            TAG_INT_ARRAY => {
+                let len = self.read_len()?;
+                let mut values = Vec::new();
+                for _ in 0..len {
+                    values.push(self.read_i32()?);
+                }
+                NbtTag::IntArray(values)
+            }
+            TAG_LONG_ARRAY => {
+                let len = self.read_len()?;
+                let mut values = Vec::new();
+                for _ in 0..len {
+                    values.push(self.read_i64()?);
+                }
+                NbtTag::LongArray(values)
+            }
+            other => return Err(format!("Unknown NBT tag type: {}", other)),
+        })
+    }
+}
+
+fn write_string(out: &mut Vec<u8>, value: &str) -> Result<(), String> {
+    let encoded = encode_modified_utf8(value);
+    let len = u16::try_from(encoded.len())
+        .map_err(|_| "NBT string is longer than 65535 bytes".to_string())?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(&encoded);
+    Ok(())
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) -> Result<(), String> {
+    let len = i32::try_from(len).map_err(|_| "NBT array is too long".to_string())?;
+    out.extend_from_slice(&len.to_be_bytes());
+    Ok(())
+}
+
+fn write_payload(out: &mut Vec<u8>, tag: &NbtTag) -> Result<(), String> {
+    match tag {
+        NbtTag::Byte(v) => out.push(*v as u8),
+        NbtTag::Short(v) => out.extend_from_slice(&v.to_be_bytes()),
+        NbtTag::Int(v) => out.extend_from_slice(&v.to_be_bytes()),
+        NbtTag::Long(v) => out.extend_from_slice(&v.to_be_bytes()),
+        NbtTag::Float(v) => out.extend_from_slice(&v.to_be_bytes()),
+        NbtTag::Double(v) => out.extend_from_slice(&v.to_be_bytes()),
+        NbtTag::ByteArray(values) => {
+            write_len(out, values.len())?;
+            out.extend(values.iter().map(|b| *b as u8));
+        }
+        NbtTag::String(s) => write_string(out, s)?,
+        NbtTag::List { element_type, items } => {
+            if let Some(item) = items.iter().find(|item| item.type_id() != *element_type) {
+                return Err(format!(
+                    "NBT list of type {} contains a tag of type {}",
+                    element_type,
+                    item.type_id()
+                ));
+            }
+            out.push(*element_type);
+            write_len(out, items.len())?;
+            for item in items {
+                write_payload(out, item)?;
+            }
+        }
+        NbtTag::Compound(entries) => {
+            for (name, value) in entries {
+                out.push(value.type_id());
+                write_string(out, name)?;
+                write_payload(out, value)?;
+            }
+            out.push(TAG_END);
+        }
+        NbtTag::IntArray(values) => {
+            write_len(out, values.len())?;
+            for v in values {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+        NbtTag::LongArray(values) => {
+            write_len(out, values.len())?;
+            for v in values {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Java's "modified UTF-8": NUL is two bytes and supplementary characters are
+/// written as surrogate pairs.
+fn encode_modified_utf8(value: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(value.len());
+    for unit in value.encode_utf16() {
+        match unit {
+            0x0001..=0x007f => out.push(unit as u8),
+            0x0000 | 0x0080..=0x07ff => {
+                out.push(0xc0 | (unit >> 6) as u8);
+                out.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                out.push(0xe0 | (unit >> 12) as u8);
+                out.push(0x80 | ((unit >> 6) & 0x3f) as u8);
+                out.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+    out
+}
+
+fn decode_modified_utf8(bytes: &[u8]) -> Result<String, String> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let (unit, len) = if b & 0x80 == 0 {
+            (b as u16, 1)
+        } else if b & 0xe0 == 0xc0 && i + 1 < bytes.len() {
+            ((((b & 0x1f) as u16) << 6) | (bytes[i + 1] & 0x3f) as u16, 2)
+        } else if b & 0xf0 == 0xe0 && i + 2 < bytes.len() {
+            (
+                (((b & 0x0f) as u16) << 12)
+                    | (((bytes[i + 1] & 0x3f) as u16) << 6)
+                    | (bytes[i + 2] & 0x3f) as u16,
+                3,
+            )
+        } else {
+            return Err("Invalid modified UTF-8 in NBT string".to_string());
+        };
+        units.push(unit);
+        i += len;
+    }
+    String::from_utf16(&units).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nbt_round_trip() {
+        let file = NbtFile {
+            name: String::new(),
+            root: NbtTag::Compound(vec![
+                ("DataVersion".to_string(), NbtTag::Int(3700)),
+                ("Name".to_string(), NbtTag::String("Stéve\u{0}🙂".to_string())),
+                ("Inventory".to_string(), NbtTag::List { element_type: TAG_COMPOUND, items: vec![
+                    NbtTag::Compound(vec![
+                        ("Slot".to_string(), NbtTag::Byte(0)),
+                        ("id".to_string(), NbtTag::String("minecraft:stone".to_string())),
+                    ]),
+                ] }),
+                ("Pos".to_string(), NbtTag::List { element_type: TAG_DOUBLE, items: vec![NbtTag::Double(1.5)] }),
+                ("UUID".to_string(), NbtTag::IntArray(vec![1, -2, 3, -4])),
+            ]),
+            compressed: true,
+        };
+
+        let bytes = file.to_bytes().unwrap();
+        let parsed = NbtFile::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, file);
+        assert_eq!(parsed.root.get("DataVersion").and_then(|t| t.as_i64()), Some(3700));
+    }
+}
@@ -2,6 +2,7 @@ use std::path::PathBuf;
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use tokio::fs;
+use crate::sessions::SessionStore;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Player {
@@ -21,6 +22,9 @@ pub struct PlayerList {
     whitelist: Vec<String>,
     blacklist: Vec<String>,
     ops: Vec<String>,
+    names: HashMap<String, String>,
+    op_entries: HashMap<String, OpEntry>,
+    ban_entries: HashMap<String, BannedPlayerEntry>,
 }
 
 impl PlayerList {
@@ -30,9 +34,23 @@ impl PlayerList {
             whitelist: Vec::new(),
             blacklist: Vec::new(),
             ops: Vec::new(),
+            names: HashMap::new(),
+            op_entries: HashMap::new(),
+            ban_entries: HashMap::new(),
         }
     }
 
+    /// Loads the whitelist, ops and bans of `server_path` and fills in each
+    /// player's activity from its session log.
+    pub async fn load(server_path: &PathBuf) -> Result<Self, String> {
+        let mut players = PlayerList::new();
+        players.load_from_files(server_path).await?;
+        let mut sessions = SessionStore::new(server_path);
+        sessions.load().await?;
+        sessions.apply_to_players(&mut players, chrono::Utc::now());
+        Ok(players)
+    }
+
     pub async fn load_from_files(&mut self, server_path: &PathBuf) -> Result<(), String> {
         let whitelist_path = server_path.join("whitelist.json");
         let ops_path = server_path.join("ops.json");
@@ -44,7 +62,10 @@ impl PlayerList {
                 .map_err(|e| e.to_string())?;
             let whitelist: Vec<WhitelistEntry> = serde_json::from_str(&whitelist_data)
                 .map_err(|e| e.to_string())?;
-            self.whitelist = whitelist.into_iter().map(|p| p.uuid).collect();
+            self.whitelist = whitelist.into_iter().map(|p| {
+                self.names.insert(p.uuid.clone(), p.name);
+                p.uuid
+            }).collect();
         }
 
         if ops_path.exists() {
@@ -53,7 +74,11 @@ impl PlayerList {
                 .map_err(|e| e.to_string())?;
             let ops: Vec<OpEntry> = serde_json::from_str(&ops_data)
                 .map_err(|e| e.to_string())?;
-            self.ops = ops.into_iter().map(|p| p.uuid).collect();
+            self.ops = ops.into_iter().map(|p| {
+                self.names.insert(p.uuid.clone(), p.name.clone());
+                self.op_entries.insert(p.uuid.clone(), p.clone());
+                p.uuid
+            }).collect();
         }
 
         if banned_players_path.exists() {
@@ -62,12 +87,74 @@ impl PlayerList {
                 .map_err(|e| e.to_string())?;
             let banned: Vec<BannedPlayerEntry> = serde_json::from_str(&banned_data)
                 .map_err(|e| e.to_string())?;
-            self.blacklist = banned.into_iter().map(|p| p.uuid).collect();
+            self.blacklist = banned.into_iter().map(|p| {
+                self.names.insert(p.uuid.clone(), p.name.clone());
+                self.ban_entries.insert(p.uuid.clone(), p.clone());
+                p.uuid
+            }).collect();
+        }
+
+        Ok(())
+    }
+
+    /// Writes the whitelist, ops and bans back in the vanilla JSON formats.
+    pub async fn save_to_files(&self, server_path: &PathBuf) -> Result<(), String> {
+        let whitelist: Vec<WhitelistEntry> = self.whitelist
+            .iter()
+            .map(|uuid| WhitelistEntry {
+                uuid: uuid.clone(),
+                name: self.player_name(uuid).unwrap_or_default().to_string(),
+            })
+            .collect();
+
+        let ops: Vec<OpEntry> = self.ops
+            .iter()
+            .map(|uuid| self.op_entries.get(uuid).cloned().unwrap_or_else(|| OpEntry {
+                uuid: uuid.clone(),
+                name: self.player_name(uuid).unwrap_or_default().to_string(),
+                level: 4,
+                bypassesPlayerLimit: false,
+            }))
+            .collect();
+
+        let banned: Vec<BannedPlayerEntry> = self.blacklist
+            .iter()
+            .map(|uuid| self.ban_entries.get(uuid).cloned().unwrap_or_else(|| BannedPlayerEntry {
+                uuid: uuid.clone(),
+                name: self.player_name(uuid).unwrap_or_default().to_string(),
+                created: chrono::Local::now().format("%Y-%m-%d %H:%M:%S %z").to_string(),
+                source: "MCSM".to_string(),
+                expires: Some("forever".to_string()),
+                reason: "Banned by an operator.".to_string(),
+            }))
+            .collect();
+
+        let files = [
+            ("whitelist.json", serde_json::to_string_pretty(&whitelist)),
+            ("ops.json", serde_json::to_string_pretty(&ops)),
+            ("banned-players.json", serde_json::to_string_pretty(&banned)),
+        ];
+        for (file, json) in files {
+            let json = json.map_err(|e| e.to_string())?;
+            fs::write(server_path.join(file), json)
+                .await
+                .map_err(|e| e.to_string())?;
         }
 
         Ok(())
     }
 
+    pub fn player_name(&self, uuid: &str) -> Option<&str> {
+        self.names
+            .get(uuid)
+            .map(|n| n.as_str())
+            .or_else(|| self.players.get(uuid).map(|p| p.name.as_str()))
+    }
+
+    pub fn set_player_name(&mut self, uuid: &str, name: &str) {
+        self.names.insert(uuid.to_string(), name.to_string());
+    }
+
     pub fn add_player(&mut self, player: Player) {
         self.players.insert(player.uuid.clone(), player);
     }
@@ -84,6 +171,10 @@ impl PlayerList {
         self.players.values().collect()
     }
 
+    pub fn get_all_players_mut(&mut self) -> Vec<&mut Player> {
+        self.players.values_mut().collect()
+    }
+
     pub fn get_online_players(&self) -> Vec<&Player> {
         self.players.values().filter(|p| p.is_online).collect()
     }
@@ -129,6 +220,56 @@ impl PlayerList {
     pub fn is_op(&self, uuid: &str) -> bool {
         self.ops.contains(&uuid.to_string())
     }
+
+    pub fn get_whitelist(&self) -> &[String] {
+        &self.whitelist
+    }
+
+    pub fn get_banned(&self) -> &[String] {
+        &self.blacklist
+    }
+
+    pub fn get_ops(&self) -> &[String] {
+        &self.ops
+    }
+
+    pub fn op_level(&self, uuid: &str) -> Option<u32> {
+        if !self.is_op(uuid) {
+            return None;
+        }
+        Some(self.op_entries.get(uuid).map(|e| e.level).unwrap_or(4))
+    }
+
+    pub fn ban_reason(&self, uuid: &str) -> Option<&str> {
+        self.ban_entries.get(uuid).map(|e| e.reason.as_str())
+    }
+
+    pub fn set_ban_reason(&mut self, uuid: &str, reason: &str, source: &str) {
+        let name = self.player_name(uuid).unwrap_or_default().to_string();
+        let entry = self.ban_entries.entry(uuid.to_string()).or_insert_with(|| BannedPlayerEntry {
+            uuid: uuid.to_string(),
+            name,
+            created: chrono::Local::now().format("%Y-%m-%d %H:%M:%S %z").to_string(),
+            source: String::new(),
+            expires: Some("forever".to_string()),
+            reason: String::new(),
+        });
+        entry.reason = reason.to_string();
+        entry.source = source.to_string();
+    }
+
+    pub fn set_op_level(&mut self, uuid: &str, level: u32) {
+        let name = self.player_name(uuid).unwrap_or_default().to_string();
+        self.op_entries
+            .entry(uuid.to_string())
+            .or_insert_with(|| OpEntry {
+                uuid: uuid.to_string(),
+                name,
+                level,
+                bypassesPlayerLimit: false,
+            })
+            .level = level;
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -137,7 +278,7 @@ struct WhitelistEntry {
     name: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct OpEntry {
     uuid: String,
     name: String,
@@ -145,7 +286,7 @@ struct OpEntry {
     bypassesPlayerLimit: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct BannedPlayerEntry {
     uuid: String,
     name: String,
@@ -0,0 +1,312 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use crate::plugins::Plugin;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissingDependency {
+    pub plugin: String,
+    pub dependency: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DependencyCycle {
+    pub plugins: Vec<String>,
+    /// Contains a loop of `depend` edges alone. The server breaks soft cycles and loads
+    /// the plugins anyway, but plugins on a hard loop don't load.
+    pub hard: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicatePlugin {
+    pub name: String,
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DependencyReport {
+    pub missing: Vec<MissingDependency>,
+    pub cycles: Vec<DependencyCycle>,
+    pub duplicates: Vec<DuplicatePlugin>,
+    /// Plugins that won't load because a hard dependency is missing, doesn't load
+    /// itself, or is part of a hard cycle.
+    pub unloadable: Vec<String>,
+    /// The order the server will load the remaining plugins in.
+    pub load_order: Vec<String>,
+}
+
+impl DependencyReport {
+    pub fn is_healthy(&self) -> bool {
+        self.missing.is_empty() && self.unloadable.is_empty() && self.duplicates.is_empty() && self.cycles.iter().all(|c| !c.hard)
+    }
+}
+
+/// `before -> after` edges between installed plugins, split into hard (`depend`) and
+/// soft (`softdepend` and `loadbefore`) ones.
+struct Graph<'a> {
+    hard: BTreeMap<&'a str, BTreeSet<&'a str>>,
+    soft: BTreeMap<&'a str, BTreeSet<&'a str>>,
+}
+
+impl<'a> Graph<'a> {
+    fn successors(&self, node: &str) -> BTreeSet<&'a str> {
+        let mut all = self.hard.get(node).cloned().unwrap_or_default();
+        all.extend(self.soft.get(node).into_iter().flatten().copied());
+        all
+    }
+
+    /// Strongly connected components that form cycles, via Tarjan's algorithm.
+    fn cycles(&self, nodes: &BTreeSet<&'a str>) -> Vec<Vec<&'a str>> {
+        struct State<'a> {
+            index: HashMap<&'a str, usize>,
+            low: HashMap<&'a str, usize>,
+            stack: Vec<&'a str>,
+            on_stack: BTreeSet<&'a str>,
+            components: Vec<Vec<&'a str>>,
+        }
+
+        fn visit<'a>(graph: &Graph<'a>, nodes: &BTreeSet<&'a str>, node: &'a str, state: &mut State<'a>) {
+            let index = state.index.len();
+            state.index.insert(node, index);
+            state.low.insert(node, index);
+            state.stack.push(node);
+            state.on_stack.insert(node);
+
+            for next in graph.successors(node).into_iter().filter(|n| nodes.contains(n)) {
+                if !state.index.contains_key(next) {
+                    visit(graph, nodes, next, state);
+                    let low = state.low[node].min(state.low[next]);
+                    state.low.insert(node, low);
+                } else if state.on_stack.contains(next) {
+                    let low = state.low[node].min(state.index[next]);
+                    state.low.insert(node, low);
+                }
+            }
+
+            if state.low[node] == state.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = state.stack.pop() {
+                    state.on_stack.remove(member);
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                let self_loop = graph.successors(node).contains(node);
+                if component.len() > 1 || self_loop {
+                    component.sort();
+                    state.components.push(component);
+                }
+            }
+        }
+
+        let mut state = State {
+            index: HashMap::new(),
+            low: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            components: Vec::new(),
+        };
+        for node in nodes {
+            if !state.index.contains_key(node) {
+                visit(self, nodes, node, &mut state);
+            }
+        }
+        state.components
+    }
+}
+
+/// Builds the dependency graph of `plugins` and works out what will load and in which
+/// order. When several jars declare the same name, the first is the one considered.
+pub fn resolve_dependencies(plugins: &[&Plugin]) -> DependencyReport {
+    let mut report = DependencyReport::default();
+
+    let mut by_name: BTreeMap<&str, Vec<&Plugin>> = BTreeMap::new();
+    for plugin in plugins {
+        by_name.entry(plugin.name.as_str()).or_default().push(plugin);
+    }
+    for (name, jars) in &by_name {
+        if jars.len() > 1 {
+            report.duplicates.push(DuplicatePlugin {
+                name: name.to_string(),
+                files: jars.iter().map(|p| p.file_path.clone()).collect(),
+            });
+        }
+    }
+    let installed: BTreeMap<&str, &Plugin> = by_name.iter().map(|(name, jars)| (*name, jars[0])).collect();
+
+    let mut graph = Graph { hard: BTreeMap::new(), soft: BTreeMap::new() };
+    for (name, plugin) in &installed {
+        for dependency in &plugin.dependencies {
+            if !installed.contains_key(dependency.as_str()) {
+                report.missing.push(MissingDependency {
+                    plugin: name.to_string(),
+                    dependency: dependency.clone(),
+                });
+            } else if !plugin.load_before.contains(dependency) {
+                // A dependency the plugin loads before (Paper's `load: AFTER`) is still
+                // required, but only its `load_before` edge orders the two.
+                graph.hard.entry(dependency.as_str()).or_default().insert(*name);
+            }
+        }
+        for soft in &plugin.soft_dependencies {
+            if installed.contains_key(soft.as_str()) {
+                graph.soft.entry(soft.as_str()).or_default().insert(*name);
+            }
+        }
+        for later in &plugin.load_before {
+            if installed.contains_key(later.as_str()) {
+                graph.soft.entry(*name).or_default().insert(later.as_str());
+            }
+        }
+    }
+
+    let nodes: BTreeSet<&str> = installed.keys().copied().collect();
+    let mut unloadable: BTreeSet<&str> = nodes
+        .iter()
+        .filter(|n| installed[*n].dependencies.iter().any(|d| !installed.contains_key(d.as_str())))
+        .copied()
+        .collect();
+    // Soft edges inside a cycle are dropped when ordering, which is how the server
+    // breaks cycles. Plugins on a cycle of hard edges alone can't load at all.
+    let hard_graph = Graph { hard: graph.hard.clone(), soft: BTreeMap::new() };
+    let mut cycle_members: BTreeSet<&str> = BTreeSet::new();
+    for cycle in graph.cycles(&nodes) {
+        let members: BTreeSet<&str> = cycle.iter().copied().collect();
+        let hard_cycles = hard_graph.cycles(&members);
+        for hard_cycle in &hard_cycles {
+            unloadable.extend(hard_cycle);
+        }
+        cycle_members.extend(&members);
+        report.cycles.push(DependencyCycle {
+            plugins: cycle.iter().map(|s| s.to_string()).collect(),
+            hard: !hard_cycles.is_empty(),
+        });
+    }
+
+    // Anything that hard-depends on an unloadable plugin doesn't load either.
+    loop {
+        let newly: Vec<&str> = nodes
+            .iter()
+            .filter(|n| !unloadable.contains(*n))
+            .filter(|n| installed[*n].dependencies.iter().any(|d| unloadable.contains(d.as_str())))
+            .copied()
+            .collect();
+        if newly.is_empty() {
+            break;
+        }
+        unloadable.extend(newly);
+    }
+    report.unloadable = unloadable.iter().map(|s| s.to_string()).collect();
+
+    // Kahn's algorithm, alphabetical among ready plugins.
+    let loadable: BTreeSet<&str> = nodes.difference(&unloadable).copied().collect();
+    let edge_counts = |from: &str, to: &str| -> bool {
+        loadable.contains(from)
+            && loadable.contains(to)
+            && (graph.hard.get(from).is_some_and(|s| s.contains(to))
+                || (graph.soft.get(from).is_some_and(|s| s.contains(to))
+                    && !(cycle_members.contains(from) && cycle_members.contains(to))))
+    };
+    let mut incoming: BTreeMap<&str, usize> = loadable
+        .iter()
+        .map(|n| (*n, loadable.iter().filter(|from| edge_counts(from, n)).count()))
+        .collect();
+    let mut ready: BTreeSet<&str> = incoming.iter().filter(|(_, c)| **c == 0).map(|(n, _)| *n).collect();
+    while let Some(next) = ready.pop_first() {
+        report.load_order.push(next.to_string());
+        incoming.remove(next);
+        for successor in graph.successors(next) {
+            if edge_counts(next, successor) {
+                if let Some(count) = incoming.get_mut(successor) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.insert(successor);
+                    }
+                }
+            }
+        }
+    }
+    report
+}
+
+/// Plugins among `plugins` that hard- and soft-depend on `name`.
+pub fn dependents_of<'a>(plugins: &[&'a Plugin], name: &str) -> (Vec<&'a Plugin>, Vec<&'a Plugin>) {
+    let hard = plugins
+        .iter()
+        .filter(|p| p.name != name && p.dependencies.iter().any(|d| d == name))
+        .copied()
+        .collect();
+    let soft = plugins
+        .iter()
+        .filter(|p| p.name != name && p.soft_dependencies.iter().any(|d| d == name))
+        .copied()
+        .collect();
+    (hard, soft)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin(name: &str, depend: &[&str], softdepend: &[&str], loadbefore: &[&str]) -> Plugin {
+        let list = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        Plugin {
+            name: name.to_string(),
+            version: "1.0".to_string(),
+            author: "Unknown".to_string(),
+            authors: Vec::new(),
+            description: String::new(),
+            dependencies: list(depend),
+            soft_dependencies: list(softdepend),
+            main: format!("dev.example.{}", name),
+            api_version: Some("1.20".to_string()),
+            load_before: list(loadbefore),
+            website: None,
+            commands: Vec::new(),
+            permissions: Vec::new(),
+            paper_plugin: false,
+            file_path: PathBuf::from(format!("plugins/{}.jar", name)),
+            is_enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_resolve_dependencies() {
+        let plugins = vec![
+            plugin("Shops", &["Vault"], &["WorldGuard"], &[]),
+            plugin("Vault", &[], &[], &[]),
+            plugin("WorldGuard", &["WorldEdit"], &[], &[]),
+            plugin("WorldEdit", &[], &[], &[]),
+            plugin("Chat", &[], &[], &["Vault"]),
+            plugin("Kits", &["Economy"], &[], &[]),
+            plugin("KitsAddon", &["Kits"], &[], &[]),
+            plugin("Ping", &["Pong"], &[], &[]),
+            plugin("Pong", &["Ping"], &[], &[]),
+            plugin("Alpha", &[], &["Beta"], &[]),
+            plugin("Beta", &[], &["Alpha"], &[]),
+            // Paper plugins requiring each other, Maps loading after Markers.
+            plugin("Markers", &["Maps"], &[], &["Maps"]),
+            plugin("Maps", &["Markers"], &[], &[]),
+        ];
+        let mut duplicate = plugin("Vault", &[], &[], &[]);
+        duplicate.file_path = PathBuf::from("plugins/Vault-old.jar");
+        let mut refs: Vec<&Plugin> = plugins.iter().collect();
+        refs.push(&duplicate);
+
+        let report = resolve_dependencies(&refs);
+        assert_eq!(report.missing, [MissingDependency { plugin: "Kits".to_string(), dependency: "Economy".to_string() }]);
+        assert_eq!(report.duplicates.len(), 1);
+        assert_eq!(report.duplicates[0].files, [PathBuf::from("plugins/Vault.jar"), PathBuf::from("plugins/Vault-old.jar")]);
+        assert_eq!(report.cycles.len(), 2);
+        assert!(report.cycles.contains(&DependencyCycle { plugins: vec!["Alpha".to_string(), "Beta".to_string()], hard: false }));
+        assert!(report.cycles.contains(&DependencyCycle { plugins: vec!["Ping".to_string(), "Pong".to_string()], hard: true }));
+        assert_eq!(report.unloadable, ["Kits", "KitsAddon", "Ping", "Pong"]);
+        assert_eq!(report.load_order, ["Alpha", "Beta", "Chat", "Markers", "Maps", "Vault", "WorldEdit", "WorldGuard", "Shops"]);
+        assert!(!report.is_healthy());
+
+        let (hard, soft) = dependents_of(&refs, "WorldGuard");
+        assert!(hard.is_empty());
+        assert_eq!(soft[0].name, "Shops");
+    }
+}
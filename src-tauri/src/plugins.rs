@@ -1,37 +1,261 @@
-use std::path::PathBuf;
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
 use serde::{Serialize, Deserialize};
+use serde_yaml::Value;
+use crate::catalog::{AvailableUpdate, Catalog};
+use crate::config_history::{is_plugin_config, is_plugin_data_dir, relative_path_string, MAX_TRACKED_FILE_SIZE};
+use crate::jar_install::{export_entries, matches_entry, record_existing, record_install, stage_jar, InstallResult, StagedJar};
+use crate::jar_scan::JarScanner;
+use crate::lockfile::{ExtensionKind, ExtensionSource, Lockfile};
+use crate::plugin_graph::{dependents_of, resolve_dependencies, DependencyReport};
+use crate::version::MinecraftVersion;
+use crate::ServerType;
 use tokio::fs;
 use std::collections::HashMap;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginCommand {
+    pub name: String,
+    pub description: Option<String>,
+    pub usage: Option<String>,
+    pub aliases: Vec<String>,
+    pub permission: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginPermission {
+    pub name: String,
+    pub description: Option<String>,
+    /// `true`, `false`, `op` or `not op`.
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Plugin {
     pub name: String,
     pub version: String,
+    /// All authors joined with ", ", or "Unknown".
     pub author: String,
+    pub authors: Vec<String>,
     pub description: String,
     pub dependencies: Vec<String>,
     pub soft_dependencies: Vec<String>,
     pub main: String,
-    pub api_version: String,
+    /// Unset for legacy plugins written before 1.13.
+    pub api_version: Option<String>,
     pub load_before: Vec<String>,
     pub website: Option<String>,
+    pub commands: Vec<PluginCommand>,
+    pub permissions: Vec<PluginPermission>,
+    /// Described by `paper-plugin.yml` rather than `plugin.yml`.
+    pub paper_plugin: bool,
+    /// The jar this plugin was read from.
+    pub file_path: PathBuf,
     pub is_enabled: bool,
 }
 
+/// A jar in the plugins folder that couldn't be read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginLoadError {
+    pub file: PathBuf,
+    pub message: String,
+}
+
+/// A config file in a plugin's data folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginConfigFile {
+    /// Relative to the data folder, with `/` separators.
+    pub path: String,
+    pub size: u64,
+}
+
+/// The result of a plugin action that went ahead, with anything the user should know.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginActionOutcome {
+    pub warnings: Vec<String>,
+    /// The change only takes effect once the server restarts.
+    pub restart_required: bool,
+}
+
+/// A YAML scalar as a string; plugin.yml often has unquoted numbers like `version: 1.0`.
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// A list of strings, also accepting a single string.
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Sequence(items)) => items.iter().filter_map(scalar).collect(),
+        Some(value) => scalar(value).into_iter().collect(),
+        None => Vec::new(),
+    }
+}
+
+fn string_field(yaml: &Value, key: &str) -> Option<String> {
+    yaml.get(key).and_then(scalar)
+}
+
+fn parse_commands(yaml: &Value) -> Vec<PluginCommand> {
+    let Some(Value::Mapping(commands)) = yaml.get("commands") else {
+        return Vec::new();
+    };
+    commands
+        .iter()
+        .filter_map(|(name, command)| {
+            Some(PluginCommand {
+                name: scalar(name)?,
+                description: string_field(command, "description"),
+                usage: string_field(command, "usage"),
+                aliases: string_list(command.get("aliases")),
+                permission: string_field(command, "permission"),
+            })
+        })
+        .collect()
+}
+
+fn parse_permissions(yaml: &Value) -> Vec<PluginPermission> {
+    let Some(Value::Mapping(permissions)) = yaml.get("permissions") else {
+        return Vec::new();
+    };
+    permissions
+        .iter()
+        .filter_map(|(name, permission)| {
+            Some(PluginPermission {
+                name: scalar(name)?,
+                description: string_field(permission, "description"),
+                default: string_field(permission, "default"),
+            })
+        })
+        .collect()
+}
+
+/// Dependencies from `paper-plugin.yml`: `dependencies.server.<name>` with `required`
+/// (default true) and `load`: `BEFORE` loads the dependency first, `AFTER` loads it
+/// after this plugin and the default `OMIT` leaves the order alone. A required
+/// dependency loaded `AFTER` is kept in both `dependencies` and `load_before`.
+fn parse_paper_dependencies(yaml: &Value, plugin: &mut Plugin) {
+    let Some(Value::Mapping(server)) = yaml.get("dependencies").and_then(|d| d.get("server")) else {
+        return;
+    };
+    for (name, dependency) in server {
+        let Some(name) = scalar(name) else {
+            continue;
+        };
+        let required = dependency.get("required").and_then(Value::as_bool).unwrap_or(true);
+        let load = dependency.get("load").and_then(Value::as_str).unwrap_or("OMIT");
+        if load.eq_ignore_ascii_case("AFTER") {
+            plugin.load_before.push(name.clone());
+        }
+        if required {
+            plugin.dependencies.push(name);
+        } else if load.eq_ignore_ascii_case("BEFORE") {
+            plugin.soft_dependencies.push(name);
+        }
+    }
+}
+
+/// Parses the text of a `plugin.yml` or, with `paper`, a `paper-plugin.yml`.
+pub fn parse_plugin_yml(text: &str, paper: bool) -> Result<Plugin, String> {
+    let yaml: Value = serde_yaml::from_str(text).map_err(|e| e.to_string())?;
+    if !yaml.is_mapping() {
+        return Err("expected a YAML mapping".to_string());
+    }
+    let name = string_field(&yaml, "name").ok_or("missing `name`")?;
+
+    let mut authors = string_list(yaml.get("authors"));
+    if let Some(author) = string_field(&yaml, "author") {
+        if !authors.contains(&author) {
+            authors.insert(0, author);
+        }
+    }
+
+    let mut plugin = Plugin {
+        name,
+        version: string_field(&yaml, "version").ok_or("missing `version`")?,
+        author: if authors.is_empty() { "Unknown".to_string() } else { authors.join(", ") },
+        authors,
+        description: string_field(&yaml, "description").unwrap_or_default(),
+        dependencies: string_list(yaml.get("depend")),
+        soft_dependencies: string_list(yaml.get("softdepend")),
+        main: string_field(&yaml, "main").ok_or("missing `main`")?,
+        api_version: string_field(&yaml, "api-version"),
+        load_before: string_list(yaml.get("loadbefore")),
+        website: string_field(&yaml, "website"),
+        commands: parse_commands(&yaml),
+        permissions: parse_permissions(&yaml),
+        paper_plugin: paper,
+        file_path: PathBuf::new(),
+        is_enabled: true,
+    };
+    if paper {
+        parse_paper_dependencies(&yaml, &mut plugin);
+    }
+    Ok(plugin)
+}
+
+/// Reads a plugin's metadata from its jar, preferring `paper-plugin.yml`, which Paper
+/// loads instead of `plugin.yml` when both are present.
+pub fn read_plugin_jar(bytes: &[u8], file_name: &str) -> Result<Plugin, String> {
+    read_plugin_jar_for(bytes, file_name, true)
+}
+
+/// Like `read_plugin_jar`, but without `paper` only `plugin.yml` is read, as on Spigot.
+pub fn read_plugin_jar_for(bytes: &[u8], file_name: &str, paper: bool) -> Result<Plugin, String> {
+    let mut jar = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| format!("{} is not a valid jar: {}", file_name, e))?;
+
+    let entries: &[(&str, bool)] = if paper {
+        &[("paper-plugin.yml", true), ("plugin.yml", false)]
+    } else {
+        &[("plugin.yml", false)]
+    };
+    for &(entry, paper) in entries {
+        let mut file = match jar.by_name(entry) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => continue,
+            Err(e) => return Err(format!("{} is not a valid jar: {}", file_name, e)),
+        };
+        let mut text = String::new();
+        file.read_to_string(&mut text)
+            .map_err(|e| format!("Failed to read {} from {}: {}", entry, file_name, e))?;
+        return parse_plugin_yml(&text, paper).map_err(|e| format!("Invalid {} in {}: {}", entry, file_name, e));
+    }
+    Err(format!("{} has no plugin.yml or paper-plugin.yml", file_name))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PluginManager {
     plugins: HashMap<String, Plugin>,
+    /// Jars declaring the same name as one in `plugins`.
+    duplicates: Vec<Plugin>,
     plugins_path: PathBuf,
+    load_errors: Vec<PluginLoadError>,
+    #[serde(skip)]
+    scanner: JarScanner,
 }
 
 impl PluginManager {
     pub fn new(server_path: &PathBuf) -> Self {
         PluginManager {
             plugins: HashMap::new(),
+            duplicates: Vec::new(),
             plugins_path: server_path.join("plugins"),
+            load_errors: Vec::new(),
+            scanner: JarScanner::new(),
         }
     }
 
+    /// Scans installed jars with `scanner` instead of one without a blocklist.
+    pub fn with_scanner(mut self, scanner: JarScanner) -> Self {
+        self.scanner = scanner;
+        self
+    }
+
     pub async fn load_plugins(&mut self) -> Result<(), String> {
         if !self.plugins_path.exists() {
             fs::create_dir_all(&self.plugins_path)
@@ -39,71 +263,451 @@ impl PluginManager {
                 .map_err(|e| e.to_string())?;
         }
 
-        let mut dir = fs::read_dir(&self.plugins_path)
-            .await
-            .map_err(|e| e.to_string())?;
+        self.plugins.clear();
+        self.duplicates.clear();
+        self.load_errors.clear();
+        // Enabled jars come first so they win name clashes with disabled ones.
+        let mut jars = Self::list_jars(&self.plugins_path).await?;
+        jars.extend(Self::list_jars(&self.disabled_path()).await?);
 
-        while let Some(entry) = dir.next_entry().await.map_err(|e| e.to_string())? {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("jar") {
-                if let Ok(plugin) = self.load_plugin_info(&path).await {
+        for path in jars {
+            match self.load_plugin_info(&path).await {
+                Ok(plugin) if self.plugins.contains_key(&plugin.name) => self.duplicates.push(plugin),
+                Ok(plugin) => {
                     self.plugins.insert(plugin.name.clone(), plugin);
                 }
+                Err(message) => self.load_errors.push(PluginLoadError { file: path, message }),
             }
         }
 
         Ok(())
     }
 
+    fn server_path(&self) -> PathBuf {
+        self.plugins_path.parent().map(PathBuf::from).unwrap_or_default()
+    }
+
+    /// Where disabled jars are kept. The server only loads jars directly in `plugins`,
+    /// and the plugin's data folder stays where it is.
+    fn disabled_path(&self) -> PathBuf {
+        self.plugins_path.join(".disabled")
+    }
+
+    async fn list_jars(folder: &PathBuf) -> Result<Vec<PathBuf>, String> {
+        let mut jars = Vec::new();
+        if !folder.exists() {
+            return Ok(jars);
+        }
+        let mut dir = fs::read_dir(folder).await.map_err(|e| e.to_string())?;
+        while let Some(entry) = dir.next_entry().await.map_err(|e| e.to_string())? {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("jar") {
+                jars.push(path);
+            }
+        }
+        jars.sort();
+        Ok(jars)
+    }
+
     async fn load_plugin_info(&self, path: &PathBuf) -> Result<Plugin, String> {
-        // TODO: Implement plugin info extraction from jar file
-        Ok(Plugin {
-            name: path.file_stem().unwrap().to_str().unwrap().to_string(),
-            version: "1.0.0".to_string(),
-            author: "Unknown".to_string(),
-            description: "No description available".to_string(),
-            dependencies: Vec::new(),
-            soft_dependencies: Vec::new(),
-            main: "".to_string(),
-            api_version: "1.20".to_string(),
-            load_before: Vec::new(),
-            website: None,
-            is_enabled: true,
+        let bytes = fs::read(path).await.map_err(|e| e.to_string())?;
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut plugin = read_plugin_jar(&bytes, &file_name)?;
+        plugin.file_path = path.clone();
+        plugin.is_enabled = path.parent() == Some(self.plugins_path.as_path());
+        Ok(plugin)
+    }
+
+    /// The dependency graph of the enabled plugins, including duplicate jars.
+    pub fn dependency_report(&self) -> DependencyReport {
+        let mut plugins = self.get_enabled_plugins();
+        plugins.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        plugins.extend(self.duplicates.iter().filter(|p| p.is_enabled));
+        resolve_dependencies(&plugins)
+    }
+
+    /// Checks whether removing `name` from the enabled plugins would break others.
+    /// Hard dependents are an error unless `force` is set; soft ones are a warning.
+    fn check_removal(&self, name: &str, action: &str, force: bool) -> Result<PluginActionOutcome, String> {
+        let enabled = self.get_enabled_plugins();
+        let (hard, soft) = dependents_of(&enabled, name);
+        let names = |plugins: &[&Plugin]| plugins.iter().map(|p| p.name.as_str()).collect::<Vec<_>>().join(", ");
+
+        let mut outcome = PluginActionOutcome { restart_required: true, ..Default::default() };
+        if !hard.is_empty() {
+            if !force {
+                return Err(format!("Cannot {} {}: it is required by {}", action, name, names(&hard)));
+            }
+            outcome.warnings.push(format!("{} won't load without {}", names(&hard), name));
+        }
+        if !soft.is_empty() {
+            outcome.warnings.push(format!("{} will lose features that use {}", names(&soft), name));
+        }
+        Ok(outcome)
+    }
+
+    /// Warnings about installing `plugin`: missing dependencies, a name clash with an
+    /// installed jar and dependency cycles it would create.
+    pub fn check_install(&self, plugin: &Plugin) -> PluginActionOutcome {
+        let mut outcome = PluginActionOutcome { restart_required: true, ..Default::default() };
+        if let Some(existing) = self.plugins.get(&plugin.name) {
+            if existing.file_path != plugin.file_path {
+                outcome.warnings.push(format!(
+                    "{} is already installed as {}; remove one of them",
+                    plugin.name,
+                    existing.file_path.display()
+                ));
+            }
+        }
+
+        let mut plugins: Vec<&Plugin> = self
+            .get_enabled_plugins()
+            .into_iter()
+            .filter(|p| p.name != plugin.name)
+            .collect();
+        plugins.push(plugin);
+        let report = resolve_dependencies(&plugins);
+        for missing in report.missing.iter().filter(|m| m.plugin == plugin.name) {
+            outcome.warnings.push(format!("{} needs {}, which isn't installed", plugin.name, missing.dependency));
+        }
+        for cycle in report.cycles.iter().filter(|c| c.plugins.contains(&plugin.name)) {
+            outcome.warnings.push(format!(
+                "{} would form a {}dependency cycle: {}",
+                plugin.name,
+                if cycle.hard { "hard " } else { "" },
+                cycle.plugins.join(" -> ")
+            ));
+        }
+        outcome
+    }
+
+    /// Installs the plugin jar at `source`, upgrading the installed jar of the same
+    /// plugin if there is one, and records it in the lockfile. The jar must carry a
+    /// plugin.yml the server type can load and an `api-version` no newer than
+    /// `minecraft_version`.
+    pub async fn install_plugin(
+        &mut self,
+        source: &ExtensionSource,
+        expected_hash: Option<&str>,
+        server_type: ServerType,
+        minecraft_version: &str,
+    ) -> Result<InstallResult, String> {
+        let paper = match server_type {
+            ServerType::Paper => true,
+            ServerType::Spigot => false,
+            other => return Err(format!("{:?} servers don't load plugins", other)),
+        };
+        self.load_plugins().await?;
+        let staged = stage_jar(source, expected_hash, &self.plugins_path).await?;
+        let checked = Self::check_plugin_jar(&staged, paper, minecraft_version);
+        let (mut plugin, warnings) = match checked {
+            Ok(checked) => checked,
+            Err(e) => {
+                staged.discard().await;
+                return Err(e);
+            }
+        };
+
+        // An upgrade replaces the installed jar in whichever folder it's in, so a
+        // disabled plugin stays disabled.
+        let existing = self.find_plugin(&plugin.name, None).ok().cloned();
+        let folder = existing
+            .as_ref()
+            .and_then(|p| p.file_path.parent().map(PathBuf::from))
+            .unwrap_or_else(|| self.plugins_path.clone());
+        plugin.file_path = folder.join(&staged.file_name);
+        plugin.is_enabled = existing.as_ref().is_none_or(|p| p.is_enabled);
+
+        let mut outcome = if plugin.is_enabled {
+            let mut check = plugin.clone();
+            if let Some(existing) = &existing {
+                check.file_path = existing.file_path.clone();
+            }
+            self.check_install(&check)
+        } else {
+            PluginActionOutcome { restart_required: true, ..Default::default() }
+        };
+        outcome.warnings.extend(warnings);
+        let (staged, findings) = staged.scan(&self.scanner, &mut outcome).await?;
+
+        let sha256 = staged.sha256.clone();
+        staged.place(&plugin.file_path, existing.as_ref().map(|p| &p.file_path)).await?;
+        let result = InstallResult {
+            kind: ExtensionKind::Plugin,
+            name: plugin.name.clone(),
+            version: plugin.version.clone(),
+            file_path: plugin.file_path.clone(),
+            sha256,
+            upgraded_from: existing.map(|p| p.version),
+            outcome,
+            findings,
+        };
+        record_install(&self.server_path(), &result, source.clone()).await?;
+        self.load_plugins().await?;
+        Ok(result)
+    }
+
+    /// The plugin set as a lockfile another server can import. Jars MCSM didn't
+    /// install, or that changed since, are exported with the jar itself as the source.
+    pub async fn export_plugins(&self) -> Result<Lockfile, String> {
+        let installed: Vec<(String, String, PathBuf)> = self
+            .plugins
+            .values()
+            .map(|p| (p.name.clone(), p.version.clone(), p.file_path.clone()))
+            .collect();
+        Ok(Lockfile {
+            entries: export_entries(&self.server_path(), ExtensionKind::Plugin, &installed).await?,
+            ..Default::default()
         })
     }
 
-    pub async fn install_plugin(&mut self, url: &str) -> Result<(), String> {
-        // TODO: Implement plugin download and installation
-        Ok(())
+    /// Installs the plugins in `set` that aren't already here with the same hash,
+    /// holding each download to the locked hash. With `prune`, plugins outside the set
+    /// are uninstalled so the server ends up with exactly the set.
+    pub async fn import_plugins(
+        &mut self,
+        set: &Lockfile,
+        server_type: ServerType,
+        minecraft_version: &str,
+        prune: bool,
+    ) -> Result<Vec<InstallResult>, String> {
+        self.load_plugins().await?;
+        let mut results = Vec::new();
+        let mut unchanged = Vec::new();
+        for entry in set.entries.iter().filter(|e| e.kind == ExtensionKind::Plugin) {
+            if let Ok(plugin) = self.find_plugin(&entry.name, None) {
+                if matches_entry(&plugin.file_path, entry).await {
+                    let mut entry = entry.clone();
+                    entry.file_name = plugin.file_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                    unchanged.push(entry);
+                    continue;
+                }
+            }
+            results.push(self.install_plugin(&entry.source, Some(&entry.sha256), server_type, minecraft_version).await?);
+        }
+        record_existing(&self.server_path(), unchanged).await?;
+
+        if prune {
+            let extra: Vec<String> = self
+                .plugins
+                .keys()
+                .filter(|name| set.get(ExtensionKind::Plugin, name).is_none())
+                .cloned()
+                .collect();
+            for name in extra {
+                self.uninstall_plugin(&name, true).await?;
+            }
+        }
+        Ok(results)
+    }
+
+    /// The folder a plugin keeps its files in, `plugins/<name>`, as Bukkit's
+    /// `getDataFolder` names it.
+    pub fn data_folder(&self, name: &str) -> Result<PathBuf, String> {
+        let plugin = self.find_plugin(name, None)?;
+        Ok(self.plugins_path.join(&plugin.name))
     }
 
-    pub async fn uninstall_plugin(&mut self, name: &str) -> Result<(), String> {
-        if let Some(plugin) = self.plugins.get(name) {
-            let path = self.plugins_path.join(format!("{}.jar", name));
-            if path.exists() {
-                fs::remove_file(path)
-                    .await
-                    .map_err(|e| e.to_string())?;
-                self.plugins.remove(name);
+    /// The config files in a plugin's data folder, subfolders included. Data folders and
+    /// large files are left out, as in config history.
+    pub async fn list_config_files(&self, name: &str) -> Result<Vec<PluginConfigFile>, String> {
+        let data_folder = self.data_folder(name)?;
+        let mut files = Vec::new();
+        let mut pending = vec![data_folder.clone()];
+        while let Some(dir) = pending.pop() {
+            if !dir.is_dir() {
+                continue;
+            }
+            let mut entries = fs::read_dir(&dir).await.map_err(|e| e.to_string())?;
+            while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+                let path = entry.path();
+                let metadata = entry.metadata().await.map_err(|e| e.to_string())?;
+                if metadata.is_dir() {
+                    if !is_plugin_data_dir(&path) {
+                        pending.push(path);
+                    }
+                } else if metadata.len() <= MAX_TRACKED_FILE_SIZE && is_plugin_config(&path) {
+                    if let Ok(relative) = path.strip_prefix(&data_folder) {
+                        files.push(PluginConfigFile {
+                            path: relative_path_string(relative),
+                            size: metadata.len(),
+                        });
+                    }
+                }
             }
         }
-        Ok(())
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
     }
 
-    pub async fn enable_plugin(&mut self, name: &str) -> Result<(), String> {
-        if let Some(plugin) = self.plugins.get_mut(name) {
-            plugin.is_enabled = true;
-            // TODO: Implement actual plugin enabling
+    /// Resolves `file` inside a plugin's data folder. Symlinks are followed, and the
+    /// result must still be inside the data folder.
+    async fn config_path(&self, name: &str, file: &str) -> Result<PathBuf, String> {
+        let relative = Path::new(file);
+        if file.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(format!("Invalid config file path: {}", file));
         }
-        Ok(())
+        let data_folder = fs::canonicalize(self.data_folder(name)?)
+            .await
+            .map_err(|e| format!("{} has no data folder: {}", name, e))?;
+        let path = data_folder.join(relative);
+        if !is_plugin_config(&path) {
+            return Err(format!("{} is not a config file", file));
+        }
+
+        // A file being created doesn't exist yet, so check its closest existing ancestor.
+        let mut existing = path.clone();
+        while fs::symlink_metadata(&existing).await.is_err() {
+            existing.pop();
+        }
+        let resolved = fs::canonicalize(&existing)
+            .await
+            .map_err(|e| format!("Failed to resolve {}: {}", file, e))?;
+        if !resolved.starts_with(&data_folder) {
+            return Err(format!("{} is outside the plugin's data folder", file));
+        }
+        Ok(if existing == path { resolved } else { path })
+    }
+
+    pub async fn read_config_file(&self, name: &str, file: &str) -> Result<String, String> {
+        let path = self.config_path(name, file).await?;
+        fs::read_to_string(&path)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", file, e))
     }
 
-    pub async fn disable_plugin(&mut self, name: &str) -> Result<(), String> {
-        if let Some(plugin) = self.plugins.get_mut(name) {
-            plugin.is_enabled = false;
-            // TODO: Implement actual plugin disabling
+    /// Replaces a config file in a plugin's data folder. YAML files must parse, so a
+    /// typo can't stop the plugin from loading its config.
+    pub async fn write_config_file(&self, name: &str, file: &str, content: &str) -> Result<(), String> {
+        let path = self.config_path(name, file).await?;
+        let yaml = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("yml") || e.eq_ignore_ascii_case("yaml"));
+        if yaml {
+            serde_yaml::from_str::<Value>(content).map_err(|e| format!("{} isn't valid YAML: {}", file, e))?;
         }
-        Ok(())
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+        }
+        fs::write(&path, content).await.map_err(|e| e.to_string())
+    }
+
+    /// The console command that reloads a plugin's config, guessed from the commands
+    /// it declares: a `...reload` command, one whose usage mentions `reload`, or
+    /// `<name> reload` when it has a command named after itself.
+    pub fn reload_command(&self, name: &str) -> Result<Option<String>, String> {
+        let plugin = self.find_plugin(name, None)?;
+        if let Some(command) = plugin.commands.iter().find(|c| c.name.to_ascii_lowercase().ends_with("reload")) {
+            return Ok(Some(command.name.clone()));
+        }
+        let mentions_reload = |c: &&PluginCommand| c.usage.as_deref().is_some_and(|u| u.to_ascii_lowercase().contains("reload"));
+        if let Some(command) = plugin.commands.iter().find(mentions_reload) {
+            return Ok(Some(format!("{} reload", command.name)));
+        }
+        Ok(plugin
+            .commands
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(&plugin.name))
+            .map(|c| format!("{} reload", c.name)))
+    }
+
+    /// Reads a staged jar's metadata and checks it against the server, returning any
+    /// warnings.
+    fn check_plugin_jar(staged: &StagedJar, paper: bool, minecraft_version: &str) -> Result<(Plugin, Vec<String>), String> {
+        let plugin = match read_plugin_jar_for(&staged.bytes, &staged.file_name, paper) {
+            Ok(plugin) => plugin,
+            Err(_) if !paper && read_plugin_jar(&staged.bytes, &staged.file_name).is_ok() => {
+                return Err(format!("{} is a Paper plugin and needs a Paper server", staged.file_name));
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut warnings = Vec::new();
+        match plugin.api_version.as_deref() {
+            Some(api) => {
+                let required = MinecraftVersion::parse(api)
+                    .map_err(|_| format!("{} has an invalid api-version: {}", plugin.name, api))?;
+                let server = MinecraftVersion::parse(minecraft_version)?;
+                if required > server {
+                    return Err(format!(
+                        "{} {} needs Minecraft {} or newer (api-version), but the server runs {}",
+                        plugin.name, plugin.version, required, server
+                    ));
+                }
+            }
+            None => warnings.push(format!("{} has no api-version and will run in legacy mode", plugin.name)),
+        }
+        Ok((plugin, warnings))
+    }
+
+    /// The installed jar for `name`, enabled or not, preferring the enabled one.
+    fn find_plugin(&self, name: &str, enabled: Option<bool>) -> Result<&Plugin, String> {
+        self.plugins
+            .get(name)
+            .into_iter()
+            .chain(self.duplicates.iter().filter(|p| p.name == name))
+            .find(|p| enabled.is_none_or(|e| p.is_enabled == e))
+            .ok_or_else(|| match enabled {
+                Some(true) => format!("{} is not enabled", name),
+                Some(false) => format!("{} is not disabled", name),
+                None => format!("{} is not installed", name),
+            })
+    }
+
+    /// Moves a jar into `folder`, refusing to overwrite a different jar there.
+    async fn move_jar(path: &PathBuf, folder: &PathBuf) -> Result<(), String> {
+        let file_name = path.file_name().ok_or("Invalid plugin path")?;
+        let target = folder.join(file_name);
+        if target.exists() {
+            return Err(format!("{} already exists", target.display()));
+        }
+        fs::create_dir_all(folder).await.map_err(|e| e.to_string())?;
+        fs::rename(path, &target).await.map_err(|e| e.to_string())
+    }
+
+    pub async fn uninstall_plugin(&mut self, name: &str, force: bool) -> Result<PluginActionOutcome, String> {
+        let plugin = self.find_plugin(name, None)?;
+        let path = plugin.file_path.clone();
+        let outcome = if plugin.is_enabled {
+            self.check_removal(name, "uninstall", force)?
+        } else {
+            PluginActionOutcome::default()
+        };
+
+        fs::remove_file(&path).await.map_err(|e| e.to_string())?;
+        let server_path = self.server_path();
+        let mut lockfile = Lockfile::load(&server_path).await?;
+        if lockfile.remove(ExtensionKind::Plugin, name).is_some() {
+            lockfile.save(&server_path).await?;
+        }
+        self.load_plugins().await?;
+        Ok(outcome)
+    }
+
+    pub async fn enable_plugin(&mut self, name: &str) -> Result<PluginActionOutcome, String> {
+        if self.find_plugin(name, Some(true)).is_ok() {
+            return Err(format!("{} is already enabled", name));
+        }
+        let plugin = self.find_plugin(name, Some(false))?;
+        let path = plugin.file_path.clone();
+        let outcome = self.check_install(plugin);
+
+        Self::move_jar(&path, &self.plugins_path).await?;
+        self.load_plugins().await?;
+        Ok(outcome)
+    }
+
+    pub async fn disable_plugin(&mut self, name: &str, force: bool) -> Result<PluginActionOutcome, String> {
+        let path = self.find_plugin(name, Some(true))?.file_path.clone();
+        let outcome = self.check_removal(name, "disable", force)?;
+
+        Self::move_jar(&path, &self.disabled_path()).await?;
+        self.load_plugins().await?;
+        Ok(outcome)
     }
 
     pub fn get_plugin(&self, name: &str) -> Option<&Plugin> {
@@ -114,6 +718,11 @@ impl PluginManager {
         self.plugins.values().collect()
     }
 
+    /// Jars from the last `load_plugins` that couldn't be read.
+    pub fn get_load_errors(&self) -> &[PluginLoadError] {
+        &self.load_errors
+    }
+
     pub fn get_enabled_plugins(&self) -> Vec<&Plugin> {
         self.plugins.values().filter(|p| p.is_enabled).collect()
     }
@@ -122,20 +731,32 @@ impl PluginManager {
         self.plugins.values().filter(|p| !p.is_enabled).collect()
     }
 
-    pub async fn check_updates(&self) -> Result<Vec<(String, String)>, String> {
-        // TODO: Implement update checking
-        Ok(Vec::new())
+    /// Newer versions of the plugins installed from Modrinth or Hangar.
+    pub async fn check_updates(&self, catalog: &Catalog, server_type: ServerType, minecraft_version: &str) -> Result<Vec<AvailableUpdate>, String> {
+        let updates = catalog.check_updates(&self.server_path(), server_type, minecraft_version).await?;
+        Ok(updates.into_iter().filter(|u| u.kind == ExtensionKind::Plugin).collect())
     }
 
-    pub async fn update_plugin(&mut self, name: &str) -> Result<(), String> {
-        // TODO: Implement plugin updating
-        Ok(())
+    /// Upgrades `name` to its newest compatible version from the catalog it was
+    /// installed from.
+    pub async fn update_plugin(&mut self, name: &str, catalog: &Catalog, server_type: ServerType, minecraft_version: &str) -> Result<InstallResult, String> {
+        let lockfile = Lockfile::load(&self.server_path()).await?;
+        let entry = lockfile
+            .get(ExtensionKind::Plugin, name)
+            .ok_or_else(|| format!("{} wasn't installed by MCSM", name))?;
+        let latest = catalog
+            .update_for(entry, server_type, minecraft_version)
+            .await?
+            .ok_or_else(|| format!("{} is up to date", name))?;
+        self.install_plugin(&latest.extension_source(), latest.hash.as_deref(), server_type, minecraft_version)
+            .await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lockfile::sha256_hex;
 
     #[tokio::test]
     async fn test_plugin_manager() {
@@ -145,4 +766,273 @@ mod tests {
         assert!(manager.load_plugins().await.is_ok());
         assert_eq!(manager.get_all_plugins().len(), 0);
     }
-} 
\ No newline at end of file
+
+    const ESSENTIALS: &[u8] = include_bytes!("../tests/fixtures/plugins/EssentialsX-2.20.1.jar");
+    const SHOPKEEPER: &[u8] = include_bytes!("../tests/fixtures/plugins/ShopKeeper.jar");
+    const CORRUPT: &[u8] = include_bytes!("../tests/fixtures/plugins/corrupt.jar");
+    const LIBRARY: &[u8] = include_bytes!("../tests/fixtures/plugins/library-only.jar");
+
+    #[tokio::test]
+    async fn test_read_plugin_jars() {
+        let essentials = read_plugin_jar(ESSENTIALS, "EssentialsX-2.20.1.jar").unwrap();
+        assert_eq!(essentials.name, "Essentials");
+        assert_eq!(essentials.version, "2.20.1");
+        assert_eq!(essentials.author, "Zenexer, ementalo, snowleo");
+        assert_eq!(essentials.api_version.as_deref(), Some("1.13"));
+        assert_eq!(essentials.soft_dependencies, ["Vault", "LuckPerms"]);
+        assert_eq!(essentials.website.as_deref(), Some("https://essentialsx.net/"));
+        assert_eq!(essentials.commands.len(), 2);
+        let tpa = essentials.commands.iter().find(|c| c.name == "tpa").unwrap();
+        assert_eq!(tpa.aliases, ["tpask"]);
+        assert_eq!(tpa.permission.as_deref(), Some("essentials.tpa"));
+        let reload = essentials.permissions.iter().find(|p| p.name == "essentials.reload").unwrap();
+        assert_eq!(reload.default.as_deref(), Some("op"));
+
+        let shopkeeper = read_plugin_jar(SHOPKEEPER, "ShopKeeper.jar").unwrap();
+        assert_eq!(shopkeeper.name, "ShopKeeper");
+        assert!(shopkeeper.paper_plugin);
+        assert_eq!(shopkeeper.authors, ["Alex"]);
+        assert_eq!(shopkeeper.dependencies, ["LuckPerms"]);
+        assert_eq!(shopkeeper.soft_dependencies, ["Vault"]);
+        assert_eq!(shopkeeper.load_before, ["WorldGuard"]);
+
+        assert!(read_plugin_jar(CORRUPT, "corrupt.jar").unwrap_err().contains("corrupt.jar is not a valid jar"));
+        assert_eq!(
+            read_plugin_jar(LIBRARY, "library-only.jar").unwrap_err(),
+            "library-only.jar has no plugin.yml or paper-plugin.yml"
+        );
+        assert!(parse_plugin_yml("name: Broken\nmain: a.B\n", false).unwrap_err().contains("version"));
+        let addon = parse_plugin_yml(
+            "name: Addon\nversion: 1.0\nmain: a.Addon\ndependencies:\n  server:\n    Shops:\n      required: true\n      load: AFTER\n    Maps:\n      required: false\n",
+            true,
+        ).unwrap();
+        assert_eq!(addon.dependencies, ["Shops"]);
+        assert_eq!(addon.load_before, ["Shops"]);
+        assert!(addon.soft_dependencies.is_empty());
+
+        let server_path = std::env::temp_dir().join("mcsm_test_plugin_jars");
+        let _ = std::fs::remove_dir_all(&server_path);
+        std::fs::create_dir_all(server_path.join("plugins")).unwrap();
+        for (name, bytes) in [("EssentialsX-2.20.1.jar", ESSENTIALS), ("ShopKeeper.jar", SHOPKEEPER), ("corrupt.jar", CORRUPT)] {
+            std::fs::write(server_path.join("plugins").join(name), bytes).unwrap();
+        }
+        let mut manager = PluginManager::new(&server_path);
+        manager.load_plugins().await.unwrap();
+        assert_eq!(manager.get_all_plugins().len(), 2);
+        assert!(manager.get_plugin("Essentials").is_some());
+        assert_eq!(manager.get_load_errors().len(), 1);
+
+        let report = manager.dependency_report();
+        assert_eq!(report.missing[0].dependency, "LuckPerms");
+        assert_eq!(report.load_order, ["Essentials"]);
+
+        let mut jar = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        jar.start_file("plugin.yml", zip::write::FileOptions::default()).unwrap();
+        std::io::Write::write_all(&mut jar, b"name: LuckPerms\nversion: 5.4\nmain: me.lucko.LuckPerms\n").unwrap();
+        let luckperms_jar = jar.finish().unwrap().into_inner();
+        let mut luckperms = read_plugin_jar(&luckperms_jar, "LuckPerms-5.4.jar").unwrap();
+        luckperms.file_path = server_path.join("plugins").join("LuckPerms-5.4.jar");
+        assert!(manager.check_install(&luckperms).warnings.is_empty());
+        std::fs::write(&luckperms.file_path, &luckperms_jar).unwrap();
+        manager.load_plugins().await.unwrap();
+        assert_eq!(manager.dependency_report().load_order, ["LuckPerms", "Essentials", "ShopKeeper"]);
+
+        let error = manager.disable_plugin("LuckPerms", false).await.unwrap_err();
+        assert_eq!(error, "Cannot disable LuckPerms: it is required by ShopKeeper");
+        let outcome = manager.disable_plugin("LuckPerms", true).await.unwrap();
+        assert!(outcome.restart_required);
+        assert_eq!(outcome.warnings, ["ShopKeeper won't load without LuckPerms", "Essentials will lose features that use LuckPerms"]);
+        assert!(server_path.join("plugins/.disabled/LuckPerms-5.4.jar").exists());
+        assert!(!server_path.join("plugins/LuckPerms-5.4.jar").exists());
+
+        // The state lives on disk, so a fresh manager sees it.
+        let mut reloaded = PluginManager::new(&server_path);
+        reloaded.load_plugins().await.unwrap();
+        assert_eq!(reloaded.get_disabled_plugins().len(), 1);
+        assert!(!reloaded.get_plugin("LuckPerms").unwrap().is_enabled);
+        assert_eq!(reloaded.disable_plugin("LuckPerms", false).await.unwrap_err(), "LuckPerms is not enabled");
+
+        reloaded.enable_plugin("LuckPerms").await.unwrap();
+        assert!(reloaded.get_plugin("LuckPerms").unwrap().is_enabled);
+        assert!(server_path.join("plugins/LuckPerms-5.4.jar").exists());
+
+        reloaded.uninstall_plugin("Essentials", false).await.unwrap();
+        assert!(!server_path.join("plugins/EssentialsX-2.20.1.jar").exists());
+        assert!(reloaded.get_plugin("Essentials").is_none());
+    }
+
+    fn plugin_jar(entry: &str, yml: &str) -> Vec<u8> {
+        let mut jar = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        jar.start_file(entry, zip::write::FileOptions::default()).unwrap();
+        std::io::Write::write_all(&mut jar, yml.as_bytes()).unwrap();
+        jar.finish().unwrap().into_inner()
+    }
+
+    #[tokio::test]
+    async fn test_install_plugin() {
+        let server_path = std::env::temp_dir().join("mcsm_test_install_plugin");
+        let downloads = std::env::temp_dir().join("mcsm_test_install_plugin_downloads");
+        let _ = std::fs::remove_dir_all(&server_path);
+        let _ = std::fs::remove_dir_all(&downloads);
+        std::fs::create_dir_all(&downloads).unwrap();
+        let local = |name: &str, bytes: &[u8]| {
+            std::fs::write(downloads.join(name), bytes).unwrap();
+            ExtensionSource::File { path: downloads.join(name) }
+        };
+
+        let old = plugin_jar("plugin.yml", "name: LuckPerms\nversion: 5.3\nmain: me.lucko.LuckPerms\napi-version: 1.13\n");
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+        let mock = server.mock("GET", "/LuckPerms-5.3.jar").with_body(&old).expect(2).create_async().await;
+        let mut manager = PluginManager::new(&server_path);
+
+        let source = ExtensionSource::Url { url: format!("{}/LuckPerms-5.3.jar", url) };
+        let error = manager.install_plugin(&source, Some(&"0".repeat(64)), ServerType::Paper, "1.20.4").await.unwrap_err();
+        assert!(error.starts_with("Checksum mismatch for LuckPerms-5.3.jar"));
+        let installed = manager
+            .install_plugin(&source, Some(&sha256_hex(&old)), ServerType::Paper, "1.20.4")
+            .await
+            .unwrap();
+        mock.assert_async().await;
+        assert_eq!(installed.upgraded_from, None);
+        assert!(installed.outcome.warnings.is_empty());
+        assert!(server_path.join("plugins/LuckPerms-5.3.jar").exists());
+        assert_eq!(std::fs::read_dir(server_path.join("plugins")).unwrap().count(), 1);
+
+        let new = plugin_jar("plugin.yml", "name: LuckPerms\nversion: 5.4\nmain: me.lucko.LuckPerms\napi-version: 1.13\n");
+        let upgraded = manager
+            .install_plugin(&local("LuckPerms-5.4.jar", &new), None, ServerType::Spigot, "1.20.4")
+            .await
+            .unwrap();
+        assert_eq!(upgraded.upgraded_from.as_deref(), Some("5.3"));
+        assert!(!server_path.join("plugins/LuckPerms-5.3.jar").exists());
+        assert_eq!(manager.get_plugin("LuckPerms").unwrap().version, "5.4");
+
+        let lockfile = Lockfile::load(&server_path).await.unwrap();
+        let entry = lockfile.get(ExtensionKind::Plugin, "LuckPerms").unwrap();
+        assert_eq!(entry.file_name, "LuckPerms-5.4.jar");
+        assert_eq!(entry.sha256, sha256_hex(&new));
+        assert_eq!(entry.source, ExtensionSource::File { path: downloads.join("LuckPerms-5.4.jar") });
+
+        let paper_only = plugin_jar("paper-plugin.yml", "name: Shops\nversion: 1.0\nmain: a.Shops\napi-version: 1.20\n");
+        let error = manager
+            .install_plugin(&local("Shops.jar", &paper_only), None, ServerType::Spigot, "1.20.4")
+            .await
+            .unwrap_err();
+        assert_eq!(error, "Shops.jar is a Paper plugin and needs a Paper server");
+        let too_new = plugin_jar("plugin.yml", "name: Future\nversion: 2.0\nmain: a.Future\napi-version: '1.21'\n");
+        let error = manager
+            .install_plugin(&local("Future.jar", &too_new), None, ServerType::Paper, "1.20.4")
+            .await
+            .unwrap_err();
+        assert_eq!(error, "Future 2.0 needs Minecraft 1.21 or newer (api-version), but the server runs 1.20.4");
+        assert!(manager.install_plugin(&local("Future.jar", &too_new), None, ServerType::Fabric, "1.21").await.is_err());
+        assert_eq!(std::fs::read_dir(server_path.join("plugins")).unwrap().count(), 1);
+
+        manager.uninstall_plugin("LuckPerms", false).await.unwrap();
+        assert!(Lockfile::load(&server_path).await.unwrap().entries.is_empty());
+
+        let mut scanner = JarScanner::new();
+        scanner.block(&sha256_hex(&new), "backdoored");
+        let error = PluginManager::new(&server_path)
+            .with_scanner(scanner)
+            .install_plugin(&local("LuckPerms-5.4.jar", &new), None, ServerType::Paper, "1.20.4")
+            .await
+            .unwrap_err();
+        assert_eq!(error, "Refusing to install LuckPerms-5.4.jar: On the jar blocklist: backdoored");
+        assert_eq!(std::fs::read_dir(server_path.join("plugins")).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_recreate_plugins() {
+        let source_path = std::env::temp_dir().join("mcsm_test_recreate_source");
+        let target_path = std::env::temp_dir().join("mcsm_test_recreate_target");
+        let _ = std::fs::remove_dir_all(&source_path);
+        let _ = std::fs::remove_dir_all(&target_path);
+        std::fs::create_dir_all(source_path.join("plugins")).unwrap();
+        std::fs::create_dir_all(target_path.join("plugins")).unwrap();
+
+        let luckperms = plugin_jar("plugin.yml", "name: LuckPerms\nversion: 5.4\nmain: a.LuckPerms\napi-version: 1.13\n");
+        let vault = plugin_jar("plugin.yml", "name: Vault\nversion: 1.7\nmain: a.Vault\napi-version: 1.13\n");
+        let other = plugin_jar("plugin.yml", "name: Other\nversion: 1.0\nmain: a.Other\napi-version: 1.13\n");
+        std::fs::write(source_path.join("LuckPerms.jar"), &luckperms).unwrap();
+        let mut source = PluginManager::new(&source_path);
+        let lp_source = ExtensionSource::File { path: source_path.join("LuckPerms.jar") };
+        source.install_plugin(&lp_source, None, ServerType::Paper, "1.20.4").await.unwrap();
+        std::fs::write(source_path.join("plugins/Vault.jar"), &vault).unwrap();
+
+        let drift = Lockfile::load(&source_path).await.unwrap().check_drift(&source_path).await.unwrap();
+        assert_eq!(drift.untracked, [source_path.join("plugins/Vault.jar")]);
+        assert!(drift.modified.is_empty() && drift.missing.is_empty());
+
+        source.load_plugins().await.unwrap();
+        let set = source.export_plugins().await.unwrap();
+        assert_eq!(set.entries.len(), 2);
+        assert_eq!(set.get(ExtensionKind::Plugin, "LuckPerms").unwrap().source, lp_source);
+        assert_eq!(
+            set.get(ExtensionKind::Plugin, "Vault").unwrap().source,
+            ExtensionSource::File { path: source_path.join("plugins/Vault.jar") }
+        );
+
+        std::fs::write(target_path.join("plugins/lp.jar"), &luckperms).unwrap();
+        std::fs::write(target_path.join("plugins/Other.jar"), &other).unwrap();
+        let mut target = PluginManager::new(&target_path);
+        let results = target.import_plugins(&set, ServerType::Paper, "1.20.4", true).await.unwrap();
+        assert_eq!(results.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), ["Vault"]);
+        assert!(target.get_plugin("Other").is_none());
+        assert!(!target_path.join("plugins/Other.jar").exists());
+
+        let lockfile = Lockfile::load(&target_path).await.unwrap();
+        assert_eq!(lockfile.get(ExtensionKind::Plugin, "LuckPerms").unwrap().file_name, "lp.jar");
+        assert!(lockfile.check_drift(&target_path).await.unwrap().is_clean());
+
+        std::fs::write(target_path.join("plugins/lp.jar"), &other).unwrap();
+        std::fs::remove_file(target_path.join("plugins/Vault.jar")).unwrap();
+        let drift = lockfile.check_drift(&target_path).await.unwrap();
+        assert_eq!(drift.modified[0].name, "LuckPerms");
+        assert_eq!(drift.missing, ["Vault"]);
+    }
+
+    #[tokio::test]
+    async fn test_plugin_configs() {
+        let server_path = std::env::temp_dir().join("mcsm_test_plugin_configs");
+        let _ = std::fs::remove_dir_all(&server_path);
+        let plugins = server_path.join("plugins");
+        std::fs::create_dir_all(plugins.join("Essentials/locale")).unwrap();
+        std::fs::write(
+            plugins.join("Essentials.jar"),
+            plugin_jar("plugin.yml", "name: Essentials\nversion: 2.20\nmain: a.Essentials\ncommands:\n  essentials:\n    usage: /<command> [reload|debug]\n"),
+        )
+        .unwrap();
+        std::fs::write(plugins.join("Essentials/config.yml"), "ops-name-color: '4'\n").unwrap();
+        std::fs::write(plugins.join("Essentials/locale/messages.properties"), "hello=Hi\n").unwrap();
+        std::fs::write(plugins.join("Essentials/userdata.db"), [0u8; 4]).unwrap();
+        std::fs::create_dir_all(plugins.join("Essentials/userdata")).unwrap();
+        std::fs::write(plugins.join("Essentials/userdata/steve.yml"), "money: 5\n").unwrap();
+
+        let mut manager = PluginManager::new(&server_path);
+        manager.load_plugins().await.unwrap();
+        assert_eq!(manager.data_folder("Essentials").unwrap(), plugins.join("Essentials"));
+        let files: Vec<String> = manager.list_config_files("Essentials").await.unwrap().into_iter().map(|f| f.path).collect();
+        assert_eq!(files, ["config.yml", "locale/messages.properties"]);
+
+        let error = manager.write_config_file("Essentials", "config.yml", "ops-name-color: [4\n").await.unwrap_err();
+        assert!(error.starts_with("config.yml isn't valid YAML"));
+        assert_eq!(manager.read_config_file("Essentials", "config.yml").await.unwrap(), "ops-name-color: '4'\n");
+        manager.write_config_file("Essentials", "config.yml", "ops-name-color: c\n").await.unwrap();
+        assert_eq!(manager.read_config_file("Essentials", "config.yml").await.unwrap(), "ops-name-color: c\n");
+        assert!(manager.read_config_file("Essentials", "../Essentials.jar").await.is_err());
+        assert!(manager.write_config_file("Essentials", "userdata.db", "").await.is_err());
+        #[cfg(unix)]
+        {
+            std::fs::write(server_path.join("secrets.yml"), "token: abc\n").unwrap();
+            std::os::unix::fs::symlink(server_path.join("secrets.yml"), plugins.join("Essentials/linked.yml")).unwrap();
+            std::os::unix::fs::symlink(&server_path, plugins.join("Essentials/server")).unwrap();
+            assert!(manager.read_config_file("Essentials", "linked.yml").await.unwrap_err().contains("outside"));
+            assert!(manager.write_config_file("Essentials", "server/new.yml", "a: 1\n").await.is_err());
+            assert!(!server_path.join("new.yml").exists());
+        }
+
+        assert_eq!(manager.reload_command("Essentials").unwrap().as_deref(), Some("essentials reload"));
+    }
+}
@@ -0,0 +1,333 @@
+use std::fmt;
+
+/// One physical (or continued) line of a `.properties` file.
+#[derive(Debug, Clone, PartialEq)]
+enum PropertyLine {
+    /// Comments and blank lines, kept verbatim.
+    Raw(String),
+    Entry {
+        key: String,
+        value: String,
+        /// The original text of the entry; `None` once the value has been changed.
+        raw: Option<String>,
+    },
+}
+
+/// A Java `.properties` file that keeps comments, ordering, unknown keys and the
+/// original escaping of untouched entries, so writing it back is lossless.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertiesDocument {
+    lines: Vec<PropertyLine>,
+    line_ending: String,
+    trailing_newline: bool,
+}
+
+impl Default for PropertiesDocument {
+    fn default() -> Self {
+        PropertiesDocument {
+            lines: Vec::new(),
+            line_ending: "\n".to_string(),
+            trailing_newline: true,
+        }
+    }
+}
+
+impl PropertiesDocument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut document = PropertiesDocument {
+            lines: Vec::new(),
+            line_ending: if content.contains("\r\n") { "\r\n" } else { "\n" }.to_string(),
+            trailing_newline: content.is_empty() || content.ends_with('\n'),
+        };
+
+        let physical: Vec<&str> = content.lines().collect();
+        let mut i = 0;
+        while i < physical.len() {
+            let line = physical[i];
+            let trimmed = trim_leading_whitespace(line);
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
+                document.lines.push(PropertyLine::Raw(line.to_string()));
+                i += 1;
+                continue;
+            }
+
+            // A line ending in an odd number of backslashes continues on the next one.
+            let start = i;
+            let mut logical = trimmed.to_string();
+            while ends_with_continuation(&logical) {
+                logical.pop();
+                if i + 1 >= physical.len() {
+                    break;
+                }
+                i += 1;
+                logical.push_str(trim_leading_whitespace(physical[i]));
+            }
+
+            let (key, value) = split_entry(&logical)
+                .map_err(|e| format!("Line {}: {}", start + 1, e))?;
+            document.lines.push(PropertyLine::Entry {
+                key,
+                value,
+                raw: Some(physical[start..=i].join(&document.line_ending)),
+            });
+            i += 1;
+        }
+
+        Ok(document)
+    }
+
+    /// Value of `key`; when a key is repeated the last occurrence wins, as in Java.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().rev().find_map(|line| match line {
+            PropertyLine::Entry { key: k, value, .. } if k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Sets `key` in place, or appends it when the file doesn't have it yet.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let mut found = false;
+        for line in self.lines.iter_mut() {
+            if let PropertyLine::Entry { key: k, value: v, raw } = line {
+                if k == key {
+                    found = true;
+                    if v != value {
+                        *v = value.to_string();
+                        *raw = None;
+                    }
+                }
+            }
+        }
+
+        if !found {
+            self.lines.push(PropertyLine::Entry {
+                key: key.to_string(),
+                value: value.to_string(),
+                raw: None,
+            });
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let value = self.get(key).map(|v| v.to_string());
+        self.lines.retain(|line| !matches!(line, PropertyLine::Entry { key: k, .. } if k == key));
+        value
+    }
+
+    /// Appends a `#` comment line.
+    pub fn push_comment(&mut self, comment: &str) {
+        self.lines.push(PropertyLine::Raw(format!("#{}", comment)));
+    }
+
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = Vec::new();
+        for line in &self.lines {
+            if let PropertyLine::Entry { key, .. } = line {
+                if !keys.contains(&key.as_str()) {
+                    keys.push(key);
+                }
+            }
+        }
+        keys
+    }
+
+    pub fn entries(&self) -> Vec<(&str, &str)> {
+        self.keys()
+            .into_iter()
+            .filter_map(|key| self.get(key).map(|value| (key, value)))
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+impl fmt::Display for PropertiesDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, line) in self.lines.iter().enumerate() {
+            if index > 0 {
+                f.write_str(&self.line_ending)?;
+            }
+            match line {
+                PropertyLine::Raw(text) => f.write_str(text)?,
+                PropertyLine::Entry { raw: Some(raw), .. } => f.write_str(raw)?,
+                PropertyLine::Entry { key, value, raw: None } => {
+                    write!(f, "{}={}", escape(key, true), escape(value, false))?
+                }
+            }
+        }
+        if self.trailing_newline && !self.lines.is_empty() {
+            f.write_str(&self.line_ending)?;
+        }
+        Ok(())
+    }
+}
+
+fn trim_leading_whitespace(line: &str) -> &str {
+    line.trim_start_matches([' ', '\t', '\x0c'])
+}
+
+fn ends_with_continuation(line: &str) -> bool {
+    line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+/// Splits a logical line at the first unescaped `=`, `:` or whitespace.
+fn split_entry(line: &str) -> Result<(String, String), String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut key_end = chars.len();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '=' | ':' | ' ' | '\t' | '\x0c' => {
+                key_end = i;
+                break;
+            }
+            _ => i += 1,
+        }
+    }
+
+    let mut value_start = key_end;
+    let mut separator_seen = false;
+    while value_start < chars.len() {
+        match chars[value_start] {
+            ' ' | '\t' | '\x0c' => value_start += 1,
+            '=' | ':' if !separator_seen => {
+                separator_seen = true;
+                value_start += 1;
+            }
+            _ => break,
+        }
+    }
+
+    let key: String = chars[..key_end.min(chars.len())].iter().collect();
+    let value: String = chars[value_start.min(chars.len())..].iter().collect();
+    Ok((unescape(&key)?, unescape(&value)?))
+}
+
+fn unescape(text: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('f') => out.push('\x0c'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                let code = u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == 4)
+                    .ok_or_else(|| format!("Malformed \\uxxxx encoding: \\u{}", hex))?;
+                // Surrogate pairs arrive as two escapes; decode them together.
+                if (0xd800..0xdc00).contains(&code) {
+                    let rest = chars.as_str();
+                    if let Some(low) = rest
+                        .strip_prefix("\\u")
+                        .and_then(|r| r.get(..4))
+                        .and_then(|h| u32::from_str_radix(h, 16).ok())
+                        .filter(|low| (0xdc00..0xe000).contains(low))
+                    {
+                        let combined = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                        out.extend(char::from_u32(combined));
+                        chars = rest[6..].chars();
+                        continue;
+                    }
+                }
+                out.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+            }
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    Ok(out)
+}
+
+/// Escapes a key or value the way `java.util.Properties#store(Writer)` does.
+fn escape(text: &str, is_key: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (index, c) in text.chars().enumerate() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\x0c' => out.push_str("\\f"),
+            '=' | ':' | '#' | '!' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' ' if is_key || index == 0 => out.push_str("\\ "),
+            c if (c as u32) < 0x20 || c as u32 == 0x7f => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VANILLA_1_21: &str = include_str!("../tests/fixtures/server-1.21.properties");
+    const VANILLA_1_12: &str = include_str!("../tests/fixtures/server-1.12.2.properties");
+    const ESCAPES: &str = include_str!("../tests/fixtures/escapes.properties");
+
+    #[test]
+    fn test_golden_round_trip() {
+        for fixture in [VANILLA_1_21, VANILLA_1_12, ESCAPES] {
+            let document = PropertiesDocument::parse(fixture).unwrap();
+            assert_eq!(document.to_string(), fixture);
+        }
+
+        let document = PropertiesDocument::parse(VANILLA_1_21).unwrap();
+        assert_eq!(document.get("level-type"), Some("minecraft:normal"));
+        assert_eq!(document.get("initial-enabled-packs"), Some("vanilla"));
+        assert_eq!(document.get("rcon.port"), Some("25575"));
+
+        let document = PropertiesDocument::parse(ESCAPES).unwrap();
+        assert_eq!(document.get("motd"), Some("\u{a7}aWelcome to \u{a7}lMCSM"));
+        assert_eq!(document.get("key with spaces"), Some("value"));
+        assert_eq!(document.get("continued"), Some("first second third"));
+        assert_eq!(document.get("emoji"), Some("\u{1f642}"));
+        assert_eq!(document.get("colon"), Some("a:b"));
+        assert_eq!(document.get("empty"), Some(""));
+    }
+
+    #[test]
+    fn test_edit_keeps_layout() {
+        let mut document = PropertiesDocument::parse(VANILLA_1_21).unwrap();
+        document.set("max-players", "50");
+        document.set("motd", "  Hello: world");
+        document.set("custom-key", "x");
+
+        let rendered = document.to_string();
+        let changed: Vec<(&str, &str)> = VANILLA_1_21
+            .lines()
+            .zip(rendered.lines())
+            .filter(|(a, b)| a != b)
+            .collect();
+        assert_eq!(changed, vec![
+            ("max-players=20", "max-players=50"),
+            ("motd=A Minecraft Server", "motd=\\  Hello\\: world"),
+        ]);
+        assert!(rendered.ends_with("custom-key=x\n"));
+
+        let reparsed = PropertiesDocument::parse(&rendered).unwrap();
+        assert_eq!(reparsed.get("motd"), Some("  Hello: world"));
+    }
+}
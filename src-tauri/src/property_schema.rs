@@ -0,0 +1,192 @@
+use serde::{Serialize, Deserialize};
+use crate::version::MinecraftVersion;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PropertyType {
+    Boolean,
+    Integer { min: i64, max: i64 },
+    Port,
+    String,
+    Enum { values: Vec<String> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertySchema {
+    pub key: String,
+    pub description: String,
+    pub property_type: PropertyType,
+    pub default: String,
+    /// First Minecraft version that reads the property.
+    pub since: Option<String>,
+    /// First Minecraft version that no longer reads it.
+    pub removed_in: Option<String>,
+    /// `false` when the game has a command that makes the same change while running,
+    /// such as `/difficulty` or `/whitelist on`. Editing the file still needs a restart.
+    pub requires_restart: bool,
+}
+
+impl PropertySchema {
+    pub fn supports(&self, version: &MinecraftVersion) -> bool {
+        version.in_range(self.since.as_deref(), self.removed_in.as_deref())
+    }
+
+    /// Checks a raw value against the property's type, returning a description of the problem.
+    pub fn check_value(&self, value: &str) -> Option<String> {
+        match &self.property_type {
+            PropertyType::Boolean => {
+                if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+                    None
+                } else {
+                    Some(format!("{} must be true or false, got \"{}\"", self.key, value))
+                }
+            }
+            PropertyType::Integer { min, max } => match value.trim().parse::<i64>() {
+                Ok(n) if n < *min || n > *max => {
+                    Some(format!("{} must be between {} and {}, got {}", self.key, min, max, n))
+                }
+                Ok(_) => None,
+                Err(_) => Some(format!("{} must be a whole number, got \"{}\"", self.key, value)),
+            },
+            PropertyType::Port => match value.trim().parse::<u16>() {
+                Ok(port) if port > 0 => None,
+                _ => Some(format!("{} must be a port between 1 and 65535, got \"{}\"", self.key, value)),
+            },
+            PropertyType::String => None,
+            PropertyType::Enum { values } => {
+                if values.iter().any(|v| v.eq_ignore_ascii_case(value.trim())) {
+                    None
+                } else {
+                    Some(format!("{} must be one of {}, got \"{}\"", self.key, values.join(", "), value))
+                }
+            }
+        }
+    }
+}
+
+fn entry(
+    key: &str,
+    property_type: PropertyType,
+    default: &str,
+    since: Option<&str>,
+    removed_in: Option<&str>,
+    requires_restart: bool,
+    description: &str,
+) -> PropertySchema {
+    PropertySchema {
+        key: key.to_string(),
+        description: description.to_string(),
+        property_type,
+        default: default.to_string(),
+        since: since.map(|v| v.to_string()),
+        removed_in: removed_in.map(|v| v.to_string()),
+        requires_restart,
+    }
+}
+
+fn int(min: i64, max: i64) -> PropertyType {
+    PropertyType::Integer { min, max }
+}
+
+fn one_of(values: &[&str]) -> PropertyType {
+    PropertyType::Enum { values: values.iter().map(|v| v.to_string()).collect() }
+}
+
+use PropertyType::{Boolean, Port, String as Text};
+
+/// Metadata for every vanilla `server.properties` key MCSM knows about.
+pub fn server_properties_schema() -> Vec<PropertySchema> {
+    // Difficulty and gamemode still accept their numeric ids.
+    let difficulty = one_of(&["peaceful", "easy", "normal", "hard", "0", "1", "2", "3"]);
+    let gamemode = one_of(&["survival", "creative", "adventure", "spectator", "0", "1", "2", "3"]);
+
+    vec![
+        entry("accepts-transfers", Boolean, "false", Some("1.20.5"), None, true, "Accept players transferred from other servers"),
+        entry("allow-flight", Boolean, "false", None, None, true, "Allow flight in survival mode"),
+        entry("allow-nether", Boolean, "true", None, None, true, "Allow players to travel to the Nether"),
+        entry("announce-player-achievements", Boolean, "true", None, Some("1.12"), true, "Announce achievements in chat"),
+        entry("broadcast-console-to-ops", Boolean, "true", None, None, true, "Send console command output to online operators"),
+        entry("broadcast-rcon-to-ops", Boolean, "true", None, None, true, "Send RCON command output to online operators"),
+        entry("bug-report-link", Text, "", Some("1.21"), None, true, "Link shown in the pause menu for reporting bugs"),
+        entry("difficulty", difficulty, "easy", None, None, false, "World difficulty"),
+        entry("enable-command-block", Boolean, "false", None, None, true, "Enable command blocks"),
+        entry("enable-jmx-monitoring", Boolean, "false", Some("1.16"), None, true, "Expose tick time metrics over JMX"),
+        entry("enable-query", Boolean, "false", None, None, true, "Enable the GameSpy4 query protocol"),
+        entry("enable-rcon", Boolean, "false", None, None, true, "Enable remote console access"),
+        entry("enable-status", Boolean, "true", Some("1.16"), None, true, "Show the server as online in the server list"),
+        entry("enforce-secure-profile", Boolean, "true", Some("1.19"), None, true, "Require players to have a Mojang-signed public key"),
+        entry("enforce-whitelist", Boolean, "false", Some("1.13"), None, false, "Kick players who are not whitelisted when the whitelist reloads"),
+        entry("entity-broadcast-range-percentage", int(10, 1000), "100", Some("1.16"), None, true, "How far away entities are sent to clients, in percent"),
+        entry("force-gamemode", Boolean, "false", None, None, true, "Force players into the default gamemode on join"),
+        entry("function-permission-level", int(1, 4), "2", Some("1.14.4"), None, true, "Permission level of functions"),
+        entry("gamemode", gamemode, "survival", None, None, false, "Default gamemode"),
+        entry("generate-structures", Boolean, "true", None, None, true, "Generate structures in new chunks"),
+        entry("generator-settings", Text, "{}", None, None, true, "Settings for customised world generation"),
+        entry("hardcore", Boolean, "false", None, None, true, "Hardcore mode"),
+        entry("hide-online-players", Boolean, "false", Some("1.18"), None, true, "Hide the player list from status requests"),
+        entry("initial-disabled-packs", Text, "", Some("1.19.3"), None, true, "Datapacks not enabled when a world is created"),
+        entry("initial-enabled-packs", Text, "vanilla", Some("1.19.3"), None, true, "Datapacks enabled when a world is created"),
+        entry("level-name", Text, "world", None, None, true, "World folder name"),
+        entry("level-seed", Text, "", None, None, true, "Seed for new worlds"),
+        entry("level-type", Text, "minecraft:normal", None, None, true, "World preset for new worlds"),
+        entry("log-ips", Boolean, "true", Some("1.20.2"), None, true, "Log player IP addresses"),
+        entry("max-build-height", int(64, 256), "256", None, Some("1.17"), true, "Maximum build height"),
+        entry("max-chained-neighbor-updates", int(-1, i32::MAX as i64), "1000000", Some("1.19"), None, true, "Limit of consecutive neighbor updates"),
+        entry("max-players", int(0, i32::MAX as i64), "20", None, None, true, "Maximum number of players"),
+        entry("max-tick-time", int(-1, i64::MAX), "60000", None, None, true, "Milliseconds a tick may take before the watchdog stops the server"),
+        entry("max-world-size", int(1, 29999984), "29999984", None, None, true, "World border radius limit"),
+        entry("motd", Text, "A Minecraft Server", None, None, true, "Message shown in the server list"),
+        entry("network-compression-threshold", int(-1, i32::MAX as i64), "256", None, None, true, "Packet size above which packets are compressed"),
+        entry("online-mode", Boolean, "true", None, None, true, "Authenticate players with Mojang"),
+        entry("op-permission-level", int(0, 4), "4", None, None, true, "Default permission level for operators"),
+        entry("pause-when-empty-seconds", int(-1, i32::MAX as i64), "60", Some("1.21.2"), None, true, "Seconds without players before the server pauses"),
+        entry("player-idle-timeout", int(0, i32::MAX as i64), "0", None, None, true, "Minutes before idle players are kicked"),
+        entry("prevent-proxy-connections", Boolean, "false", Some("1.11"), None, true, "Kick players whose ISP differs from the authentication server's"),
+        entry("previews-chat", Boolean, "false", Some("1.19"), Some("1.19.3"), true, "Enable chat previews"),
+        entry("pvp", Boolean, "true", None, None, true, "Allow players to fight each other"),
+        entry("query.port", Port, "25565", None, None, true, "UDP port for the query protocol"),
+        entry("rate-limit", int(0, i32::MAX as i64), "0", Some("1.16.2"), None, true, "Packets per second before a player is kicked"),
+        entry("rcon.password", Text, "", None, None, true, "Password for remote console access"),
+        entry("rcon.port", Port, "25575", None, None, true, "TCP port for remote console access"),
+        entry("region-file-compression", one_of(&["deflate", "lz4", "none"]), "deflate", Some("1.20.5"), None, true, "Compression used for region files"),
+        entry("require-resource-pack", Boolean, "false", Some("1.17"), None, true, "Kick players who decline the resource pack"),
+        entry("resource-pack", Text, "", None, None, true, "URL of the server resource pack"),
+        entry("resource-pack-id", Text, "", Some("1.20.3"), None, true, "UUID of the server resource pack"),
+        entry("resource-pack-prompt", Text, "", Some("1.17"), None, true, "Message shown when prompting for the resource pack"),
+        entry("resource-pack-sha1", Text, "", None, None, true, "SHA-1 of the server resource pack"),
+        entry("server-ip", Text, "", None, None, true, "Address to bind to; empty binds to all"),
+        entry("server-port", Port, "25565", None, None, true, "TCP port players connect to"),
+        entry("simulation-distance", int(3, 32), "10", Some("1.18"), None, true, "Chunk radius in which entities are ticked"),
+        entry("snooper-enabled", Boolean, "true", None, Some("1.18"), true, "Send usage statistics to Mojang"),
+        entry("spawn-animals", Boolean, "true", None, Some("1.21.2"), true, "Spawn animals"),
+        entry("spawn-monsters", Boolean, "true", None, None, true, "Spawn monsters"),
+        entry("spawn-npcs", Boolean, "true", None, Some("1.21.2"), true, "Spawn villagers"),
+        entry("spawn-protection", int(0, i32::MAX as i64), "16", None, None, true, "Radius around spawn only operators can build in"),
+        entry("sync-chunk-writes", Boolean, "true", Some("1.16"), None, true, "Write chunk files synchronously"),
+        entry("text-filtering-config", Text, "", Some("1.17"), None, true, "Chat filtering service configuration"),
+        entry("use-native-transport", Boolean, "true", None, None, true, "Use Linux native networking"),
+        entry("view-distance", int(3, 32), "10", None, None, true, "Chunk radius sent to clients"),
+        entry("white-list", Boolean, "false", None, None, false, "Only allow whitelisted players"),
+    ]
+}
+
+/// Schema entries that apply to the given version.
+pub fn schema_for_version(version: &MinecraftVersion) -> Vec<PropertySchema> {
+    server_properties_schema()
+        .into_iter()
+        .filter(|schema| schema.supports(version))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum IssueSeverity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub key: String,
+    pub message: String,
+    pub severity: IssueSeverity,
+}
@@ -0,0 +1,75 @@
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const PACKET_RESPONSE: i32 = 0;
+const PACKET_COMMAND: i32 = 2;
+const PACKET_LOGIN: i32 = 3;
+const MAX_PACKET_SIZE: i32 = 4110;
+
+/// Minimal client for the Source RCON protocol spoken by `enable-rcon=true` servers.
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl RconClient {
+    pub async fn connect(address: &str, password: &str) -> Result<Self, String> {
+        let stream = TcpStream::connect(address)
+            .await
+            .map_err(|e| format!("Failed to connect to RCON at {}: {}", address, e))?;
+
+        let mut client = RconClient { stream, next_id: 1 };
+        let id = client.send_packet(PACKET_LOGIN, password).await?;
+        let (response_id, _, _) = client.read_packet().await?;
+        if response_id == -1 || response_id != id {
+            return Err("RCON authentication failed".to_string());
+        }
+
+        Ok(client)
+    }
+
+    pub async fn command(&mut self, command: &str) -> Result<String, String> {
+        let id = self.send_packet(PACKET_COMMAND, command).await?;
+        loop {
+            let (response_id, packet_type, body) = self.read_packet().await?;
+            if response_id == id && packet_type == PACKET_RESPONSE {
+                return Ok(body);
+            }
+        }
+    }
+
+    async fn send_packet(&mut self, packet_type: i32, body: &str) -> Result<i32, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let length = 4 + 4 + body.len() as i32 + 2;
+        if length > MAX_PACKET_SIZE {
+            return Err("RCON command is too long".to_string());
+        }
+
+        let mut packet = Vec::with_capacity(length as usize + 4);
+        packet.extend_from_slice(&length.to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&packet_type.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+
+        self.stream.write_all(&packet).await.map_err(|e| e.to_string())?;
+        Ok(id)
+    }
+
+    async fn read_packet(&mut self) -> Result<(i32, i32, String), String> {
+        let length = self.stream.read_i32_le().await.map_err(|e| e.to_string())?;
+        if !(10..=MAX_PACKET_SIZE).contains(&length) {
+            return Err(format!("Invalid RCON packet length: {}", length));
+        }
+
+        let id = self.stream.read_i32_le().await.map_err(|e| e.to_string())?;
+        let packet_type = self.stream.read_i32_le().await.map_err(|e| e.to_string())?;
+        let mut body = vec![0u8; length as usize - 8];
+        self.stream.read_exact(&mut body).await.map_err(|e| e.to_string())?;
+        body.truncate(body.len() - 2);
+
+        Ok((id, packet_type, String::from_utf8_lossy(&body).to_string()))
+    }
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+use tokio::fs;
+use crate::properties::PropertiesDocument;
+use crate::ServerConfig;
+
+/// The servers MCSM manages, persisted as `servers.json` in the app data directory.
+pub struct ServerRegistry {
+    registry_file: PathBuf,
+    servers: Vec<ServerConfig>,
+}
+
+impl ServerRegistry {
+    pub fn new(data_dir: &PathBuf) -> Self {
+        ServerRegistry {
+            registry_file: data_dir.join("servers.json"),
+            servers: Vec::new(),
+        }
+    }
+
+    pub async fn load(&mut self) -> Result<(), String> {
+        if !self.registry_file.exists() {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&self.registry_file)
+            .await
+            .map_err(|e| e.to_string())?;
+        self.servers = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub async fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.registry_file.parent() {
+            fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(&self.servers).map_err(|e| e.to_string())?;
+        fs::write(&self.registry_file, content)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn register(&mut self, config: ServerConfig) -> Result<(), String> {
+        if self.get_server(&config.path).is_some() {
+            return Err(format!("A server is already registered at {}", config.path.display()));
+        }
+        self.servers.push(config);
+        Ok(())
+    }
+
+    pub fn unregister(&mut self, server_path: &PathBuf) -> Option<ServerConfig> {
+        let index = self.servers.iter().position(|s| &s.path == server_path)?;
+        Some(self.servers.remove(index))
+    }
+
+    pub fn get_server(&self, server_path: &PathBuf) -> Option<&ServerConfig> {
+        self.servers.iter().find(|s| &s.path == server_path)
+    }
+
+    pub fn get_all_servers(&self) -> Vec<&ServerConfig> {
+        self.servers.iter().collect()
+    }
+
+    /// Game, RCON and query ports claimed by registered servers, whether or not they
+    /// are running, so a new server doesn't collide with a stopped one.
+    pub async fn reserved_ports(&self) -> Vec<u16> {
+        let mut ports = Vec::new();
+        for server in &self.servers {
+            ports.push(server.port);
+            let Ok(content) = fs::read_to_string(server.path.join("server.properties")).await else {
+                continue;
+            };
+            let Ok(properties) = PropertiesDocument::parse(&content) else {
+                continue;
+            };
+            for key in ["server-port", "rcon.port", "query.port"] {
+                if let Some(port) = properties.get(key).and_then(|p| p.trim().parse().ok()) {
+                    ports.push(port);
+                }
+            }
+        }
+        ports.sort();
+        ports.dedup();
+        ports
+    }
+}
@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use chrono::{DateTime, Utc};
 use std::time::Duration;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityEvent {
     pub timestamp: DateTime<Utc>,
     pub event_type: SecurityEventType,
@@ -14,7 +14,7 @@ pub struct SecurityEvent {
     pub severity: SecuritySeverity,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum SecurityEventType {
     LoginAttempt,
     CommandExecution,
@@ -26,7 +26,7 @@ pub enum SecurityEventType {
     PlayerAction,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum SecuritySeverity {
     Info,
     Warning,
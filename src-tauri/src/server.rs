@@ -1,15 +1,17 @@
-use std::path::PathBuf;
 use std::process::{Child, Command};
 use std::sync::{Arc, Mutex};
 use tokio::fs;
 use serde::{Serialize, Deserialize};
-use crate::{ServerConfig, ServerType, MemoryConfig};
+use crate::{ServerConfig, ServerType};
+use crate::java::{resolve_java, JavaDetector, JavaInfo};
+use crate::jvm::JvmOptions;
 
 #[derive(Debug)]
 pub struct MinecraftServer {
     config: ServerConfig,
     process: Arc<Mutex<Option<Child>>>,
     status: Arc<Mutex<ServerStatus>>,
+    java_detector: JavaDetector,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -33,29 +35,32 @@ impl MinecraftServer {
                 memory_usage: 0.0,
                 cpu_usage: 0.0,
             })),
+            java_detector: JavaDetector::new(),
         }
     }
 
+    /// Picks Java with `detector`, such as `ManagedJava::detector`, so runtimes MCSM
+    /// installed are found too.
+    pub fn with_java_detector(mut self, detector: JavaDetector) -> Self {
+        self.java_detector = detector;
+        self
+    }
+
     pub async fn start(&self) -> Result<(), String> {
+        // Resolved before locking, as the guard can't be held across these awaits.
+        let options = JvmOptions::load(&self.config.path).await?;
+        if options.args_file.is_none() && !self.config.path.join("server.jar").exists() {
+            return Err("Server jar not found".to_string());
+        }
+        let java = self.find_java(&options).await?;
+
         let mut process = self.process.lock().unwrap();
         if process.is_some() {
             return Err("Server is already running".to_string());
         }
-
-        let java_path = self.find_java().await?;
-        let server_jar = self.config.path.join("server.jar");
-
-        if !server_jar.exists() {
-            return Err("Server jar not found".to_string());
-        }
-
-        let mut command = Command::new(java_path);
+        let mut command = Command::new(&java.path);
         command
-            .arg(format!("-Xms{}M", self.config.memory.min_mb))
-            .arg(format!("-Xmx{}M", self.config.memory.max_mb))
-            .arg("-jar")
-            .arg(server_jar)
-            .arg("nogui")
+            .args(options.command_args(&self.config.memory, Some(java.major_version), "server.jar"))
             .current_dir(&self.config.path);
 
         match command.spawn() {
@@ -89,9 +94,8 @@ impl MinecraftServer {
         self.start()
     }
 
-    async fn find_java(&self) -> Result<PathBuf, String> {
-        // TODO: Implement Java detection logic
-        Ok(PathBuf::from("java"))
+    async fn find_java(&self, options: &JvmOptions) -> Result<JavaInfo, String> {
+        resolve_java(&self.java_detector, &self.config.version, options.java_path.as_deref()).await
     }
 
     pub async fn download_server(&self, version: &str, server_type: ServerType) -> Result<(), String> {
@@ -107,6 +111,8 @@ impl MinecraftServer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
+    use crate::MemoryConfig;
 
     #[tokio::test]
     async fn test_server_creation() {
@@ -114,6 +120,7 @@ mod tests {
             name: "Test Server".to_string(),
             version: "1.20.4".to_string(),
             server_type: ServerType::Vanilla,
+            loader_version: None,
             path: PathBuf::from("test_server"),
             memory: MemoryConfig {
                 min_mb: 1024,
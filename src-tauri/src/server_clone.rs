@@ -0,0 +1,246 @@
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use tokio::fs;
+use crate::properties::PropertiesDocument;
+use crate::utils::SystemUtils;
+use crate::ServerConfig;
+
+/// Top-level folders that only make sense for the original server. `.mcsm` holds its
+/// session history, config history and backups.
+const EXCLUDED_DIRS: [&str; 3] = ["logs", "crash-reports", ".mcsm"];
+/// Held by a running server; a copied lock can stop the clone from opening the world.
+const EXCLUDED_FILES: [&str; 1] = ["session.lock"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloneOptions {
+    pub name: String,
+    pub destination: PathBuf,
+    pub include_worlds: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloneResult {
+    pub config: ServerConfig,
+    pub rcon_port: Option<u16>,
+    pub query_port: Option<u16>,
+    /// World folders left out because `include_worlds` was off.
+    pub skipped_worlds: Vec<String>,
+}
+
+/// Copies a server directory and gives the copy its own ports.
+pub struct ServerCloner {
+    source: ServerConfig,
+}
+
+impl ServerCloner {
+    pub fn new(source: &ServerConfig) -> Self {
+        ServerCloner { source: source.clone() }
+    }
+
+    /// Clones into `options.destination`, avoiding `reserved_ports` as well as ports in use.
+    pub async fn clone_to(&self, options: &CloneOptions, reserved_ports: &[u16]) -> Result<CloneResult, String> {
+        let destination = &options.destination;
+        if destination.exists() && destination.read_dir().map(|mut d| d.next().is_some()).unwrap_or(true) {
+            return Err(format!("{} already exists and is not empty", destination.display()));
+        }
+        if destination.starts_with(&self.source.path) {
+            return Err("Cannot clone a server into its own directory".to_string());
+        }
+
+        let mut skipped_worlds = Vec::new();
+        fs::create_dir_all(destination)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", destination.display(), e))?;
+
+        let mut entries = fs::read_dir(&self.source.path)
+            .await
+            .map_err(|e| e.to_string())?;
+        while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = entry.path();
+            if path.is_dir() {
+                if EXCLUDED_DIRS.contains(&name.as_str()) {
+                    continue;
+                }
+                if !options.include_worlds && path.join("level.dat").exists() {
+                    skipped_worlds.push(name);
+                    continue;
+                }
+            }
+            copy_entry(&path, &destination.join(&name)).await?;
+        }
+
+        let mut reserved = reserved_ports.to_vec();
+        reserved.push(self.source.port);
+        let ports = self.rewrite_ports(destination, &mut reserved).await?;
+        skipped_worlds.sort();
+
+        Ok(CloneResult {
+            config: ServerConfig {
+                name: options.name.clone(),
+                version: self.source.version.clone(),
+                server_type: self.source.server_type,
+                loader_version: self.source.loader_version.clone(),
+                path: destination.clone(),
+                memory: self.source.memory.clone(),
+                port: ports.0,
+            },
+            rcon_port: ports.1,
+            query_port: ports.2,
+            skipped_worlds,
+        })
+    }
+
+    /// Gives the clone a free game port, and free RCON and query ports when the
+    /// original sets them. Query sharing the game port (the vanilla default) is kept.
+    async fn rewrite_ports(&self, destination: &Path, reserved: &mut Vec<u16>) -> Result<(u16, Option<u16>, Option<u16>), String> {
+        let properties_path = destination.join("server.properties");
+        let content = if properties_path.exists() {
+            fs::read_to_string(&properties_path)
+                .await
+                .map_err(|e| e.to_string())?
+        } else {
+            String::new()
+        };
+        let mut properties = PropertiesDocument::parse(&content)?;
+        let source_port = properties.get("server-port").and_then(|p| p.trim().parse().ok());
+        for key in ["rcon.port", "query.port"] {
+            if let Some(port) = properties.get(key).and_then(|p| p.trim().parse().ok()) {
+                reserved.push(port);
+            }
+        }
+
+        let start = self.source.port.saturating_add(1);
+        let server_port = allocate_port(start, reserved).await?;
+        properties.set("server-port", &server_port.to_string());
+
+        let rcon_port = match properties.get("rcon.port") {
+            Some(_) => {
+                let port = allocate_port(start, reserved).await?;
+                properties.set("rcon.port", &port.to_string());
+                Some(port)
+            }
+            None => None,
+        };
+
+        let query_port = match properties.get("query.port").and_then(|p| p.trim().parse::<u16>().ok()) {
+            Some(port) if Some(port) == source_port => {
+                properties.set("query.port", &server_port.to_string());
+                Some(server_port)
+            }
+            Some(_) => {
+                let port = allocate_port(start, reserved).await?;
+                properties.set("query.port", &port.to_string());
+                Some(port)
+            }
+            None => None,
+        };
+
+        fs::write(&properties_path, properties.to_string())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok((server_port, rcon_port, query_port))
+    }
+}
+
+async fn allocate_port(start: u16, reserved: &mut Vec<u16>) -> Result<u16, String> {
+    let mut candidate = start;
+    loop {
+        let port = SystemUtils::find_available_port(candidate)
+            .await
+            .ok_or_else(|| "No free port available".to_string())?;
+        if !reserved.contains(&port) {
+            reserved.push(port);
+            return Ok(port);
+        }
+        candidate = port.checked_add(1).ok_or_else(|| "No free port available".to_string())?;
+    }
+}
+
+async fn copy_entry(source: &Path, destination: &Path) -> Result<(), String> {
+    let mut pending = vec![(source.to_path_buf(), destination.to_path_buf())];
+    while let Some((from, to)) = pending.pop() {
+        let metadata = fs::metadata(&from).await.map_err(|e| e.to_string())?;
+        if metadata.is_dir() {
+            fs::create_dir_all(&to).await.map_err(|e| e.to_string())?;
+            let mut entries = fs::read_dir(&from).await.map_err(|e| e.to_string())?;
+            while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+                pending.push((entry.path(), to.join(entry.file_name())));
+            }
+        } else if !from
+            .file_name()
+            .map(|n| EXCLUDED_FILES.contains(&n.to_string_lossy().as_ref()))
+            .unwrap_or(false)
+        {
+            fs::copy(&from, &to)
+                .await
+                .map_err(|e| format!("Failed to copy {}: {}", from.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MemoryConfig, ServerType};
+
+    #[tokio::test]
+    async fn test_clone_server() {
+        let root = std::env::temp_dir().join("mcsm_test_clone");
+        let _ = std::fs::remove_dir_all(&root);
+        let source = root.join("production");
+        for dir in ["world/region", "world_nether", "logs", "crash-reports", "plugins/Essentials", ".mcsm"] {
+            std::fs::create_dir_all(source.join(dir)).unwrap();
+        }
+        std::fs::write(source.join("world").join("level.dat"), b"").unwrap();
+        std::fs::write(source.join("world").join("session.lock"), b"").unwrap();
+        std::fs::write(source.join("world_nether").join("level.dat"), b"").unwrap();
+        std::fs::write(source.join("logs").join("latest.log"), b"").unwrap();
+        std::fs::write(source.join("plugins").join("Essentials").join("config.yml"), b"a: 1\n").unwrap();
+        std::fs::write(
+            source.join("server.properties"),
+            "#Minecraft server properties\nquery.port=25565\nrcon.port=25575\nserver-port=25565\n",
+        ).unwrap();
+
+        let config = ServerConfig {
+            name: "Production".to_string(),
+            version: "1.21.4".to_string(),
+            server_type: ServerType::Paper,
+            loader_version: None,
+            path: source.clone(),
+            memory: MemoryConfig { min_mb: 1024, max_mb: 2048 },
+            port: 25565,
+        };
+        let cloner = ServerCloner::new(&config);
+        let options = CloneOptions {
+            name: "Staging".to_string(),
+            destination: root.join("staging"),
+            include_worlds: false,
+        };
+        let result = cloner.clone_to(&options, &[25566]).await.unwrap();
+
+        let staging = root.join("staging");
+        assert_eq!(result.skipped_worlds, vec!["world", "world_nether"]);
+        assert!(staging.join("plugins").join("Essentials").join("config.yml").exists());
+        assert!(!staging.join("world").exists());
+        assert!(!staging.join("logs").exists());
+        assert!(!staging.join(".mcsm").exists());
+
+        assert_ne!(result.config.port, 25565);
+        assert_ne!(result.config.port, 25566);
+        assert_eq!(result.query_port, Some(result.config.port));
+        let properties = PropertiesDocument::parse(&std::fs::read_to_string(staging.join("server.properties")).unwrap()).unwrap();
+        assert_eq!(properties.get("server-port"), Some(result.config.port.to_string().as_str()));
+        assert_eq!(properties.get("rcon.port"), result.rcon_port.map(|p| p.to_string()).as_deref());
+        assert_ne!(result.rcon_port, Some(25575));
+
+        assert!(cloner.clone_to(&options, &[]).await.is_err());
+
+        let with_worlds = CloneOptions { destination: root.join("full"), include_worlds: true, ..options };
+        let result = cloner.clone_to(&with_worlds, &[]).await.unwrap();
+        assert!(result.skipped_worlds.is_empty());
+        assert!(root.join("full").join("world").join("level.dat").exists());
+        assert!(!root.join("full").join("world").join("session.lock").exists());
+    }
+}
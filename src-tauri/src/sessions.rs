@@ -0,0 +1,466 @@
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, NaiveDate, Utc};
+use crate::players::{Player, PlayerList};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEvent {
+    Join {
+        uuid: String,
+        name: String,
+        ip: String,
+        timestamp: DateTime<Utc>,
+    },
+    Leave {
+        uuid: String,
+        timestamp: DateTime<Utc>,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSession {
+    pub uuid: String,
+    pub name: String,
+    pub ip: String,
+    pub joined_at: DateTime<Utc>,
+    pub left_at: Option<DateTime<Utc>>,
+    pub disconnect_reason: Option<String>,
+}
+
+impl PlayerSession {
+    pub fn duration_secs(&self, now: DateTime<Utc>) -> u64 {
+        let end = self.left_at.unwrap_or(now);
+        (end - self.joined_at).num_seconds().max(0) as u64
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailyActivity {
+    pub date: NaiveDate,
+    pub active_players: usize,
+    pub new_players: usize,
+    pub returning_players: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PeakConcurrency {
+    pub players: usize,
+    pub at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AltAccountGroup {
+    pub ip: String,
+    pub accounts: Vec<(String, String)>,
+}
+
+/// Append-only join/leave log for a single server, stored in `.mcsm/sessions.jsonl`.
+pub struct SessionStore {
+    log_path: PathBuf,
+    sessions: Vec<PlayerSession>,
+    pending_uuids: HashMap<String, String>,
+}
+
+impl SessionStore {
+    pub fn new(server_path: &PathBuf) -> Self {
+        SessionStore {
+            log_path: server_path.join(".mcsm").join("sessions.jsonl"),
+            sessions: Vec::new(),
+            pending_uuids: HashMap::new(),
+        }
+    }
+
+    pub async fn load(&mut self) -> Result<(), String> {
+        self.sessions.clear();
+        if !self.log_path.exists() {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&self.log_path)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event: SessionEvent = serde_json::from_str(line)
+                .map_err(|e| format!("Invalid session record on line {}: {}", index + 1, e))?;
+            self.apply_event(event);
+        }
+
+        Ok(())
+    }
+
+    pub async fn record_join(&mut self, uuid: &str, name: &str, ip: &str, timestamp: DateTime<Utc>) -> Result<(), String> {
+        // A join without a matching leave means the server went down with the player online.
+        if self.open_session(uuid).is_some() {
+            self.record_leave(uuid, timestamp, "Session not closed").await?;
+        }
+
+        self.append(SessionEvent::Join {
+            uuid: uuid.to_string(),
+            name: name.to_string(),
+            ip: ip.to_string(),
+            timestamp,
+        }).await
+    }
+
+    pub async fn record_leave(&mut self, uuid: &str, timestamp: DateTime<Utc>, reason: &str) -> Result<(), String> {
+        if self.open_session(uuid).is_none() {
+            return Ok(());
+        }
+
+        self.append(SessionEvent::Leave {
+            uuid: uuid.to_string(),
+            timestamp,
+            reason: reason.to_string(),
+        }).await
+    }
+
+    /// Closes every open session, used when the server process stops.
+    pub async fn close_all(&mut self, timestamp: DateTime<Utc>, reason: &str) -> Result<(), String> {
+        let open: Vec<String> = self.sessions
+            .iter()
+            .filter(|s| s.left_at.is_none())
+            .map(|s| s.uuid.clone())
+            .collect();
+
+        for uuid in open {
+            self.record_leave(&uuid, timestamp, reason).await?;
+        }
+        Ok(())
+    }
+
+    /// Feeds a line of server console output into the store, recording joins and leaves.
+    /// Only lines the server itself logs are read, so chat can't fake a session.
+    pub async fn handle_console_line(&mut self, line: &str, timestamp: DateTime<Utc>) -> Result<(), String> {
+        let Some((source, message)) = console_message(line) else {
+            return Ok(());
+        };
+
+        if let Some(rest) = message.strip_prefix("UUID of player ") {
+            if let Some((name, uuid)) = rest.split_once(" is ") {
+                if is_player_name(name) {
+                    self.pending_uuids.insert(name.to_string(), uuid.trim().to_string());
+                }
+            }
+            return Ok(());
+        }
+        if source == LogSource::Authenticator {
+            return Ok(());
+        }
+
+        if let Some((name, ip)) = parse_login(message) {
+            let uuid = self.pending_uuids
+                .remove(name)
+                .unwrap_or_else(|| name.to_string());
+            self.record_join(&uuid, name, ip, timestamp).await?;
+        } else if let Some((name, reason)) = message.split_once(" lost connection: ") {
+            if let Some(uuid) = self.open_uuid_by_name(name) {
+                self.record_leave(&uuid, timestamp, reason.trim()).await?;
+            }
+        } else if let Some(name) = message.strip_suffix(" left the game") {
+            if let Some(uuid) = self.open_uuid_by_name(name) {
+                self.record_leave(&uuid, timestamp, "left the game").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn append(&mut self, event: SessionEvent) -> Result<(), String> {
+        if let Some(parent) = self.log_path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        let mut line = serde_json::to_string(&event).map_err(|e| e.to_string())?;
+        line.push('\n');
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.log_path)
+            .await
+            .map_err(|e| e.to_string())?;
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        self.apply_event(event);
+        Ok(())
+    }
+
+    fn apply_event(&mut self, event: SessionEvent) {
+        match event {
+            SessionEvent::Join { uuid, name, ip, timestamp } => {
+                self.sessions.push(PlayerSession {
+                    uuid,
+                    name,
+                    ip,
+                    joined_at: timestamp,
+                    left_at: None,
+                    disconnect_reason: None,
+                });
+            }
+            SessionEvent::Leave { uuid, timestamp, reason } => {
+                if let Some(session) = self.sessions
+                    .iter_mut()
+                    .rev()
+                    .find(|s| s.uuid == uuid && s.left_at.is_none())
+                {
+                    session.left_at = Some(timestamp);
+                    session.disconnect_reason = Some(reason);
+                }
+            }
+        }
+    }
+
+    fn open_session(&self, uuid: &str) -> Option<&PlayerSession> {
+        self.sessions.iter().find(|s| s.uuid == uuid && s.left_at.is_none())
+    }
+
+    fn open_uuid_by_name(&self, name: &str) -> Option<String> {
+        self.sessions
+            .iter()
+            .find(|s| s.name == name && s.left_at.is_none())
+            .map(|s| s.uuid.clone())
+    }
+
+    pub fn get_sessions(&self) -> &[PlayerSession] {
+        &self.sessions
+    }
+
+    pub fn get_player_sessions(&self, uuid: &str) -> Vec<&PlayerSession> {
+        self.sessions.iter().filter(|s| s.uuid == uuid).collect()
+    }
+
+    pub fn is_online(&self, uuid: &str) -> bool {
+        self.open_session(uuid).is_some()
+    }
+
+    pub fn total_playtime(&self, uuid: &str, now: DateTime<Utc>) -> u64 {
+        self.sessions
+            .iter()
+            .filter(|s| s.uuid == uuid)
+            .map(|s| s.duration_secs(now))
+            .sum()
+    }
+
+    pub fn last_seen(&self, uuid: &str) -> Option<DateTime<Utc>> {
+        self.sessions
+            .iter()
+            .filter(|s| s.uuid == uuid)
+            .map(|s| s.left_at.unwrap_or(s.joined_at))
+            .max()
+    }
+
+    /// Active, new and returning player counts for each day in `from..=to`.
+    pub fn daily_activity(&self, from: NaiveDate, to: NaiveDate, now: DateTime<Utc>) -> Vec<DailyActivity> {
+        let mut first_seen: HashMap<&str, NaiveDate> = HashMap::new();
+        for session in &self.sessions {
+            let date = session.joined_at.date_naive();
+            first_seen
+                .entry(session.uuid.as_str())
+                .and_modify(|d| *d = (*d).min(date))
+                .or_insert(date);
+        }
+
+        let mut days = Vec::new();
+        let mut date = from;
+        while date <= to {
+            let active: HashSet<&str> = self.sessions
+                .iter()
+                .filter(|s| {
+                    let start = s.joined_at.date_naive();
+                    let end = s.left_at.unwrap_or(now).date_naive();
+                    start <= date && date <= end
+                })
+                .map(|s| s.uuid.as_str())
+                .collect();
+
+            let new_players = active
+                .iter()
+                .filter(|uuid| first_seen.get(*uuid) == Some(&date))
+                .count();
+
+            days.push(DailyActivity {
+                date,
+                active_players: active.len(),
+                new_players,
+                returning_players: active.len() - new_players,
+            });
+
+            date = match date.succ_opt() {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        days
+    }
+
+    pub fn peak_concurrency(&self, from: DateTime<Utc>, to: DateTime<Utc>, now: DateTime<Utc>) -> PeakConcurrency {
+        let mut changes: Vec<(DateTime<Utc>, i32)> = Vec::new();
+        for session in &self.sessions {
+            let start = session.joined_at.max(from);
+            let end = session.left_at.unwrap_or(now).min(to);
+            if start < end {
+                changes.push((start, 1));
+                changes.push((end, -1));
+            }
+        }
+        // Leaves sort before joins at the same instant so a reconnect doesn't count twice.
+        changes.sort_by_key(|(time, delta)| (*time, *delta));
+
+        let mut current = 0;
+        let mut peak = PeakConcurrency { players: 0, at: None };
+        for (time, delta) in changes {
+            current += delta;
+            if current as usize > peak.players {
+                peak.players = current as usize;
+                peak.at = Some(time);
+            }
+        }
+        peak
+    }
+
+    /// Groups accounts that have connected from the same IP address.
+    pub fn alt_accounts(&self) -> Vec<AltAccountGroup> {
+        let mut by_ip: HashMap<&str, Vec<(String, String)>> = HashMap::new();
+        for session in self.sessions.iter().filter(|s| !s.ip.is_empty()) {
+            let accounts = by_ip.entry(session.ip.as_str()).or_default();
+            if !accounts.iter().any(|(uuid, _)| uuid == &session.uuid) {
+                accounts.push((session.uuid.clone(), session.name.clone()));
+            }
+        }
+
+        let mut groups: Vec<AltAccountGroup> = by_ip
+            .into_iter()
+            .filter(|(_, accounts)| accounts.len() > 1)
+            .map(|(ip, accounts)| AltAccountGroup {
+                ip: ip.to_string(),
+                accounts,
+            })
+            .collect();
+        groups.sort_by(|a, b| a.ip.cmp(&b.ip));
+        groups
+    }
+
+    /// Adds players seen in the log to `players`, then updates `last_seen`,
+    /// `playtime` and `is_online` for every known player.
+    pub fn apply_to_players(&self, players: &mut PlayerList, now: DateTime<Utc>) {
+        for session in &self.sessions {
+            if players.get_player(&session.uuid).is_none() {
+                players.add_player(Player {
+                    uuid: session.uuid.clone(),
+                    name: session.name.clone(),
+                    is_online: false,
+                    is_op: players.is_op(&session.uuid),
+                    last_seen: String::new(),
+                    playtime: 0,
+                    deaths: 0,
+                    kills: 0,
+                });
+            }
+        }
+        for player in players.get_all_players_mut() {
+            if let Some(last_seen) = self.last_seen(&player.uuid) {
+                player.last_seen = last_seen.to_rfc3339();
+            }
+            player.playtime = self.total_playtime(&player.uuid, now);
+            player.is_online = self.is_online(&player.uuid);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum LogSource {
+    /// The main thread: `[time] [Server thread/INFO]` or Paper's `[time INFO]`.
+    Server,
+    /// `[time] [User Authenticator #n/INFO]`, which logs player UUIDs.
+    Authenticator,
+}
+
+/// The source and message of an INFO line the server logged itself. Chat, which
+/// players control, comes from other threads or starts with `<name>`.
+fn console_message(line: &str) -> Option<(LogSource, &str)> {
+    let (prefix, message) = line.split_once("]: ")?;
+    let thread = prefix.rsplit_once('[').map_or(prefix, |(_, thread)| thread);
+    let source = if thread == "Server thread/INFO" {
+        LogSource::Server
+    } else if thread.starts_with("User Authenticator #") && thread.ends_with("/INFO") {
+        LogSource::Authenticator
+    } else if thread.strip_suffix(" INFO").is_some_and(|time| time.chars().all(|c| c.is_ascii_digit() || c == ':')) {
+        LogSource::Server
+    } else {
+        return None;
+    };
+    let message = message.trim();
+    (!message.starts_with('<')).then_some((source, message))
+}
+
+fn is_player_name(name: &str) -> bool {
+    (1..=16).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `name[/ip:port] logged in with entity id ...`, as a whole message.
+fn parse_login(message: &str) -> Option<(&str, &str)> {
+    let (name, rest) = message.split_once("[/")?;
+    let (address, _) = rest.split_once("] logged in with entity id ")?;
+    let (ip, port) = address.rsplit_once(':')?;
+    let valid = is_player_name(name) && !ip.is_empty() && port.parse::<u16>().is_ok();
+    valid.then_some((name, ip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn test_session_store() {
+        let server_path = std::env::temp_dir().join("mcsm_test_sessions");
+        let _ = std::fs::remove_dir_all(&server_path);
+        let mut store = SessionStore::new(&server_path);
+
+        let t = |h: u32, m: u32| Utc.with_ymd_and_hms(2024, 1, 1, h, m, 0).unwrap();
+
+        store.handle_console_line("[10:00:00] [User Authenticator #1/INFO]: UUID of player Steve is uuid-steve", t(10, 0)).await.unwrap();
+        store.handle_console_line("[10:00:00] [Server thread/INFO]: Steve[/10.0.0.5:51234] logged in with entity id 1 at (0.5, 64.0, 0.5)", t(10, 0)).await.unwrap();
+        store.record_join("uuid-alex", "Alex", "10.0.0.5", t(10, 30)).await.unwrap();
+        // Chat can't fake a login, whatever thread it's logged from.
+        store.handle_console_line("[10:05:00] [Server thread/INFO]: <Mallory> Bob[/6.6.6.6:1] logged in with entity id 1 at (0.0, 0.0, 0.0)", t(10, 5)).await.unwrap();
+        store.handle_console_line("[10:05:00] [Async Chat Thread - #0/INFO]: Bob[/6.6.6.6:1] logged in with entity id 1 at (0.0, 0.0, 0.0)", t(10, 5)).await.unwrap();
+        assert_eq!(store.get_sessions().len(), 2);
+        store.handle_console_line("[11:00:00] [Server thread/INFO]: Steve lost connection: Disconnected", t(11, 0)).await.unwrap();
+        store.handle_console_line("[11:00:00] [Server thread/INFO]: Steve left the game", t(11, 0)).await.unwrap();
+
+        assert_eq!(store.total_playtime("uuid-steve", t(12, 0)), 3600);
+        assert_eq!(store.get_player_sessions("uuid-steve")[0].disconnect_reason.as_deref(), Some("Disconnected"));
+        assert!(store.is_online("uuid-alex"));
+        assert_eq!(store.peak_concurrency(t(0, 0), t(23, 0), t(12, 0)).players, 2);
+        assert_eq!(store.alt_accounts().len(), 1);
+
+        let mut reloaded = SessionStore::new(&server_path);
+        reloaded.load().await.unwrap();
+        assert_eq!(reloaded.get_sessions().len(), 2);
+
+        let day = t(0, 0).date_naive();
+        let activity = reloaded.daily_activity(day, day, t(12, 0));
+        assert_eq!(activity[0].active_players, 2);
+        assert_eq!(activity[0].new_players, 2);
+
+        let mut players = PlayerList::new();
+        reloaded.apply_to_players(&mut players, t(12, 0));
+        let steve = players.get_player("uuid-steve").unwrap();
+        assert_eq!((steve.playtime, steve.is_online), (3600, false));
+        assert!(players.get_player("uuid-alex").unwrap().is_online);
+    }
+}
@@ -0,0 +1,449 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use tokio::fs;
+use crate::config::ServerProperties;
+use crate::jar_install::StagedJar;
+use crate::jar_scan::JarScanner;
+use crate::jvm::{aikar_flags, JvmOptions};
+use crate::plugins::PluginActionOutcome;
+use crate::{MemoryConfig, ServerConfig, ServerType};
+
+/// Bumped when the exported template format changes incompatibly.
+const TEMPLATE_FORMAT_VERSION: u32 = 1;
+
+/// A plugin, mod or datapack a template installs. Entries without a URL are listed
+/// for the user to install by hand.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateResource {
+    pub name: String,
+    pub url: Option<String>,
+    pub file_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub server_type: ServerType,
+    pub version: String,
+    pub memory: MemoryConfig,
+    pub jvm_flags: Vec<String>,
+    /// `server.properties` overrides; values may use `${name}`, `${port}` and `${seed}`.
+    pub properties: BTreeMap<String, String>,
+    /// Plugins for Bukkit-based servers, mods for Forge and Fabric.
+    pub plugins: Vec<TemplateResource>,
+    pub datapacks: Vec<TemplateResource>,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+/// Values substituted into a template when a server is created from it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateVariables {
+    pub name: String,
+    pub port: u16,
+    pub seed: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TemplateFile {
+    format_version: u32,
+    template: ServerTemplate,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateCreation {
+    pub config: ServerConfig,
+    /// Resources the template lists without a download URL.
+    pub manual_installs: Vec<String>,
+    /// What the jar scanner flagged in downloaded plugins and mods.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+impl ServerTemplate {
+    fn substitute(&self, text: &str, variables: &TemplateVariables) -> Result<String, String> {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            out.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("Unterminated variable in \"{}\"", text))?;
+            match &rest[start + 2..start + end] {
+                "name" => out.push_str(&variables.name),
+                "port" => out.push_str(&variables.port.to_string()),
+                "seed" => out.push_str(variables.seed.as_deref().unwrap_or("")),
+                other => return Err(format!("Unknown template variable ${{{}}}", other)),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    fn resource_folder(&self) -> Result<&'static str, String> {
+        match self.server_type {
+            ServerType::Spigot | ServerType::Paper => Ok("plugins"),
+            ServerType::Forge | ServerType::NeoForge | ServerType::Fabric | ServerType::Quilt => Ok("mods"),
+            ServerType::Vanilla => Err("Vanilla servers cannot load plugins or mods".to_string()),
+        }
+    }
+
+    /// The template's JVM flags as the server's saved JVM options.
+    pub fn jvm_options(&self, variables: &TemplateVariables) -> Result<JvmOptions, String> {
+        let flags = self.jvm_flags
+            .iter()
+            .map(|flag| self.substitute(flag, variables))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(JvmOptions::from_flags(&flags, &self.memory))
+    }
+
+    /// Creates a server at `server_path`: `server.properties` with the template's
+    /// overrides, JVM options and a start script from its memory and JVM flags,
+    /// and its resources.
+    /// Plugin and mod jars go through `scanner`, which refuses blocklisted ones.
+    pub async fn instantiate(&self, server_path: &PathBuf, variables: &TemplateVariables, scanner: &JarScanner) -> Result<TemplateCreation, String> {
+        if server_path.join("server.properties").exists() {
+            return Err("A server already exists at this location".to_string());
+        }
+
+        let mut properties = ServerProperties::new();
+        for (key, value) in &self.properties {
+            properties.set_property(key, &self.substitute(value, variables)?)?;
+        }
+        properties.set_property("server-port", &variables.port.to_string())?;
+        // Check everything before touching the disk.
+        properties.validate()?;
+        let jvm_options = self.jvm_options(variables)?;
+
+        fs::create_dir_all(server_path)
+            .await
+            .map_err(|e| format!("Failed to create server directory: {}", e))?;
+        properties.save_to_file(&server_path.join("server.properties")).await?;
+
+        jvm_options.save(server_path).await?;
+        jvm_options.write_start_script(server_path, &self.memory, None, "java").await?;
+
+        let mut manual_installs = Vec::new();
+        let mut warnings = Vec::new();
+        if !self.plugins.is_empty() {
+            let folder = server_path.join(self.resource_folder()?);
+            manual_installs.extend(download_resources(&self.plugins, &folder, Some((scanner, &mut warnings))).await?);
+        }
+        if !self.datapacks.is_empty() {
+            let level_name = self
+                .properties
+                .get("level-name")
+                .map(|name| self.substitute(name, variables))
+                .transpose()?
+                .unwrap_or_else(|| "world".to_string());
+            let folder = server_path.join(level_name).join("datapacks");
+            manual_installs.extend(download_resources(&self.datapacks, &folder, None).await?);
+        }
+
+        Ok(TemplateCreation {
+            config: ServerConfig {
+                name: variables.name.clone(),
+                version: self.version.clone(),
+                server_type: self.server_type,
+                loader_version: None,
+                path: server_path.clone(),
+                memory: self.memory.clone(),
+                port: variables.port,
+            },
+            manual_installs,
+            warnings,
+        })
+    }
+}
+
+/// Downloads the resources that have a URL into `folder`, returning the names of the rest.
+/// Jars are checked with `scan`'s scanner, and its findings added to its warnings.
+async fn download_resources(resources: &[TemplateResource], folder: &PathBuf, mut scan: Option<(&JarScanner, &mut Vec<String>)>) -> Result<Vec<String>, String> {
+    let mut manual = Vec::new();
+    for resource in resources {
+        let Some(url) = &resource.url else {
+            manual.push(resource.name.clone());
+            continue;
+        };
+
+        let file_name = resource
+            .file_name
+            .clone()
+            .or_else(|| url.rsplit('/').next().map(|s| s.split('?').next().unwrap_or(s).to_string()))
+            .filter(|name| !name.is_empty() && !name.contains(['/', '\\']) && name != "..")
+            .ok_or_else(|| format!("Cannot determine a file name for {}", resource.name))?;
+
+        let response = reqwest::get(url)
+            .await
+            .map_err(|e| format!("Failed to download {}: {}", resource.name, e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to download {}: HTTP {}", resource.name, response.status()));
+        }
+        let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+
+        if let Some((scanner, warnings)) = scan.as_mut() {
+            let target = folder.join(&file_name);
+            let staged = StagedJar::write(bytes.to_vec(), file_name, folder).await?;
+            let mut outcome = PluginActionOutcome::default();
+            let (staged, _) = staged.scan(scanner, &mut outcome).await?;
+            warnings.extend(outcome.warnings);
+            staged.place(&target, None).await?;
+            continue;
+        }
+        fs::create_dir_all(folder).await.map_err(|e| e.to_string())?;
+        fs::write(folder.join(file_name), bytes)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(manual)
+}
+
+fn properties(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+    entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+fn manual(names: &[&str]) -> Vec<TemplateResource> {
+    names
+        .iter()
+        .map(|name| TemplateResource { name: name.to_string(), url: None, file_name: None })
+        .collect()
+}
+
+/// The example templates that ship with MCSM.
+pub fn builtin_templates() -> Vec<ServerTemplate> {
+    let aikar = |max_mb: u32| {
+        let mut flags = aikar_flags(max_mb);
+        flags.push("-XX:+AlwaysPreTouch".to_string());
+        flags
+    };
+    vec![
+        ServerTemplate {
+            id: "survival-smp".to_string(),
+            name: "Survival SMP".to_string(),
+            description: "Whitelisted survival server for a group of friends".to_string(),
+            server_type: ServerType::Paper,
+            version: "1.21.4".to_string(),
+            memory: MemoryConfig { min_mb: 4096, max_mb: 4096 },
+            jvm_flags: aikar(4096),
+            properties: properties(&[
+                ("motd", "${name}"),
+                ("level-seed", "${seed}"),
+                ("difficulty", "hard"),
+                ("view-distance", "10"),
+                ("simulation-distance", "8"),
+                ("white-list", "true"),
+                ("enforce-whitelist", "true"),
+            ]),
+            plugins: manual(&["EssentialsX", "LuckPerms", "CoreProtect"]),
+            datapacks: Vec::new(),
+            builtin: true,
+        },
+        ServerTemplate {
+            id: "creative-build".to_string(),
+            name: "Creative build".to_string(),
+            description: "Flat creative world for building, without mobs or PvP".to_string(),
+            server_type: ServerType::Paper,
+            version: "1.21.4".to_string(),
+            memory: MemoryConfig { min_mb: 2048, max_mb: 4096 },
+            jvm_flags: aikar(4096),
+            properties: properties(&[
+                ("motd", "${name}"),
+                ("level-seed", "${seed}"),
+                ("gamemode", "creative"),
+                ("force-gamemode", "true"),
+                ("difficulty", "peaceful"),
+                ("level-type", "minecraft:flat"),
+                ("generate-structures", "false"),
+                ("spawn-monsters", "false"),
+                ("pvp", "false"),
+            ]),
+            plugins: manual(&["WorldEdit", "WorldGuard"]),
+            datapacks: Vec::new(),
+            builtin: true,
+        },
+        ServerTemplate {
+            id: "minigame-lobby".to_string(),
+            name: "Minigame lobby".to_string(),
+            description: "Adventure-mode hub with many slots and a small view distance".to_string(),
+            server_type: ServerType::Paper,
+            version: "1.21.4".to_string(),
+            memory: MemoryConfig { min_mb: 2048, max_mb: 2048 },
+            jvm_flags: aikar(2048),
+            properties: properties(&[
+                ("motd", "${name}"),
+                ("gamemode", "adventure"),
+                ("force-gamemode", "true"),
+                ("difficulty", "peaceful"),
+                ("max-players", "100"),
+                ("level-type", "minecraft:flat"),
+                ("generate-structures", "false"),
+                ("allow-nether", "false"),
+                ("spawn-monsters", "false"),
+                ("pvp", "false"),
+                ("spawn-protection", "0"),
+                ("view-distance", "6"),
+                ("simulation-distance", "4"),
+            ]),
+            plugins: manual(&["Multiverse-Core"]),
+            datapacks: Vec::new(),
+            builtin: true,
+        },
+    ]
+}
+
+/// Built-in templates plus the user's own, saved as JSON under the app data directory.
+pub struct TemplateManager {
+    templates_path: PathBuf,
+}
+
+impl TemplateManager {
+    pub fn new(data_dir: &PathBuf) -> Self {
+        TemplateManager {
+            templates_path: data_dir.join("templates"),
+        }
+    }
+
+    fn template_file(&self, id: &str) -> Result<PathBuf, String> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("Invalid template id: {}", id));
+        }
+        Ok(self.templates_path.join(format!("{}.json", id)))
+    }
+
+    pub async fn get_all_templates(&self) -> Result<Vec<ServerTemplate>, String> {
+        let mut templates = builtin_templates();
+        if !self.templates_path.exists() {
+            return Ok(templates);
+        }
+
+        let mut entries = fs::read_dir(&self.templates_path)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut custom = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+            if entry.path().extension().and_then(|e| e.to_str()) == Some("json") {
+                let content = fs::read_to_string(entry.path())
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let mut template: ServerTemplate = serde_json::from_str(&content)
+                    .map_err(|e| format!("Invalid template {}: {}", entry.path().display(), e))?;
+                template.builtin = false;
+                custom.push(template);
+            }
+        }
+        custom.sort_by(|a, b| a.name.cmp(&b.name));
+        templates.extend(custom);
+        Ok(templates)
+    }
+
+    pub async fn get_template(&self, id: &str) -> Result<ServerTemplate, String> {
+        self.get_all_templates()
+            .await?
+            .into_iter()
+            .find(|t| t.id == id)
+            .ok_or_else(|| format!("Template {} not found", id))
+    }
+
+    pub async fn save_template(&self, template: &ServerTemplate) -> Result<(), String> {
+        if builtin_templates().iter().any(|t| t.id == template.id) {
+            return Err(format!("{} is a built-in template; save it under a new id", template.id));
+        }
+
+        let path = self.template_file(&template.id)?;
+        fs::create_dir_all(&self.templates_path)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut template = template.clone();
+        template.builtin = false;
+        let content = serde_json::to_string_pretty(&template).map_err(|e| e.to_string())?;
+        fs::write(path, content).await.map_err(|e| e.to_string())
+    }
+
+    pub async fn delete_template(&self, id: &str) -> Result<(), String> {
+        let path = self.template_file(id)?;
+        if !path.exists() {
+            return Err(format!("Template {} not found", id));
+        }
+        fs::remove_file(path).await.map_err(|e| e.to_string())
+    }
+
+    /// Writes a template to a single shareable file.
+    pub async fn export_template(&self, id: &str, destination: &PathBuf) -> Result<(), String> {
+        let file = TemplateFile {
+            format_version: TEMPLATE_FORMAT_VERSION,
+            template: self.get_template(id).await?,
+        };
+        let content = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        fs::write(destination, content).await.map_err(|e| e.to_string())
+    }
+
+    /// Imports an exported template file, saving it as a user template.
+    pub async fn import_template(&self, source: &PathBuf) -> Result<ServerTemplate, String> {
+        let content = fs::read_to_string(source).await.map_err(|e| e.to_string())?;
+        let file: TemplateFile = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid template file: {}", e))?;
+        if file.format_version > TEMPLATE_FORMAT_VERSION {
+            return Err(format!(
+                "Template file version {} is newer than this version of MCSM supports",
+                file.format_version
+            ));
+        }
+
+        self.save_template(&file.template).await?;
+        Ok(file.template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_from_template() {
+        let root = std::env::temp_dir().join("mcsm_test_templates");
+        let _ = std::fs::remove_dir_all(&root);
+        let manager = TemplateManager::new(&root.join("data"));
+
+        let mut template = manager.get_template("survival-smp").await.unwrap();
+        template.id = "my-smp".to_string();
+        template.properties.insert("max-players".to_string(), "12".to_string());
+        manager.save_template(&template).await.unwrap();
+        manager.export_template("my-smp", &root.join("my-smp.json")).await.unwrap();
+        manager.delete_template("my-smp").await.unwrap();
+        assert!(manager.import_template(&root.join("my-smp.json")).await.is_ok());
+        assert!(manager.delete_template("survival-smp").await.is_err());
+
+        let variables = TemplateVariables {
+            name: "Friends SMP".to_string(),
+            port: 25570,
+            seed: Some("12345".to_string()),
+        };
+        let template = manager.get_template("my-smp").await.unwrap();
+        let server_path = root.join("server");
+        let created = template.instantiate(&server_path, &variables, &JarScanner::new()).await.unwrap();
+        assert_eq!(created.config.port, 25570);
+        assert_eq!(created.manual_installs, vec!["EssentialsX", "LuckPerms", "CoreProtect"]);
+
+        let mut properties = ServerProperties::new();
+        properties.load_from_file(&server_path.join("server.properties")).await.unwrap();
+        assert_eq!(properties.get_property("motd").as_deref(), Some("Friends SMP"));
+        assert_eq!(properties.get_property("level-seed").as_deref(), Some("12345"));
+        assert_eq!(properties.get_property("server-port").as_deref(), Some("25570"));
+        assert_eq!(properties.get_property("max-players").as_deref(), Some("12"));
+        assert!(template.instantiate(&server_path, &variables, &JarScanner::new()).await.is_err());
+
+        let script = std::fs::read_to_string(server_path.join("start.sh")).unwrap();
+        assert!(script.contains("java -Xms4096M -Xmx4096M -XX:+UseG1GC"));
+        let jvm_options = JvmOptions::load(&server_path).await.unwrap();
+        assert_eq!(jvm_options.preset, crate::jvm::GcPreset::Aikar);
+        assert!(jvm_options.always_pre_touch);
+
+        let mut broken = template.clone();
+        broken.properties.insert("motd".to_string(), "${owner}".to_string());
+        assert!(broken.instantiate(&root.join("broken"), &variables, &JarScanner::new()).await.is_err());
+    }
+}
@@ -1,19 +1,13 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use serde::{Serialize, Deserialize};
 use tokio::fs;
-use std::process::Command;
 use std::io::{BufReader, BufRead};
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
+use sysinfo::{CpuExt, DiskExt, System, SystemExt};
+use crate::java::{JavaDetector, JavaInfo};
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct JavaInfo {
-    pub version: String,
-    pub path: PathBuf,
-    pub vendor: String,
-    pub is_64bit: bool,
-    pub heap_size: u64,
-}
+const MB: u64 = 1024 * 1024;
 
 pub struct SystemUtils {
     java_versions: Arc<Mutex<Vec<JavaInfo>>>,
@@ -27,139 +21,65 @@ impl SystemUtils {
     }
 
     pub async fn find_java_installations(&self) -> Result<Vec<JavaInfo>, String> {
-        let mut java_versions = Vec::new();
-
-        // Check common Java installation paths
-        let paths = vec![
-            PathBuf::from("C:\\Program Files\\Java"),
-            PathBuf::from("C:\\Program Files (x86)\\Java"),
-            PathBuf::from("/usr/lib/jvm"),
-            PathBuf::from("/Library/Java/JavaVirtualMachines"),
-        ];
-
-        for base_path in paths {
-            if base_path.exists() {
-                let mut dir = fs::read_dir(&base_path)
-                    .await
-                    .map_err(|e| e.to_string())?;
-
-                while let Some(entry) = dir.next_entry().await.map_err(|e| e.to_string())? {
-                    let path = entry.path();
-                    if path.is_dir() {
-                        if let Some(java_info) = Self::check_java_installation(&path).await {
-                            java_versions.push(java_info);
-                        }
-                    }
-                }
-            }
-        }
+        let java_versions = JavaDetector::new().detect().await;
 
-        // Check PATH
-        if let Ok(output) = Command::new("java")
-            .arg("-version")
-            .output()
-        {
-            if let Some(java_info) = Self::parse_java_version(&output.stderr).await {
-                java_versions.push(java_info);
-            }
-        }
-
-        let mut versions = self.java_versions.lock().unwrap();
+        let mut versions = self.java_versions.lock().map_err(|e| e.to_string())?;
         *versions = java_versions.clone();
 
         Ok(java_versions)
     }
 
-    async fn check_java_installation(path: &PathBuf) -> Option<JavaInfo> {
-        let java_path = path.join("bin").join("java");
-        if !java_path.exists() {
-            return None;
-        }
-
-        if let Ok(output) = Command::new(&java_path)
-            .arg("-version")
-            .output()
-        {
-            Self::parse_java_version(&output.stderr).await
-        } else {
-            None
-        }
-    }
-
-    async fn parse_java_version(output: &[u8]) -> Option<JavaInfo> {
-        let output = String::from_utf8_lossy(output);
-        let mut version = None;
-        let mut vendor = None;
-        let mut is_64bit = false;
-
-        for line in output.lines() {
-            if line.contains("version") {
-                version = line.split('"').nth(1).map(|s| s.to_string());
-            }
-            if line.contains("Java(TM)") {
-                vendor = Some("Oracle".to_string());
-            } else if line.contains("OpenJDK") {
-                vendor = Some("OpenJDK".to_string());
-            }
-            if line.contains("64-Bit") {
-                is_64bit = true;
-            }
-        }
-
-        if let (Some(version), Some(vendor)) = (version, vendor) {
-            Some(JavaInfo {
-                version,
-                path: PathBuf::from("java"), // This will be updated with actual path
-                vendor,
-                is_64bit,
-                heap_size: 0, // This will be calculated
-            })
-        } else {
-            None
-        }
-    }
-
     pub async fn get_system_info() -> Result<SystemInfo, String> {
+        let mut system = System::new();
+        system.refresh_cpu();
+        system.refresh_memory();
+        system.refresh_disks_list();
+        system.refresh_disks();
+
         let mut info = SystemInfo::new();
 
-        // Get CPU info
-        if let Ok(output) = Command::new("wmic")
-            .args(&["cpu", "get", "name"])
-            .output()
-        {
-            let output = String::from_utf8_lossy(&output.stdout);
-            info.cpu = output.lines()
-                .nth(1)
-                .map(|s| s.trim().to_string())
-                .unwrap_or_default();
+        info.cpu = system
+            .cpus()
+            .first()
+            .map(|cpu| cpu.brand().trim().to_string())
+            .unwrap_or_default();
+        // Some ARM kernels leave the brand empty; /proc/cpuinfo still names the chip.
+        if info.cpu.is_empty() {
+            if let Ok(cpuinfo) = fs::read_to_string("/proc/cpuinfo").await {
+                info.cpu = parse_cpuinfo_model(&cpuinfo).unwrap_or_default();
+            }
         }
-
-        // Get memory info
-        if let Ok(output) = Command::new("wmic")
-            .args(&["memorychip", "get", "capacity"])
-            .output()
-        {
-            let output = String::from_utf8_lossy(&output.stdout);
-            let total_memory: u64 = output.lines()
-                .skip(1)
-                .filter_map(|s| s.trim().parse::<u64>().ok())
-                .sum();
-            info.total_memory = total_memory / (1024 * 1024 * 1024); // Convert to GB
+        info.logical_cores = system.cpus().len();
+        info.physical_cores = system.physical_core_count();
+
+        info.total_memory = system.total_memory() / MB;
+        info.available_memory = system.available_memory() / MB;
+        info.total_swap = system.total_swap() / MB;
+        info.free_swap = system.free_swap() / MB;
+
+        info.os = system
+            .long_os_version()
+            .or_else(|| system.name())
+            .unwrap_or_else(|| std::env::consts::OS.to_string());
+        info.kernel = system.kernel_version().unwrap_or_default();
+
+        // Not reported on Windows.
+        if !cfg!(target_os = "windows") {
+            let load = system.load_average();
+            info.load_average = Some([load.one, load.five, load.fifteen]);
         }
 
-        // Get OS info
-        if let Ok(output) = Command::new("systeminfo")
-            .output()
-        {
-            let output = String::from_utf8_lossy(&output.stdout);
-            for line in output.lines() {
-                if line.contains("OS Name") {
-                    info.os = line.split(":").nth(1)
-                        .map(|s| s.trim().to_string())
-                        .unwrap_or_default();
-                }
-            }
-        }
+        info.disks = system
+            .disks()
+            .iter()
+            .map(|disk| DiskInfo {
+                name: disk.name().to_string_lossy().to_string(),
+                mount_point: disk.mount_point().to_path_buf(),
+                file_system: String::from_utf8_lossy(disk.file_system()).to_string(),
+                total_space: disk.total_space() / MB,
+                available_space: disk.available_space() / MB,
+            })
+            .collect();
 
         Ok(info)
     }
@@ -179,21 +99,69 @@ impl SystemUtils {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskInfo {
+    pub name: String,
+    pub mount_point: PathBuf,
+    pub file_system: String,
+    /// In megabytes.
+    pub total_space: u64,
+    /// In megabytes.
+    pub available_space: u64,
+}
+
+/// Host details; memory and disk sizes are in megabytes.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SystemInfo {
     pub cpu: String,
+    pub logical_cores: usize,
+    pub physical_cores: Option<usize>,
     pub total_memory: u64,
+    pub available_memory: u64,
+    pub total_swap: u64,
+    pub free_swap: u64,
     pub os: String,
+    pub kernel: String,
+    /// 1, 5 and 15 minute load averages; `None` on Windows.
+    pub load_average: Option<[f64; 3]>,
+    pub disks: Vec<DiskInfo>,
 }
 
 impl SystemInfo {
     pub fn new() -> Self {
         SystemInfo {
             cpu: String::new(),
+            logical_cores: 0,
+            physical_cores: None,
             total_memory: 0,
+            available_memory: 0,
+            total_swap: 0,
+            free_swap: 0,
             os: String::new(),
+            kernel: String::new(),
+            load_average: None,
+            disks: Vec::new(),
         }
     }
+
+    /// The disk holding `path`: the one with the longest mount point containing it.
+    pub fn disk_for(&self, path: &Path) -> Option<&DiskInfo> {
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.disks
+            .iter()
+            .filter(|disk| path.starts_with(&disk.mount_point))
+            .max_by_key(|disk| disk.mount_point.as_os_str().len())
+    }
+}
+
+/// The `model name` (x86) or `Hardware`/`Processor` (ARM) line of `/proc/cpuinfo`.
+fn parse_cpuinfo_model(cpuinfo: &str) -> Option<String> {
+    ["model name", "Hardware", "Processor"].iter().find_map(|field| {
+        cpuinfo.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == *field && !value.trim().is_empty()).then(|| value.trim().to_string())
+        })
+    })
 }
 
 #[cfg(test)]
@@ -209,6 +177,13 @@ mod tests {
         assert!(!system_info.cpu.is_empty());
         assert!(system_info.total_memory > 0);
         assert!(!system_info.os.is_empty());
+        assert!(system_info.logical_cores > 0);
+        assert!(system_info.available_memory <= system_info.total_memory);
+        assert_eq!(
+            parse_cpuinfo_model("processor\t: 0\nmodel name\t: AMD Ryzen 9 7950X 16-Core Processor\n").as_deref(),
+            Some("AMD Ryzen 9 7950X 16-Core Processor")
+        );
+        assert_eq!(parse_cpuinfo_model("Processor\t: 0\nHardware\t: BCM2835\n").as_deref(), Some("BCM2835"));
         
         assert!(SystemUtils::check_port_availability(25565).await);
     }
@@ -0,0 +1,96 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+
+/// A Minecraft release version such as `1.20.4`, `1.21` or `26.1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct MinecraftVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl MinecraftVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        MinecraftVersion { major, minor, patch }
+    }
+
+    /// Parses a release version, ignoring pre-release and release-candidate suffixes
+    /// (`1.20.5-pre1` is treated as `1.20.5`). Snapshots like `24w14a` are rejected.
+    pub fn parse(version: &str) -> Result<Self, String> {
+        let release = version
+            .trim()
+            .split(['-', ' ', '+'])
+            .next()
+            .unwrap_or("");
+
+        let mut parts = release.split('.');
+        let mut next = |required: bool| -> Result<u32, String> {
+            match parts.next() {
+                Some(part) => part
+                    .parse()
+                    .map_err(|_| format!("Invalid Minecraft version: {}", version)),
+                None if required => Err(format!("Invalid Minecraft version: {}", version)),
+                None => Ok(0),
+            }
+        };
+
+        let parsed = MinecraftVersion {
+            major: next(true)?,
+            minor: next(true)?,
+            patch: next(false)?,
+        };
+        if parts.next().is_some() {
+            return Err(format!("Invalid Minecraft version: {}", version));
+        }
+        Ok(parsed)
+    }
+
+    /// Whether this version is in `since..until` (`until` exclusive), with either bound optional.
+    pub fn in_range(&self, since: Option<&str>, until: Option<&str>) -> bool {
+        let after_since = since
+            .and_then(|v| MinecraftVersion::parse(v).ok())
+            .map(|v| *self >= v)
+            .unwrap_or(true);
+        let before_until = until
+            .and_then(|v| MinecraftVersion::parse(v).ok())
+            .map(|v| *self < v)
+            .unwrap_or(true);
+        after_since && before_until
+    }
+}
+
+impl FromStr for MinecraftVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MinecraftVersion::parse(s)
+    }
+}
+
+impl fmt::Display for MinecraftVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.patch == 0 {
+            write!(f, "{}.{}", self.major, self.minor)
+        } else {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_parsing() {
+        assert_eq!(MinecraftVersion::parse("1.20.4").unwrap(), MinecraftVersion::new(1, 20, 4));
+        assert_eq!(MinecraftVersion::parse("1.21").unwrap(), MinecraftVersion::new(1, 21, 0));
+        assert_eq!(MinecraftVersion::parse("1.20.5-pre1").unwrap(), MinecraftVersion::new(1, 20, 5));
+        assert!(MinecraftVersion::parse("24w14a").is_err());
+        assert!(MinecraftVersion::parse("26.1").unwrap() > MinecraftVersion::parse("1.21.10").unwrap());
+        assert!(MinecraftVersion::new(1, 19, 0).in_range(Some("1.19"), Some("1.19.3")));
+        assert!(!MinecraftVersion::new(1, 19, 3).in_range(Some("1.19"), Some("1.19.3")));
+        assert_eq!(MinecraftVersion::new(1, 21, 0).to_string(), "1.21");
+    }
+}
@@ -0,0 +1,608 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::ops::RangeInclusive;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use serde_yaml::Value;
+use tokio::fs;
+use crate::ServerType;
+use crate::property_schema::{IssueSeverity, ValidationIssue};
+use crate::version::MinecraftVersion;
+
+/// A YAML file edited line by line, so comments and layout survive a save.
+/// Reads go through `serde_yaml`; writes replace only the scalar being changed.
+#[derive(Debug, Clone)]
+pub struct YamlDocument {
+    text: String,
+    value: Value,
+}
+
+struct KeyLine {
+    line: usize,
+    indent: usize,
+    path: Vec<String>,
+    /// Byte offset just past the `:` that ends the key.
+    value_start: usize,
+}
+
+impl YamlDocument {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let value = if text.trim().is_empty() {
+            Value::Mapping(Default::default())
+        } else {
+            serde_yaml::from_str(text).map_err(|e| format!("Invalid YAML: {}", e))?
+        };
+        Ok(YamlDocument { text: text.to_string(), value })
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Looks up a dotted path such as `world-settings.default.view-distance`.
+    pub fn get(&self, path: &str) -> Option<&Value> {
+        path.split('.').try_fold(&self.value, |value, key| value.get(key))
+    }
+
+    pub fn get_as<T: DeserializeOwned>(&self, path: &str) -> Option<T> {
+        self.get(path).and_then(|value| serde_yaml::from_value(value.clone()).ok())
+    }
+
+    /// Sets a dotted path, rewriting just that line or inserting the missing keys
+    /// under their closest existing parent.
+    pub fn set(&mut self, path: &str, value: &Value) -> Result<(), String> {
+        let segments: Vec<String> = path.split('.').map(|s| s.to_string()).collect();
+        let formatted = format_value(value)?;
+        let lines: Vec<&str> = self.text.lines().collect();
+        let keys = scan_keys(&lines);
+
+        let mut new_lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        if let Some(found) = keys.iter().find(|k| k.path == segments) {
+            let line = lines[found.line];
+            let (current, comment) = split_comment(&line[found.value_start..]);
+            if current.trim().is_empty() && keys.iter().any(|k| k.path.starts_with(&segments) && k.path.len() > segments.len()) {
+                return Err(format!("{} is a section, not a single value", path));
+            }
+            new_lines[found.line] = format!("{} {}{}", &line[..found.value_start], formatted, comment);
+        } else {
+            let parent = (1..segments.len())
+                .rev()
+                .find_map(|len| keys.iter().find(|k| k.path == segments[..len]));
+
+            let (insert_at, indent, unit, depth) = match parent {
+                Some(parent) => {
+                    let children: Vec<&KeyLine> = keys
+                        .iter()
+                        .filter(|k| k.path.starts_with(&parent.path) && k.path.len() > parent.path.len())
+                        .collect();
+                    let child_indent = children
+                        .iter()
+                        .find(|k| k.path.len() == parent.path.len() + 1)
+                        .map(|k| k.indent)
+                        .unwrap_or(parent.indent + 2);
+                    let last_line = last_block_line(&lines, parent.line, parent.indent);
+                    (last_line + 1, child_indent, child_indent - parent.indent, parent.path.len())
+                }
+                None => (lines.len(), 0, detect_indent_unit(&keys), 0),
+            };
+
+            let mut inserted = Vec::new();
+            for (offset, segment) in segments[depth..].iter().enumerate() {
+                let pad = " ".repeat(indent + offset * unit);
+                if depth + offset + 1 == segments.len() {
+                    inserted.push(format!("{}{}: {}", pad, format_key(segment), formatted));
+                } else {
+                    inserted.push(format!("{}{}:", pad, format_key(segment)));
+                }
+            }
+            new_lines.splice(insert_at..insert_at, inserted);
+        }
+
+        let mut text = new_lines.join("\n");
+        if self.text.ends_with('\n') || self.text.is_empty() {
+            text.push('\n');
+        }
+
+        let updated = YamlDocument::parse(&text)?;
+        if updated.get(path) != Some(value) {
+            return Err(format!("Failed to update {}", path));
+        }
+        *self = updated;
+        Ok(())
+    }
+}
+
+impl fmt::Display for YamlDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+fn scan_keys(lines: &[&str]) -> Vec<KeyLine> {
+    let mut keys = Vec::new();
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut block_scalar_indent: Option<usize> = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(block_indent) = block_scalar_indent {
+            if indent > block_indent {
+                continue;
+            }
+            block_scalar_indent = None;
+        }
+        if trimmed.starts_with('-') || trimmed == "---" {
+            continue;
+        }
+
+        let Some((key, key_len)) = parse_key(trimmed) else {
+            continue;
+        };
+        while stack.last().map(|(i, _)| *i >= indent).unwrap_or(false) {
+            stack.pop();
+        }
+        stack.push((indent, key));
+
+        let value_start = indent + key_len + 1;
+        let (value, _) = split_comment(&line[value_start..]);
+        if value.trim().starts_with('|') || value.trim().starts_with('>') {
+            block_scalar_indent = Some(indent);
+        }
+
+        keys.push(KeyLine {
+            line: index,
+            indent,
+            path: stack.iter().map(|(_, k)| k.clone()).collect(),
+            value_start,
+        });
+    }
+    keys
+}
+
+/// Returns the key and the byte length of the key text (up to, not including, the colon).
+fn parse_key(trimmed: &str) -> Option<(String, usize)> {
+    if let Some(quote) = trimmed.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        let end = trimmed[1..].find(quote)? + 1;
+        let rest = &trimmed[end + 1..];
+        if !rest.starts_with(':') {
+            return None;
+        }
+        return Some((trimmed[1..end].to_string(), end + 1));
+    }
+
+    let bytes = trimmed.as_bytes();
+    for (i, b) in bytes.iter().enumerate() {
+        if *b == b':' && (i + 1 == bytes.len() || bytes[i + 1] == b' ' || bytes[i + 1] == b'\t') {
+            return Some((trimmed[..i].trim_end().to_string(), i));
+        }
+        if *b == b'#' && i > 0 && bytes[i - 1] == b' ' {
+            return None;
+        }
+    }
+    None
+}
+
+/// Splits `value # comment`, ignoring `#` inside quotes. The comment keeps its leading spaces.
+fn split_comment(value: &str) -> (&str, &str) {
+    let mut quote: Option<char> = None;
+    let mut previous = ' ';
+    for (i, c) in value.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' && previous.is_whitespace() => {
+                let start = value[..i].trim_end().len();
+                return (&value[..start], &value[start..]);
+            }
+            None => {}
+        }
+        previous = c;
+    }
+    (value, "")
+}
+
+fn last_block_line(lines: &[&str], start: usize, indent: usize) -> usize {
+    let mut last = start;
+    for (index, line) in lines.iter().enumerate().skip(start + 1) {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if line.len() - trimmed.len() <= indent {
+            break;
+        }
+        last = index;
+    }
+    last
+}
+
+fn detect_indent_unit(keys: &[KeyLine]) -> usize {
+    keys.iter()
+        .map(|k| k.indent)
+        .filter(|i| *i > 0)
+        .min()
+        .unwrap_or(2)
+}
+
+fn format_key(key: &str) -> String {
+    if key.contains(':') || key.contains('#') || key.starts_with(['"', '\'', '-', ' ']) {
+        format!("{:?}", key)
+    } else {
+        key.to_string()
+    }
+}
+
+fn format_value(value: &Value) -> Result<String, String> {
+    match value {
+        Value::Sequence(_) | Value::Mapping(_) => {
+            // JSON is valid flow-style YAML and keeps the edit on one line.
+            serde_json::to_string(value).map_err(|e| e.to_string())
+        }
+        Value::String(s) if s.contains('\n') => Err("Multi-line values are not supported".to_string()),
+        _ => Ok(serde_yaml::to_string(value)
+            .map_err(|e| e.to_string())?
+            .trim_end()
+            .to_string()),
+    }
+}
+
+/// Server configuration files MCSM knows how to edit.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ConfigFileKind {
+    Bukkit,
+    Spigot,
+    PaperGlobal,
+    PaperWorldDefaults,
+}
+
+impl ConfigFileKind {
+    pub fn all() -> [ConfigFileKind; 4] {
+        [
+            ConfigFileKind::Bukkit,
+            ConfigFileKind::Spigot,
+            ConfigFileKind::PaperGlobal,
+            ConfigFileKind::PaperWorldDefaults,
+        ]
+    }
+
+    pub fn relative_path(&self) -> &'static str {
+        match self {
+            ConfigFileKind::Bukkit => "bukkit.yml",
+            ConfigFileKind::Spigot => "spigot.yml",
+            ConfigFileKind::PaperGlobal => "config/paper-global.yml",
+            ConfigFileKind::PaperWorldDefaults => "config/paper-world-defaults.yml",
+        }
+    }
+
+    /// Whether a server of this type and version reads the file at all.
+    pub fn applies_to(&self, server_type: &ServerType, version: Option<&MinecraftVersion>) -> bool {
+        match self {
+            ConfigFileKind::Bukkit | ConfigFileKind::Spigot => {
+                matches!(server_type, ServerType::Spigot | ServerType::Paper)
+            }
+            // Paper split paper.yml into the config/ folder in 1.19.
+            ConfigFileKind::PaperGlobal | ConfigFileKind::PaperWorldDefaults => {
+                *server_type == ServerType::Paper
+                    && version.map(|v| *v >= MinecraftVersion::new(1, 19, 0)).unwrap_or(true)
+            }
+        }
+    }
+}
+
+/// A performance-related setting and the file and path it lives at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceSetting {
+    pub file: ConfigFileKind,
+    pub path: String,
+    pub label: String,
+    pub value: Option<Value>,
+    pub min: f64,
+    pub max: f64,
+    /// Whether the value must be a whole number. Only the merge radii take fractions.
+    pub integer: bool,
+    /// Values other than numbers the file accepts, such as `default`.
+    pub keywords: Vec<String>,
+    pub since: Option<String>,
+}
+
+fn knob(file: ConfigFileKind, path: &str, label: &str, range: RangeInclusive<i32>, integer: bool, keywords: &[&str], since: Option<&str>) -> PerformanceSetting {
+    PerformanceSetting {
+        file,
+        path: path.to_string(),
+        label: label.to_string(),
+        value: None,
+        min: f64::from(*range.start()),
+        max: f64::from(*range.end()),
+        integer,
+        keywords: keywords.iter().map(|k| k.to_string()).collect(),
+        since: since.map(|s| s.to_string()),
+    }
+}
+
+/// The commonly tuned knobs across bukkit.yml, spigot.yml and the Paper configs.
+pub fn performance_knobs() -> Vec<PerformanceSetting> {
+    use ConfigFileKind::*;
+    const SPIGOT_WORLD: &str = "world-settings.default";
+    let spigot = |path: &str| format!("{}.{}", SPIGOT_WORLD, path);
+
+    vec![
+        knob(Bukkit, "chunk-gc.period-in-ticks", "Chunk GC period (ticks)", 0..=72000, true, &[], None),
+        knob(Bukkit, "ticks-per.autosave", "Autosave interval (ticks)", 0..=72000, true, &[], None),
+        knob(Bukkit, "spawn-limits.monsters", "Monster spawn limit", 0..=500, true, &[], None),
+        knob(Bukkit, "spawn-limits.animals", "Animal spawn limit", 0..=500, true, &[], None),
+        knob(Bukkit, "ticks-per.monster-spawns", "Ticks between monster spawns", 0..=6000, true, &[], None),
+        knob(Spigot, &spigot("view-distance"), "View distance", 3..=32, true, &["default"], None),
+        knob(Spigot, &spigot("simulation-distance"), "Simulation distance", 3..=32, true, &["default"], Some("1.18")),
+        knob(Spigot, &spigot("entity-activation-range.animals"), "Animal activation range", 0..=128, true, &[], None),
+        knob(Spigot, &spigot("entity-activation-range.monsters"), "Monster activation range", 0..=128, true, &[], None),
+        knob(Spigot, &spigot("entity-activation-range.raiders"), "Raider activation range", 0..=128, true, &[], None),
+        knob(Spigot, &spigot("entity-activation-range.misc"), "Misc entity activation range", 0..=128, true, &[], None),
+        knob(Spigot, &spigot("entity-activation-range.villagers"), "Villager activation range", 0..=128, true, &[], None),
+        knob(Spigot, &spigot("mob-spawn-range"), "Mob spawn range (chunks)", 1..=8, true, &[], None),
+        knob(Spigot, &spigot("ticks-per.hopper-transfer"), "Hopper transfer rate (ticks)", 1..=200, true, &[], None),
+        knob(Spigot, &spigot("ticks-per.hopper-check"), "Hopper check rate (ticks)", 1..=200, true, &[], None),
+        knob(Spigot, &spigot("hopper-amount"), "Items per hopper transfer", 1..=64, true, &[], None),
+        knob(Spigot, &spigot("merge-radius.item"), "Item merge radius", 0..=16, false, &[], None),
+        knob(Spigot, &spigot("merge-radius.exp"), "Experience merge radius", -1..=16, false, &[], None),
+        knob(PaperWorldDefaults, "chunks.auto-save-interval", "Chunk autosave interval (ticks)", -1..=72000, true, &["default"], None),
+        knob(PaperWorldDefaults, "chunks.delay-chunk-unloads-by", "Delay chunk unloads by", 0..=600, true, &[], None),
+        knob(PaperWorldDefaults, "chunks.max-auto-save-chunks-per-tick", "Max autosaved chunks per tick", 1..=1000, true, &[], None),
+        knob(PaperWorldDefaults, "tick-rates.grass-spread", "Grass spread tick rate", 1..=1000, true, &[], None),
+        knob(PaperWorldDefaults, "hopper.ignore-occluding-blocks", "Hoppers ignore occluding blocks", 0..=1, true, &["true", "false"], None),
+        knob(PaperWorldDefaults, "hopper.disable-move-event", "Disable hopper move event", 0..=1, true, &["true", "false"], None),
+        knob(PaperGlobal, "chunk-loading-basic.player-max-chunk-load-rate", "Max chunk loads per player per second", -1..=10000, true, &[], Some("1.20")),
+        knob(PaperGlobal, "chunk-system.worker-threads", "Chunk worker threads", -1..=256, true, &[], None),
+    ]
+}
+
+impl PerformanceSetting {
+    pub fn check(&self, value: &Value) -> Option<String> {
+        let keyword = match value {
+            Value::String(s) => Some(s.clone()),
+            Value::Bool(b) => Some(b.to_string()),
+            _ => None,
+        };
+        if let Some(keyword) = keyword {
+            if self.keywords.contains(&keyword) {
+                return None;
+            }
+            return Some(format!("{} must be a number{}", self.path, if self.keywords.is_empty() {
+                String::new()
+            } else {
+                format!(" or one of {}", self.keywords.join(", "))
+            }));
+        }
+
+        match value.as_f64() {
+            Some(n) if self.integer && n.fract() != 0.0 => Some(format!("{} must be a whole number, got {}", self.path, n)),
+            Some(n) if n < self.min || n > self.max => {
+                Some(format!("{} must be between {} and {}, got {}", self.path, self.min, self.max, n))
+            }
+            Some(_) => None,
+            None => Some(format!("{} has an unsupported value", self.path)),
+        }
+    }
+}
+
+/// Loads and saves the YAML configs of one server, checked against its type and version.
+pub struct ServerConfigFiles {
+    server_path: PathBuf,
+    server_type: ServerType,
+    version: Option<MinecraftVersion>,
+}
+
+impl ServerConfigFiles {
+    pub fn new(server_path: &PathBuf, server_type: ServerType, version: Option<MinecraftVersion>) -> Self {
+        ServerConfigFiles {
+            server_path: server_path.clone(),
+            server_type,
+            version,
+        }
+    }
+
+    pub fn path_of(&self, kind: ConfigFileKind) -> PathBuf {
+        self.server_path.join(kind.relative_path())
+    }
+
+    /// Config files this server uses that exist on disk.
+    pub fn available_files(&self) -> Vec<ConfigFileKind> {
+        ConfigFileKind::all()
+            .into_iter()
+            .filter(|kind| kind.applies_to(&self.server_type, self.version.as_ref()))
+            .filter(|kind| self.path_of(*kind).exists())
+            .collect()
+    }
+
+    pub async fn load(&self, kind: ConfigFileKind) -> Result<YamlDocument, String> {
+        if !kind.applies_to(&self.server_type, self.version.as_ref()) {
+            return Err(format!("{} is not used by {:?} servers", kind.relative_path(), self.server_type));
+        }
+
+        let path = self.path_of(kind);
+        let content = fs::read_to_string(&path)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", kind.relative_path(), e))?;
+        YamlDocument::parse(&content)
+            .map_err(|e| format!("{}: {}", kind.relative_path(), e))
+    }
+
+    pub async fn save(&self, kind: ConfigFileKind, document: &YamlDocument) -> Result<(), String> {
+        fs::write(self.path_of(kind), document.to_string())
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Current values of every knob that applies to this server.
+    pub async fn performance_settings(&self) -> Result<Vec<PerformanceSetting>, String> {
+        let mut settings = Vec::new();
+        for kind in self.available_files() {
+            let document = self.load(kind).await?;
+            for mut knob in performance_knobs().into_iter().filter(|k| k.file == kind) {
+                if !self.knob_supported(&knob) {
+                    continue;
+                }
+                knob.value = document.get(&knob.path).cloned();
+                settings.push(knob);
+            }
+        }
+        Ok(settings)
+    }
+
+    pub fn validate(&self, changes: &[(ConfigFileKind, String, Value)]) -> Vec<ValidationIssue> {
+        let knobs = performance_knobs();
+        let mut issues = Vec::new();
+        for (kind, path, value) in changes {
+            if !kind.applies_to(&self.server_type, self.version.as_ref()) {
+                issues.push(ValidationIssue {
+                    key: path.clone(),
+                    message: format!("{} is not used by {:?} servers", kind.relative_path(), self.server_type),
+                    severity: IssueSeverity::Error,
+                });
+                continue;
+            }
+            let Some(knob) = knobs.iter().find(|k| k.file == *kind && k.path == *path) else {
+                continue;
+            };
+            if !self.knob_supported(knob) {
+                issues.push(ValidationIssue {
+                    key: path.clone(),
+                    message: format!("{} needs Minecraft {} or newer", path, knob.since.as_deref().unwrap_or("?")),
+                    severity: IssueSeverity::Warning,
+                });
+            }
+            if let Some(message) = knob.check(value) {
+                issues.push(ValidationIssue {
+                    key: path.clone(),
+                    message,
+                    severity: IssueSeverity::Error,
+                });
+            }
+        }
+        issues
+    }
+
+    /// Validates and writes a batch of changes, touching only the edited lines.
+    pub async fn apply_changes(&self, changes: &[(ConfigFileKind, String, Value)]) -> Result<Vec<ValidationIssue>, String> {
+        let issues = self.validate(changes);
+        let errors: Vec<&str> = issues
+            .iter()
+            .filter(|i| i.severity == IssueSeverity::Error)
+            .map(|i| i.message.as_str())
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
+
+        for kind in ConfigFileKind::all() {
+            let file_changes: Vec<_> = changes.iter().filter(|(k, _, _)| *k == kind).collect();
+            if file_changes.is_empty() {
+                continue;
+            }
+            let mut document = self.load(kind).await?;
+            for (_, path, value) in file_changes {
+                document.set(path, value)?;
+            }
+            self.save(kind, &document).await?;
+        }
+
+        Ok(issues)
+    }
+
+    fn knob_supported(&self, knob: &PerformanceSetting) -> bool {
+        match &self.version {
+            Some(version) => version.in_range(knob.since.as_deref(), None),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPIGOT: &str = "# This is the main configuration file for Spigot.
+config-version: 12
+settings:
+  debug: false # verbose logging
+  'netty-threads': 4
+world-settings:
+  default:
+    view-distance: default
+    entity-activation-range:
+      animals: 32
+      monsters: 32
+    ticks-per:
+      hopper-transfer: 8 # lower is faster
+    verbose: false
+";
+
+    #[test]
+    fn test_yaml_edit_preserves_comments() {
+        let mut document = YamlDocument::parse(SPIGOT).unwrap();
+        assert_eq!(document.get_as::<i64>("world-settings.default.entity-activation-range.animals"), Some(32));
+        assert_eq!(document.get_as::<i64>("settings.netty-threads"), Some(4));
+
+        document.set("world-settings.default.entity-activation-range.animals", &Value::from(16)).unwrap();
+        document.set("world-settings.default.ticks-per.hopper-transfer", &Value::from(16)).unwrap();
+        document.set("world-settings.default.ticks-per.hopper-check", &Value::from(4)).unwrap();
+        document.set("world-settings.default.merge-radius.item", &Value::from(3.5)).unwrap();
+        document.set("settings.bungeecord", &Value::from(true)).unwrap();
+
+        let text = document.to_string();
+        assert!(text.starts_with("# This is the main configuration file for Spigot.\n"));
+        assert!(text.contains("      animals: 16\n      monsters: 32\n"));
+        assert!(text.contains("      hopper-transfer: 16 # lower is faster\n      hopper-check: 4\n"));
+        assert!(text.contains("    merge-radius:\n      item: 3.5\n"));
+        assert!(text.contains("  debug: false # verbose logging\n"));
+        assert_eq!(document.get_as::<bool>("settings.bungeecord"), Some(true));
+        assert!(document.set("world-settings.default", &Value::from(1)).is_err());
+    }
+
+    #[test]
+    fn test_knob_validation() {
+        let files = ServerConfigFiles::new(&PathBuf::from("test_server"), ServerType::Paper, Some(MinecraftVersion::new(1, 17, 1)));
+        let issues = files.validate(&[
+            (ConfigFileKind::Spigot, "world-settings.default.view-distance".to_string(), Value::from(64)),
+            (ConfigFileKind::Spigot, "world-settings.default.simulation-distance".to_string(), Value::from(8)),
+            (ConfigFileKind::PaperGlobal, "chunk-system.worker-threads".to_string(), Value::from(2)),
+        ]);
+        assert!(issues.iter().any(|i| i.key.ends_with("view-distance") && i.severity == IssueSeverity::Error));
+        assert!(issues.iter().any(|i| i.key.ends_with("simulation-distance") && i.severity == IssueSeverity::Warning));
+        assert!(issues.iter().any(|i| i.key == "chunk-system.worker-threads" && i.severity == IssueSeverity::Error));
+
+        let vanilla = ServerConfigFiles::new(&PathBuf::from("test_server"), ServerType::Vanilla, None);
+        assert!(vanilla.available_files().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fractional_knobs() {
+        let server_path = std::env::temp_dir().join("mcsm_test_fractional_knobs");
+        let _ = std::fs::remove_dir_all(&server_path);
+        std::fs::create_dir_all(&server_path).unwrap();
+        std::fs::write(
+            server_path.join("spigot.yml"),
+            "world-settings:\n  default:\n    merge-radius:\n      item: 2.5\n      exp: 3.0\n",
+        ).unwrap();
+
+        let files = ServerConfigFiles::new(&server_path, ServerType::Spigot, Some(MinecraftVersion::new(1, 21, 4)));
+        let settings = files.performance_settings().await.unwrap();
+        for path in ["world-settings.default.merge-radius.item", "world-settings.default.merge-radius.exp"] {
+            let setting = settings.iter().find(|s| s.path == path).unwrap();
+            assert_eq!(setting.check(setting.value.as_ref().unwrap()), None);
+        }
+
+        let issues = files.validate(&[
+            (ConfigFileKind::Spigot, "world-settings.default.merge-radius.item".to_string(), Value::from(16.5)),
+        ]);
+        assert_eq!(issues[0].message, "world-settings.default.merge-radius.item must be between 0 and 16, got 16.5");
+        let issues = files.validate(&[
+            (ConfigFileKind::Spigot, "world-settings.default.view-distance".to_string(), Value::from(10.5)),
+        ]);
+        assert_eq!(issues[0].message, "world-settings.default.view-distance must be a whole number, got 10.5");
+    }
+}